@@ -0,0 +1,148 @@
+// benches/board_bench.rs
+//
+// Headless benchmarks for the board engine. No window is opened,
+// so these run fine in CI. Run with `cargo bench`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use gameover2025::{
+    config::BoardConfig,
+    models::{Board, PieceType},
+    views::{BoardInstance, BoardPosition, GameState, PieceInstance, PlayerInput},
+};
+use nannou::{
+    prelude::*,
+    rand::{rngs::StdRng, Rng, SeedableRng},
+};
+
+const SEED: u64 = 2025;
+const PIECE_COUNT: u64 = 500;
+const DT: f32 = 1.0 / 60.0;
+
+// Same board size as config.toml
+fn bench_config() -> BoardConfig {
+    BoardConfig {
+        width: 16,
+        height: 24,
+        ..BoardConfig::default()
+    }
+}
+
+// Drop `count` pieces through the full update path, steered by seeded random input.
+// Boards that top out are restarted.
+fn drop_pieces(count: u64, seed: u64) -> usize {
+    let mut board = BoardInstance::new("bench", vec2(0.0, 0.0), &bench_config(), seed).unwrap();
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut spawned = 0;
+
+    while spawned < count {
+        let input = match board.game_state() {
+            GameState::Ready => {
+                spawned += 1;
+                None
+            }
+            GameState::Falling => Some(match rng.gen_range(0..4) {
+                0 => PlayerInput::L,
+                1 => PlayerInput::R,
+                2 => PlayerInput::Rotate,
+                _ => PlayerInput::HardDrop,
+            }),
+            GameState::Frozen => {
+                board.restart(rng.gen());
+                None
+            }
+            _ => None,
+        };
+
+        board.update(DT, input.as_slice());
+    }
+
+    board.score()
+}
+
+// A board with a ragged stack up to half its height,
+// low enough that every piece fits at the top
+fn half_filled_board(seed: u64) -> Board {
+    let config = bench_config();
+    let mut board = Board::new(config.width, config.height).unwrap();
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    while board.col_score_all().iter().all(|&h| h < board.height / 2) {
+        let mut piece = test_piece(PieceType::from_idx(rng.gen_range(0..7)));
+        piece.rot_idx = rng.gen_range(0..4);
+        let (min_x, max_x) = board.pieces().minmax_x(piece.typ, piece.rot_idx);
+        piece.position.x = rng.gen_range(-min_x..board.width - max_x);
+
+        let (drop_pos, _) = board.calculate_drop(&piece);
+        piece.position = drop_pos;
+        if let Some(rows) = board.commit_piece(&piece) {
+            board.clear_rows(&rows);
+        }
+    }
+
+    board
+}
+
+fn test_piece(typ: PieceType) -> PieceInstance {
+    let position = BoardPosition {
+        x: 0,
+        y: bench_config().height as isize - bench_config().pieces.max_y(typ, 0) - 1,
+    };
+    PieceInstance::new(typ, rgba(1.0, 1.0, 1.0, 1.0), position)
+}
+
+// Every piece type, in every rotation, at every column it fits in
+fn all_placements(board: &Board) -> Vec<PieceInstance> {
+    let mut pieces = Vec::new();
+    for idx in 0..7 {
+        let typ = PieceType::from_idx(idx);
+        for rot_idx in 0..board.pieces().rotation_count(typ) {
+            let (min_x, max_x) = board.pieces().minmax_x(typ, rot_idx);
+            for x in -min_x..board.width - max_x {
+                let mut piece = test_piece(typ);
+                piece.rot_idx = rot_idx;
+                piece.position = BoardPosition {
+                    x,
+                    y: board.height - board.pieces().max_y(typ, rot_idx) - 1,
+                };
+                pieces.push(piece);
+            }
+        }
+    }
+    pieces
+}
+
+fn bench_update(c: &mut Criterion) {
+    let mut group = c.benchmark_group("update");
+    group.throughput(Throughput::Elements(PIECE_COUNT));
+    group.sample_size(20);
+    group.bench_function("drop_pieces", |b| {
+        b.iter(|| drop_pieces(black_box(PIECE_COUNT), SEED))
+    });
+    group.finish();
+}
+
+fn bench_placement(c: &mut Criterion) {
+    let mut board = half_filled_board(SEED);
+    let pieces = all_placements(&board);
+
+    let mut group = c.benchmark_group("placement");
+    group.throughput(Throughput::Elements(pieces.len() as u64));
+    group.bench_function("try_place", |b| {
+        b.iter(|| {
+            for piece in &pieces {
+                black_box(board.try_place(piece, piece.position));
+            }
+        })
+    });
+    group.bench_function("calculate_drop", |b| {
+        b.iter(|| {
+            for piece in &pieces {
+                black_box(board.calculate_drop(piece));
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_update, bench_placement);
+criterion_main!(benches);
@@ -0,0 +1,69 @@
+// benches/draw_bench.rs
+//
+// Compares drawing board cells one rect at a time against batching them
+// into a CellMesh. Only builds the Draw commands, so no window or GPU is needed.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use gameover2025::views::CellMesh;
+use nannou::prelude::*;
+
+// A wall of full-size boards, every cell filled
+const BOARD_COUNT: usize = 8;
+const BOARD_WIDTH: usize = 16;
+const BOARD_HEIGHT: usize = 24;
+const CELL_SIZE: f32 = 30.0;
+const STROKE_WEIGHT: f32 = 1.5;
+
+fn cell_centers() -> Vec<Vec2> {
+    let mut centers = Vec::with_capacity(BOARD_COUNT * BOARD_WIDTH * BOARD_HEIGHT);
+    for board in 0..BOARD_COUNT {
+        let board_x = board as f32 * (BOARD_WIDTH as f32 + 2.0) * CELL_SIZE;
+        for y in 0..BOARD_HEIGHT {
+            for x in 0..BOARD_WIDTH {
+                centers.push(vec2(board_x + x as f32 * CELL_SIZE, y as f32 * CELL_SIZE));
+            }
+        }
+    }
+    centers
+}
+
+fn bench_draw(c: &mut Criterion) {
+    let centers = cell_centers();
+    let color: Rgba = hsva(40.0 / 360.0, 1.0, 0.7, 1.0).into();
+    let draw = Draw::new();
+
+    let mut group = c.benchmark_group("draw_cells");
+    group.throughput(Throughput::Elements(centers.len() as u64));
+
+    group.bench_function("per_cell", |b| {
+        b.iter(|| {
+            draw.reset();
+            for &center in &centers {
+                draw.rect()
+                    .xy(center)
+                    .w_h(CELL_SIZE, CELL_SIZE)
+                    .color(color)
+                    .stroke_weight(STROKE_WEIGHT)
+                    .stroke(BLACK);
+            }
+            black_box(&draw);
+        })
+    });
+
+    group.bench_function("batched", |b| {
+        b.iter(|| {
+            draw.reset();
+            let mut mesh = CellMesh::with_capacity(CELL_SIZE, STROKE_WEIGHT, centers.len());
+            for &center in &centers {
+                mesh.push(center, color);
+            }
+            mesh.draw(&draw);
+            black_box(&draw);
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_draw);
+criterion_main!(benches);
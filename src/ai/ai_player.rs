@@ -0,0 +1,432 @@
+// src/ai/ai_player.rs
+//
+// A simple computer player for demo games.
+// Picks the best reachable placement for the active piece, looking ahead to
+// the next piece, and steers it there one input at a time.
+
+use crate::{
+    ai::{Reachable, Step},
+    config::{AiSkill, AiWeights},
+    models::{Board, PieceShape, PieceType},
+    views::{BoardInstance, BoardPosition, GameState, PlayerInput},
+};
+use nannou::rand::{rngs::StdRng, Rng, SeedableRng};
+
+// Placements of the active piece searched again with the next piece
+const LOOKAHEAD_BRANCHES: usize = 8;
+// Score for a placement that leaves no room for the next piece
+const TOP_OUT_PENALTY: f32 = -1000.0;
+
+// A mistake picks one of this many runners-up to the best placement
+const MISTAKE_CHOICES: usize = 4;
+
+// Where the active piece should lock
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Placement {
+    pub rot_idx: usize,
+    pub x: isize,
+    pub y: isize,
+}
+
+impl Placement {
+    fn of(piece: &PieceShape) -> Self {
+        Self {
+            rot_idx: piece.rot_idx,
+            x: piece.position.x,
+            y: piece.position.y,
+        }
+    }
+
+    // The piece moved into this placement
+    fn apply(&self, piece: &PieceShape) -> PieceShape {
+        let mut placed = *piece;
+        placed.rot_idx = self.rot_idx;
+        placed.position = BoardPosition {
+            x: self.x,
+            y: self.y,
+        };
+        placed
+    }
+}
+
+#[derive(Debug)]
+pub struct AiPlayer {
+    weights: AiWeights, // how this player judges a board
+    skill: AiSkill,     // how often it errs, and how fast it reacts
+    rng: StdRng,        // seeded, so mistakes are reproducible
+
+    target: Option<Placement>,
+    cooldown: f32, // seconds until the next input may be sent
+}
+
+impl AiPlayer {
+    pub fn new() -> Self {
+        Self::with_weights(AiWeights::default())
+    }
+
+    pub fn with_weights(weights: AiWeights) -> Self {
+        Self::with_skill(weights, AiSkill::default(), 0)
+    }
+
+    pub fn with_skill(weights: AiWeights, skill: AiSkill, seed: u64) -> Self {
+        Self {
+            weights,
+            skill,
+            rng: StdRng::seed_from_u64(seed),
+
+            target: None,
+            cooldown: 0.0,
+        }
+    }
+
+    // Decide the next input for the board, called once per frame
+    pub fn next_input(&mut self, dt: f32, board: &BoardInstance) -> Option<PlayerInput> {
+        // Only steer a piece that can still move; the board handles everything else.
+        // A landed piece can still be tucked in before it locks.
+        if !matches!(
+            board.game_state(),
+            GameState::Falling | GameState::Locking { now: false, .. }
+        ) {
+            self.reset();
+            return None;
+        }
+
+        // React no faster than the reaction delay allows
+        if self.cooldown > 0.0 {
+            self.cooldown -= dt;
+            return None;
+        }
+
+        let input = self.steer(board);
+        if input.is_some() {
+            self.cooldown = self.skill.reaction_delay;
+        }
+        input
+    }
+
+    fn steer(&mut self, board: &BoardInstance) -> Option<PlayerInput> {
+        let piece = board.active_piece()?;
+
+        let target = match self.target {
+            Some(target) => target,
+            None => {
+                let target = self.choose_target(board, piece)?;
+                self.target = Some(target);
+                target
+            }
+        };
+
+        // Plan again from wherever gravity has taken the piece
+        let reachable = Reachable::search(board.board(), piece);
+        let Some(path) = reachable.path_to(&target.apply(piece)) else {
+            // Fell past the way there; pick another spot
+            self.target = None;
+            return None;
+        };
+
+        match path.first() {
+            Some(Step::Rotate) => Some(PlayerInput::Rotate),
+            Some(Step::Left) => Some(PlayerInput::L),
+            Some(Step::Right) => Some(PlayerInput::R),
+            // Waiting for gravity before a tuck or spin
+            Some(Step::Down) if path.iter().any(|&step| step != Step::Down) => None,
+            // Only falling left: drop it, unless it has already landed
+            Some(Step::Down) | None => {
+                (board.game_state() == GameState::Falling).then_some(PlayerInput::HardDrop)
+            }
+        }
+    }
+
+    // Forget the current piece. The next one takes a moment to react to.
+    pub fn reset(&mut self) {
+        self.target = None;
+        self.cooldown = self.skill.reaction_delay;
+    }
+
+    // Usually the best placement; now and then one of the runners-up
+    fn choose_target(&mut self, board: &BoardInstance, piece: &PieceShape) -> Option<Placement> {
+        let ranked = self.ranked_placements(board.board(), piece, Some(board.next_piece()));
+        let best = ranked.first().map(|&(_, placement)| placement)?;
+
+        let runners_up = ranked.len().min(MISTAKE_CHOICES + 1) - 1;
+        if runners_up > 0 && self.rng.gen::<f32>() < self.skill.mistake_rate {
+            let (_, placement) = ranked[self.rng.gen_range(1..=runners_up)];
+            return Some(placement);
+        }
+
+        Some(best)
+    }
+
+    /************************ Placement search *******************************/
+
+    // Best placement for the piece. With a next piece, the best few placements
+    // are scored by the best board reachable after placing the next piece too.
+    pub fn best_placement(
+        &self,
+        board: &Board,
+        piece: &PieceShape,
+        next: Option<PieceType>,
+    ) -> Option<Placement> {
+        self.ranked_placements(board, piece, next)
+            .first()
+            .map(|&(_, placement)| placement)
+    }
+
+    // Placements with their scores, best first
+    fn ranked_placements(
+        &self,
+        board: &Board,
+        piece: &PieceShape,
+        next: Option<PieceType>,
+    ) -> Vec<(f32, Placement)> {
+        let mut candidates: Vec<(f32, Placement, Board, usize)> = placements(board, piece)
+            .map(|(placement, landed)| {
+                let (sim, lines) = land_piece(board, &landed);
+                (self.evaluate(&sim, lines), placement, sim, lines)
+            })
+            .collect();
+        candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let Some(next) = next else {
+            return candidates
+                .into_iter()
+                .map(|(score, placement, ..)| (score, placement))
+                .collect();
+        };
+
+        // Only the most promising placements get the second search
+        candidates.truncate(LOOKAHEAD_BRANCHES);
+        let mut ranked = Vec::with_capacity(candidates.len());
+        for (score, placement, sim, lines) in candidates {
+            let next_piece = spawn_piece(&sim, next);
+            let combined = placements(&sim, &next_piece)
+                .map(|(_, landed)| {
+                    let (sim, next_lines) = land_piece(&sim, &landed);
+                    self.evaluate(&sim, lines + next_lines)
+                })
+                .max_by(f32::total_cmp)
+                // Nowhere to put the next piece is as bad as it gets
+                .unwrap_or(score + TOP_OUT_PENALTY);
+
+            ranked.push((combined, placement));
+        }
+
+        ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
+        ranked
+    }
+
+    // Score a board after `lines` were cleared to reach it
+    fn evaluate(&self, board: &Board, lines: usize) -> f32 {
+        let weights = &self.weights;
+        weights.aggregate_height * aggregate_height(board) as f32
+            + weights.lines * lines as f32
+            + weights.holes * board.hole_count() as f32
+            + weights.bumpiness * bumpiness(board) as f32
+            + weights.well_depth * well_depth(board) as f32
+    }
+}
+
+// Base weights with each one scaled by a random factor within +/- spread.
+// The same seed always gives the same personality.
+pub fn personality(base: &AiWeights, spread: f32, seed: u64) -> AiWeights {
+    if spread <= 0.0 {
+        return *base;
+    }
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut vary = |weight: f32| weight * (1.0 + rng.gen_range(-spread..=spread));
+    AiWeights {
+        aggregate_height: vary(base.aggregate_height),
+        lines: vary(base.lines),
+        holes: vary(base.holes),
+        bumpiness: vary(base.bumpiness),
+        well_depth: vary(base.well_depth),
+    }
+}
+
+// Everywhere the piece can reach and lock, tucks and spins included
+fn placements(board: &Board, piece: &PieceShape) -> impl Iterator<Item = (Placement, PieceShape)> {
+    Reachable::search(board, piece)
+        .placements()
+        .into_iter()
+        .map(|landed| (Placement::of(&landed), landed))
+}
+
+// The next piece as it will spawn, once the current one has landed
+fn spawn_piece(board: &Board, typ: PieceType) -> PieceShape {
+    let position = BoardPosition {
+        x: board.midpoint_x() - board.pieces().max_x(typ, 0) / 2,
+        y: board.height - board.pieces().max_y(typ, 0) - 1,
+    };
+    PieceShape::new(typ, position)
+}
+
+// Lock a landed piece and clear lines. Returns the resulting board and lines cleared.
+fn land_piece(board: &Board, piece: &PieceShape) -> (Board, usize) {
+    let mut sim = board.clone();
+    let lines = match sim.commit_piece(piece) {
+        Some(rows) => {
+            sim.clear_rows(&rows);
+            rows.len()
+        }
+        None => 0,
+    };
+    (sim, lines)
+}
+
+impl Default for AiPlayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/************************ Board features *******************************/
+
+fn aggregate_height(board: &Board) -> isize {
+    board.col_score_all().iter().sum()
+}
+
+// How far each column sits below both neighbours, the walls counting as full height
+fn well_depth(board: &Board) -> isize {
+    let heights = board.col_score_all();
+    let wall = board.height;
+    (0..heights.len())
+        .map(|x| {
+            let left = if x == 0 { wall } else { heights[x - 1] };
+            let right = heights.get(x + 1).copied().unwrap_or(wall);
+            (left.min(right) - heights[x]).max(0)
+        })
+        .sum()
+}
+
+fn bumpiness(board: &Board) -> isize {
+    board
+        .col_score_all()
+        .windows(2)
+        .map(|pair| (pair[0] - pair[1]).abs())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::BoardConfig;
+    use nannou::prelude::*;
+
+    // Rows listed top first, '#' for a filled cell
+    fn board_from_rows(width: usize, height: usize, rows: &[&str]) -> Board {
+        let mut board = Board::new(width, height).unwrap();
+        let rows: Vec<Vec<bool>> = rows
+            .iter()
+            .rev()
+            .map(|row| row.chars().map(|cell| cell == '#').collect())
+            .collect();
+        board.add_garbage(&rows);
+        board
+    }
+
+    fn place(board: &Board, piece: &PieceShape, placement: Placement) -> Board {
+        land_piece(board, &placement.apply(piece)).0
+    }
+
+    #[test]
+    fn test_lookahead_avoids_greedy_hole() {
+        // A one-wide well between two towers, then open floor
+        let board = board_from_rows(6, 10, &["#.#...", "#.#...", "#.#..."]);
+        let piece = spawn_piece(&board, PieceType::J);
+        assert_eq!(board.hole_count(), 0);
+
+        // On its own, the best scoring J leaves a hole
+        let ai = AiPlayer::new();
+        let greedy = ai.best_placement(&board, &piece, None).unwrap();
+        assert!(place(&board, &piece, greedy).hole_count() > 0);
+
+        // Knowing an O comes next, it keeps the board clean
+        let lookahead = ai
+            .best_placement(&board, &piece, Some(PieceType::O))
+            .unwrap();
+        assert_ne!(lookahead, greedy);
+        assert_eq!(place(&board, &piece, lookahead).hole_count(), 0);
+    }
+
+    #[test]
+    fn test_weights_change_the_chosen_move() {
+        let board = board_from_rows(6, 10, &["#.#...", "#.#...", "#.#..."]);
+        let piece = spawn_piece(&board, PieceType::J);
+
+        // One player minds holes far more than the default does
+        let careful = AiPlayer::with_weights(AiWeights {
+            holes: -10.0,
+            ..AiWeights::default()
+        });
+        let default = AiPlayer::new();
+
+        let careful_move = careful.best_placement(&board, &piece, None).unwrap();
+        let default_move = default.best_placement(&board, &piece, None).unwrap();
+        assert_ne!(careful_move, default_move);
+        assert_eq!(place(&board, &piece, careful_move).hole_count(), 0);
+
+        // Personalities vary per seed, and are reproducible
+        let base = AiWeights::default();
+        assert_eq!(personality(&base, 0.2, 1), personality(&base, 0.2, 1));
+        assert_ne!(personality(&base, 0.2, 1), personality(&base, 0.2, 2));
+        assert_eq!(personality(&base, 0.0, 1), base);
+    }
+
+    fn falling_board(seed: u64) -> BoardInstance {
+        let config = BoardConfig::default();
+        let mut board = BoardInstance::new("board1", vec2(0.0, 0.0), &config, seed).unwrap();
+        board.update(1.0 / 60.0, &[]);
+        board
+    }
+
+    // Whether the AI's first target for a fresh board differs from the best placement
+    fn deviates(skill: AiSkill, ai_seed: u64, board_seed: u64) -> bool {
+        let board = falling_board(board_seed);
+        let mut ai = AiPlayer::with_skill(AiWeights::default(), skill, ai_seed);
+        ai.next_input(1.0 / 60.0, &board);
+
+        let piece = board.active_piece().unwrap();
+        let best = ai.best_placement(board.board(), piece, Some(board.next_piece()));
+        ai.target != best
+    }
+
+    #[test]
+    fn test_mistake_rate() {
+        let perfect = AiSkill {
+            mistake_rate: 0.0,
+            reaction_delay: 0.0,
+        };
+        let sloppy = AiSkill {
+            mistake_rate: 0.8,
+            reaction_delay: 0.0,
+        };
+
+        assert!((0..20).all(|seed| !deviates(perfect, seed, seed)));
+        assert!((0..20).any(|seed| deviates(sloppy, seed, seed)));
+
+        // Same seeds, same mistakes
+        let run = |seed| deviates(sloppy, seed, 3);
+        assert_eq!(
+            (0..20).map(run).collect::<Vec<_>>(),
+            (0..20).map(run).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_reaction_delay() {
+        let board = falling_board(1);
+        let skill = AiSkill {
+            mistake_rate: 0.0,
+            reaction_delay: 0.1,
+        };
+        let mut ai = AiPlayer::with_skill(AiWeights::default(), skill, 0);
+
+        // Inputs come no more often than every 0.1s
+        let dt = 0.025;
+        let inputs = (0..8)
+            .filter(|_| ai.next_input(dt, &board).is_some())
+            .count();
+        assert_eq!(inputs, 2);
+    }
+}
@@ -0,0 +1,7 @@
+// src/ai/mod.rs
+
+pub mod ai_player;
+pub mod reachability;
+
+pub use ai_player::{personality, AiPlayer, Placement};
+pub use reachability::{Reachable, Step};
@@ -0,0 +1,240 @@
+// src/ai/reachability.rs
+//
+// Where a piece can really end up. Searches every position and rotation
+// the player's moves reach from where the piece is, so placements that
+// need a tuck or spin under an overhang are found, not just straight drops.
+
+use crate::{
+    models::{Board, PieceShape, PlaceResult},
+    views::{BoardPosition, RotationDirection},
+};
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, VecDeque},
+};
+
+// One input's worth of movement. Down is a step of gravity.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Step {
+    Rotate,
+    Left,
+    Right,
+    Down,
+}
+
+type State = (isize, isize, usize); // x, y, rot_idx
+
+pub struct Reachable {
+    start: PieceShape,
+    parents: HashMap<State, (State, Step)>, // how each state was first reached
+    resting: Vec<State>,                    // states where the piece would lock
+}
+
+impl Reachable {
+    pub fn search(board: &Board, piece: &PieceShape) -> Self {
+        let mut board = board.clone();
+        let mut reachable = Self {
+            start: *piece,
+            parents: HashMap::new(),
+            resting: Vec::new(),
+        };
+
+        let start = state_of(piece);
+        match board.try_place(piece, piece.position) {
+            PlaceResult::PlaceOk => {}
+            PlaceResult::RowFilled => {
+                reachable.resting.push(start);
+                return reachable;
+            }
+            // Nowhere to go from a piece that doesn't fit
+            PlaceResult::OutOfBounds | PlaceResult::PlaceBad => return reachable,
+        }
+
+        // Breadth first, trying rotations and sideways moves before falling,
+        // so paths move the piece while it's high up
+        let mut queue = VecDeque::from([start]);
+        while let Some(state) = queue.pop_front() {
+            let current = reachable.piece_at(state);
+            let mut landed = true;
+
+            for step in [Step::Rotate, Step::Left, Step::Right, Step::Down] {
+                let Some((next, locks)) = try_step(&mut board, &current, step) else {
+                    continue;
+                };
+                if step == Step::Down {
+                    landed = false;
+                }
+                if next == start || reachable.parents.contains_key(&next) {
+                    continue;
+                }
+
+                reachable.parents.insert(next, (state, step));
+                if locks {
+                    reachable.resting.push(next);
+                } else {
+                    queue.push_back(next);
+                }
+            }
+
+            if landed {
+                reachable.resting.push(state);
+            }
+        }
+        reachable
+    }
+
+    // Every place the piece could lock, in the order found
+    pub fn placements(&self) -> Vec<PieceShape> {
+        self.resting
+            .iter()
+            .map(|&state| self.piece_at(state))
+            .collect()
+    }
+
+    // Placements that would clear at least one line, with the lines each clears,
+    // most lines first. For hints: any of these is a move worth showing.
+    pub fn line_clears(&self, board: &Board) -> Vec<(PieceShape, usize)> {
+        let mut clears: Vec<(PieceShape, usize)> = self
+            .placements()
+            .into_iter()
+            .map(|piece| {
+                let lines = board.simulate_commit(&piece).filled_rows.len();
+                (piece, lines)
+            })
+            .filter(|&(_, lines)| lines > 0)
+            .collect();
+        clears.sort_by_key(|&(_, lines)| Reverse(lines));
+        clears
+    }
+
+    // Steps that take the piece to the target. None if it can't get there.
+    pub fn path_to(&self, target: &PieceShape) -> Option<Vec<Step>> {
+        let start = state_of(&self.start);
+        let mut state = state_of(target);
+        let mut path = Vec::new();
+
+        while state != start {
+            let &(parent, step) = self.parents.get(&state)?;
+            path.push(step);
+            state = parent;
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    // Whether moves from the start can take the piece to the target
+    pub fn reaches(&self, target: &PieceShape) -> bool {
+        self.parents.contains_key(&state_of(target))
+    }
+
+    fn piece_at(&self, (x, y, rot_idx): State) -> PieceShape {
+        let mut piece = self.start;
+        piece.rot_idx = rot_idx;
+        piece.position = BoardPosition { x, y };
+        piece
+    }
+}
+
+fn state_of(piece: &PieceShape) -> State {
+    (piece.position.x, piece.position.y, piece.rot_idx)
+}
+
+// Where a step takes the piece, as the game would move it, and whether the
+// piece locks there at once because it fills a row. None if it's blocked.
+fn try_step(board: &mut Board, piece: &PieceShape, step: Step) -> Option<(State, bool)> {
+    let mut moved = *piece;
+    match step {
+        Step::Rotate => {
+            moved.position = board.try_rotation(piece, &RotationDirection::Cw)?;
+            moved.rotate(&RotationDirection::Cw, board.pieces());
+        }
+        Step::Left => moved.position.x -= 1,
+        Step::Right => moved.position.x += 1,
+        Step::Down => moved.position.y -= 1,
+    }
+
+    match board.try_place(&moved, moved.position) {
+        PlaceResult::PlaceOk => Some((state_of(&moved), false)),
+        PlaceResult::RowFilled => Some((state_of(&moved), true)),
+        PlaceResult::OutOfBounds | PlaceResult::PlaceBad => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PieceType;
+
+    #[test]
+    fn test_tuck_under_overhang() {
+        // A ledge two rows up on the left, with open floor beneath it
+        let mut board = Board::new(6, 10).unwrap();
+        let ledge = vec![true, true, false, false, false, false];
+        board.add_garbage(&[vec![false; 6], vec![false; 6], ledge]);
+
+        let piece = PieceShape::new(PieceType::O, BoardPosition { x: 2, y: 8 });
+        let reachable = Reachable::search(&board, &piece);
+
+        // Straight down from the left wall, the O lands on the ledge
+        let mut under = piece;
+        under.position = BoardPosition { x: 0, y: 8 };
+        assert_eq!(board.clone().calculate_drop(&under).0.y, 3);
+
+        // Falling first, then sliding in, gets it underneath
+        under.position.y = 0;
+        assert!(reachable
+            .placements()
+            .iter()
+            .any(|p| p.position == under.position));
+        let path = reachable.path_to(&under).unwrap();
+        assert_eq!(path.iter().filter(|&&s| s == Step::Down).count(), 8);
+        assert_eq!(&path[path.len() - 2..], &[Step::Left, Step::Left]);
+    }
+
+    #[test]
+    fn test_line_clears() {
+        // A row missing only its rightmost cell
+        let mut board = Board::new(6, 10).unwrap();
+        let mut row = vec![true; 6];
+        row[5] = false;
+        board.add_garbage(&[row]);
+
+        let piece = PieceShape::new(PieceType::I, BoardPosition { x: 1, y: 7 });
+        let reachable = Reachable::search(&board, &piece);
+        let clears = reachable.line_clears(&board);
+
+        // The hint stands the I up in the gap
+        let (best, lines) = &clears[0];
+        assert_eq!(*lines, 1);
+        let cells: Vec<(isize, isize)> = best
+            .cells(board.pieces())
+            .iter()
+            .map(|&(dx, dy)| (best.position.x + dx, best.position.y + dy))
+            .collect();
+        assert!(cells.contains(&(5, 0)));
+        assert!(reachable.path_to(best).is_some());
+        assert!(clears.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+    }
+
+    #[test]
+    fn test_reaches() {
+        // A sealed pocket two wide and two tall at the bottom left
+        let mut board = Board::new(6, 10).unwrap();
+        let pocket = vec![false, false, true, false, false, false];
+        let lid = vec![true, true, true, false, false, false];
+        board.add_garbage(&[pocket.clone(), pocket, lid]);
+
+        let piece = PieceShape::new(PieceType::O, BoardPosition { x: 3, y: 8 });
+        let reachable = Reachable::search(&board, &piece);
+
+        // The O fits in the pocket but can't get in
+        let mut sealed = piece;
+        sealed.position = BoardPosition { x: 0, y: 0 };
+        assert!(board.try_place(&sealed, sealed.position) == PlaceResult::PlaceOk);
+        assert!(!reachable.reaches(&sealed));
+
+        let mut open = piece;
+        open.position = BoardPosition { x: 4, y: 0 };
+        assert!(reachable.reaches(&open));
+    }
+}
@@ -6,6 +6,7 @@
 // 7 Apr 2025
 
 use super::config_types::*;
+use crate::models::{PieceDef, PieceSet, PieceSetError, PieceType};
 use serde::Deserialize;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -19,6 +20,10 @@ pub struct Config {
     pub osc: OscConfig,
     pub frame_recorder: FrameRecorderConfig,
     pub speed: SpeedConfig,
+    pub attract: AttractConfig,
+    pub ws: WsConfig,
+    #[serde(default)]
+    pub versus: VersusConfig,
 }
 
 impl Config {
@@ -52,6 +57,26 @@ impl Config {
         Ok(toml::from_str(&content)?)
     }
 
+    // The piece set named in [paths], if there is one
+    pub fn load_piece_set(&self) -> Result<Option<PieceSet>, Box<dyn std::error::Error>> {
+        let Some(file) = &self.paths.piece_set else {
+            return Ok(None);
+        };
+        let path = if Path::new(file).is_absolute() {
+            PathBuf::from(file)
+        } else {
+            std::env::current_exe()
+                .ok()
+                .and_then(|p| p.parent().map(|p| p.join(file)))
+                .filter(|path| path.exists())
+                .unwrap_or_else(|| PathBuf::from(file))
+        };
+
+        let content = fs::read_to_string(path)?;
+        let set_file: PieceSetFile = toml::from_str(&content)?;
+        Ok(Some(set_file.piece_set()?))
+    }
+
     /************************* Resolving paths to the types needed in app ********************/
 
     /*
@@ -103,3 +128,44 @@ impl Config {
         path.to_string_lossy().into_owned() // Convert PathBuf to String safely
     }
 }
+
+impl PieceSetFile {
+    pub fn piece_set(&self) -> Result<PieceSet, PieceSetError> {
+        let defs = self
+            .piece
+            .iter()
+            .map(|def| {
+                Ok(PieceDef {
+                    typ: PieceType::named(&def.typ)
+                        .ok_or_else(|| PieceSetError::UnknownPiece(def.typ.clone()))?,
+                    rotations: def.rotations.clone(),
+                    color: def.color,
+                })
+            })
+            .collect::<Result<Vec<_>, PieceSetError>>()?;
+        PieceSet::custom(&defs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHIPPED: &str = include_str!("../../config.toml");
+
+    #[test]
+    fn test_shipped_config() {
+        // Keys under the wrong table are ignored, not rejected, so check the
+        // [rendering] ones are where serde will look for them
+        let file: toml::Table = toml::from_str(SHIPPED).unwrap();
+        let rendering = file["rendering"].as_table().unwrap();
+        for key in ["board_anchor", "flip_y", "landing_guide"] {
+            assert!(rendering.contains_key(key), "{} is not in [rendering]", key);
+        }
+
+        let config: Config = toml::from_str(SHIPPED).unwrap();
+        assert_eq!(config.rendering.board_anchor, Anchor::Center);
+        assert!(!config.rendering.flip_y);
+        assert_eq!(config.rendering.landing_guide, LandingGuide::Off);
+    }
+}
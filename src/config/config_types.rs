@@ -2,15 +2,422 @@
 //
 // Config types for the app
 
+use crate::models::PieceSet;
 use serde::Deserialize;
+use std::rc::Rc;
 
 #[derive(Debug, Deserialize)]
+#[serde(from = "BoardConfigFile")]
 pub struct BoardConfig {
     pub width: usize,
     pub height: usize,
     pub cell_size: f32,
     pub gravity_interval: f32,
     pub lock_delay: f32,
+    pub lock_delay_floor: Option<f32>, // lock delay on the floor, growing to lock_delay at the top
+    pub lock_mode: LockMode,
+    pub gravity: GravityMode,
+    pub clear_gravity: ClearGravity,
+    pub dig: DigConfig,
+    pub scoring: ScoringConfig,
+    pub mask: Option<String>, // ascii rows, top first: '#' blocked, '.' open
+    pub das: DasConfig,
+    pub irs: bool, // initial rotation: a piece spawned with rotate held spawns rotated
+    pub ihs: bool, // initial hold: a piece spawned with hold held goes straight to hold
+    pub assist: bool, // casual mode: hard drops that would leave a hole are nudged over
+    pub spawn_delay: f32, // entry delay (ARE): seconds a board waits before each spawn
+    pub spawn_jitter: f32, // up to this many seconds of random wait before each spawn
+    pub full_ratio: Option<f32>, // share of open cells filled that the app hears about
+    pub gravity_curve: Vec<GravityPoint>, // gravity by level; empty keeps gravity_interval
+    pub level_lines: Vec<usize>, // lines for each level in turn, the last repeating; empty is 10
+    pub randomizer: Randomizer,
+    pub kicks: KickTable,
+    pub mirror_kicks: bool, // a rotation no kick fits tries the kicks again, left for right
+    pub pieces: Rc<PieceSet>, // shapes and colors, from [paths] piece_set once it's loaded
+}
+
+// A 10 by 20 board with nothing extra switched on
+impl Default for BoardConfig {
+    fn default() -> Self {
+        Self {
+            width: 10,
+            height: 20,
+            cell_size: 30.0,
+            gravity_interval: 0.5,
+            lock_delay: 0.25,
+            lock_delay_floor: None,
+            lock_mode: LockMode::StepReset,
+            gravity: GravityMode::Naive,
+            clear_gravity: ClearGravity::Naive,
+            dig: DigConfig::default(),
+            scoring: ScoringConfig::default(),
+            mask: None,
+            das: DasConfig::default(),
+            irs: false,
+            ihs: false,
+            assist: false,
+            spawn_delay: 0.0,
+            spawn_jitter: 0.0,
+            full_ratio: None,
+            gravity_curve: Vec::new(),
+            level_lines: Vec::new(),
+            randomizer: Randomizer::Uniform,
+            kicks: KickTable::Srs,
+            mirror_kicks: false,
+            pieces: Rc::default(),
+        }
+    }
+}
+
+// [board] as written in config.toml: speed and handling left out come from the preset
+#[derive(Debug, Default, Deserialize)]
+struct BoardConfigFile {
+    width: usize,
+    height: usize,
+    cell_size: f32,
+    #[serde(default)]
+    preset: Preset,
+    gravity_interval: Option<f32>,
+    lock_delay: Option<f32>,
+    lock_delay_floor: Option<f32>,
+    lock_mode: Option<LockMode>,
+    gravity: Option<GravityMode>,
+    #[serde(default)]
+    clear_gravity: ClearGravity,
+    #[serde(default)]
+    dig: DigConfig,
+    #[serde(default)]
+    scoring: ScoringConfig,
+    #[serde(default)]
+    mask: Option<String>,
+    #[serde(default)]
+    das: DasConfigFile,
+    #[serde(default)]
+    irs: bool,
+    #[serde(default)]
+    ihs: bool,
+    #[serde(default)]
+    assist: bool,
+    spawn_delay: Option<f32>,
+    #[serde(default)]
+    spawn_jitter: f32,
+    #[serde(default)]
+    full_ratio: Option<f32>,
+    gravity_curve: Option<Vec<GravityPoint>>,
+    #[serde(default)]
+    level_lines: Vec<usize>,
+    #[serde(default)]
+    randomizer: Randomizer,
+    #[serde(default)]
+    kicks: KickTable,
+    #[serde(default)]
+    mirror_kicks: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(default)]
+struct DasConfigFile {
+    delay: Option<f32>,
+    repeat: Option<f32>,
+    preserve_charge: Option<bool>,
+}
+
+impl From<BoardConfigFile> for BoardConfig {
+    fn from(file: BoardConfigFile) -> Self {
+        let preset = file.preset.settings();
+        Self {
+            width: file.width,
+            height: file.height,
+            cell_size: file.cell_size,
+            gravity_interval: file.gravity_interval.unwrap_or(preset.gravity_interval),
+            lock_delay: file.lock_delay.unwrap_or(preset.lock_delay),
+            lock_delay_floor: file.lock_delay_floor,
+            lock_mode: file.lock_mode.unwrap_or(preset.lock_mode),
+            gravity: file.gravity.unwrap_or(preset.gravity),
+            clear_gravity: file.clear_gravity,
+            dig: file.dig,
+            scoring: file.scoring,
+            mask: file.mask,
+            das: DasConfig {
+                delay: file.das.delay.unwrap_or(preset.das.delay),
+                repeat: file.das.repeat.unwrap_or(preset.das.repeat),
+                preserve_charge: file
+                    .das
+                    .preserve_charge
+                    .unwrap_or(preset.das.preserve_charge),
+            },
+            irs: file.irs,
+            ihs: file.ihs,
+            assist: file.assist,
+            spawn_delay: file.spawn_delay.unwrap_or(preset.spawn_delay),
+            spawn_jitter: file.spawn_jitter,
+            full_ratio: file.full_ratio,
+            gravity_curve: file.gravity_curve.unwrap_or(preset.gravity_curve),
+            level_lines: file.level_lines,
+            randomizer: file.randomizer,
+            kicks: file.kicks,
+            mirror_kicks: file.mirror_kicks,
+            pieces: Rc::default(),
+        }
+    }
+}
+
+// Named bundles of speed and handling settings, for setting up without tuning
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum Preset {
+    Beginner,
+    #[default]
+    Normal,
+    Marathon, // gravity speeds up with the level
+    Sprint,   // quick handling for racing through lines
+    Master,   // 20G: a piece lands the moment it spawns
+}
+
+// What a preset sets
+#[derive(Debug, Clone, PartialEq)]
+pub struct PresetSettings {
+    pub gravity_interval: f32,
+    pub lock_delay: f32,
+    pub lock_mode: LockMode,
+    pub gravity: GravityMode,
+    pub das: DasConfig,
+    pub gravity_curve: Vec<GravityPoint>,
+    pub spawn_delay: f32,
+}
+
+impl Preset {
+    // Names are matched ignoring case, so "Master" and "master" are the same
+    pub fn named(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "beginner" => Some(Self::Beginner),
+            "normal" => Some(Self::Normal),
+            "marathon" => Some(Self::Marathon),
+            "sprint" => Some(Self::Sprint),
+            "master" | "20g" => Some(Self::Master),
+            _ => None,
+        }
+    }
+
+    pub fn settings(self) -> PresetSettings {
+        let das = |delay, repeat| DasConfig {
+            delay,
+            repeat,
+            preserve_charge: false,
+        };
+        match self {
+            Self::Beginner => PresetSettings {
+                gravity_interval: 1.0,
+                lock_delay: 0.5,
+                lock_mode: LockMode::MoveReset,
+                gravity: GravityMode::Naive,
+                das: das(0.25, 0.05),
+                gravity_curve: Vec::new(),
+                spawn_delay: 0.2,
+            },
+            Self::Normal => PresetSettings {
+                gravity_interval: 0.5,
+                lock_delay: 0.25,
+                lock_mode: LockMode::StepReset,
+                gravity: GravityMode::Naive,
+                das: DasConfig::default(),
+                gravity_curve: Vec::new(),
+                spawn_delay: 0.0,
+            },
+            Self::Marathon => PresetSettings {
+                gravity_interval: 1.0,
+                lock_delay: 0.5,
+                lock_mode: LockMode::MoveReset,
+                gravity: GravityMode::Naive,
+                das: DasConfig::default(),
+                gravity_curve: vec![
+                    GravityPoint {
+                        level: 1,
+                        interval: 1.0,
+                    },
+                    GravityPoint {
+                        level: 15,
+                        interval: 0.05,
+                    },
+                ],
+                spawn_delay: 0.1,
+            },
+            Self::Sprint => PresetSettings {
+                gravity_interval: 1.0,
+                lock_delay: 0.5,
+                lock_mode: LockMode::MoveReset,
+                gravity: GravityMode::Naive,
+                das: das(0.1, 0.0),
+                gravity_curve: Vec::new(),
+                spawn_delay: 0.0,
+            },
+            Self::Master => PresetSettings {
+                gravity_interval: 1.0 / 1200.0,
+                gravity: GravityMode::Instant,
+                lock_delay: 0.5,
+                lock_mode: LockMode::StepReset,
+                das: das(0.133, 0.0167),
+                gravity_curve: Vec::new(),
+                spawn_delay: 0.25,
+            },
+        }
+    }
+}
+
+impl TryFrom<String> for Preset {
+    type Error = String;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        Self::named(&name).ok_or_else(|| format!("unknown preset: {}", name))
+    }
+}
+
+// Wall kicks tried, in order, when a rotation is blocked
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KickTable {
+    #[default]
+    Srs,
+    Ars, // Arika: one step right, then one left, no kicks for the I or off the floor
+    // [dx, dy] kicks for each of the 8 rotation transitions
+    Custom {
+        jlszt: Vec<Vec<[isize; 2]>>,
+        i: Vec<Vec<[isize; 2]>>,
+    },
+}
+
+// A breakpoint on the gravity curve. Levels in between are interpolated.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct GravityPoint {
+    pub level: usize,
+    pub interval: f32, // seconds between gravity steps at this level
+}
+
+// Delayed auto shift: holding left or right steps once, waits, then repeats
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct DasConfig {
+    pub delay: f32,            // seconds held before auto-shift starts
+    pub repeat: f32,           // seconds between auto-shift steps, 0 to go straight to the wall
+    pub preserve_charge: bool, // a new piece spawned with the key still held shifts at once
+}
+
+impl Default for DasConfig {
+    fn default() -> Self {
+        Self {
+            delay: 0.167,
+            repeat: 0.033,
+            preserve_charge: false,
+        }
+    }
+}
+
+// Solo "dig" challenge: the board starts with rows of garbage to clear
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct DigConfig {
+    pub garbage_rows: usize, // rows of garbage at game start, 0 for none
+    pub hole_density: f32,   // chance of each garbage cell being a hole, 0 to 1
+}
+
+// Points for line clears, t-spins, combos and back-to-backs
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ScoringConfig {
+    pub single: usize,
+    pub double: usize,
+    pub triple: usize,
+    pub tetris: usize,
+    pub tspin: usize, // a t-spin that clears nothing
+    pub tspin_single: usize,
+    pub tspin_double: usize,
+    pub tspin_triple: usize,
+    pub tspin_mini: usize,        // a mini t-spin that clears nothing
+    pub tspin_mini_single: usize, // minis clearing more count as full t-spins
+    pub combo: usize,             // per clear in a row after the first
+    pub back_to_back: f32,        // multiplier for a tetris or t-spin clear following another
+}
+
+impl ScoringConfig {
+    // Points for clearing 1 to 4 rows at once
+    pub fn line_points(&self, tspin: bool, mini: bool) -> [usize; 4] {
+        if tspin && mini {
+            [
+                self.tspin_mini_single,
+                self.tspin_double,
+                self.tspin_triple,
+                self.tetris,
+            ]
+        } else if tspin {
+            [
+                self.tspin_single,
+                self.tspin_double,
+                self.tspin_triple,
+                self.tetris,
+            ]
+        } else {
+            [self.single, self.double, self.triple, self.tetris]
+        }
+    }
+}
+
+// The guideline's table
+impl Default for ScoringConfig {
+    fn default() -> Self {
+        Self {
+            single: 100,
+            double: 300,
+            triple: 500,
+            tetris: 800,
+            tspin: 400,
+            tspin_single: 800,
+            tspin_double: 1200,
+            tspin_triple: 1600,
+            tspin_mini: 100,
+            tspin_mini_single: 200,
+            combo: 50,
+            back_to_back: 1.5,
+        }
+    }
+}
+
+// How the active piece falls
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GravityMode {
+    #[default]
+    Naive, // a row each gravity interval, at most one a frame
+    Fractional, // rows in proportion to time, so short intervals drop several a frame
+    Instant,    // 20G: straight to the floor
+}
+
+// How the cells above cleared rows fall
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClearGravity {
+    #[default]
+    Naive, // everything above moves down by the number of cleared rows
+    Sticky, // connected groups of cells fall separately, as far as they can
+}
+
+// When a landed piece's lock delay starts over
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LockMode {
+    #[default]
+    StepReset, // only when the piece steps down a row
+    MoveReset, // on every move or rotate, up to a limited number per piece
+    Infinite,  // every move or rotate, until the piece has spent a capped time locking
+    Instant,   // never: no lock delay, the piece commits as it lands
+}
+
+// How the next piece type is picked
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Randomizer {
+    #[default]
+    Uniform, // any type, every time
+    Bag, // every type once in each bag of 7
 }
 
 #[derive(Debug, Deserialize)]
@@ -19,6 +426,224 @@ pub struct RenderConfig {
     pub texture_height: u32,
     pub texture_samples: u32,
     pub arc_resolution: u32,
+    #[serde(default)]
+    pub board_background: BoardBackground,
+    #[serde(default)]
+    pub piece_glow: PieceGlow,
+    #[serde(default)]
+    pub show_col_heights: bool,
+    #[serde(default)]
+    pub age_heatmap: AgeHeatmap,
+    #[serde(default)]
+    pub drop_trail: DropTrail,
+    #[serde(default)]
+    pub clear_burst: ClearBurst,
+    #[serde(default)]
+    pub spawn_preview: SpawnPreview,
+    #[serde(default)]
+    pub lock_flash: LockFlash,
+    #[serde(default)]
+    pub color_drift: ColorDrift,
+    #[serde(default)]
+    pub garbage_rise: GarbageRise,
+    #[serde(default)]
+    pub landing_guide: LandingGuide,
+    #[serde(default)]
+    pub board_anchor: Anchor,
+    #[serde(default)]
+    pub flip_y: bool, // draw row 0 at the top; the game itself is unchanged
+}
+
+// The point of a board its location gives
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Anchor {
+    #[default]
+    Center,
+    TopLeft,
+    BottomLeft,
+}
+
+// What shows where the active piece will land. One guide at a time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LandingGuide {
+    #[default]
+    Off,
+    Row, // brighten the row the piece's lowest cells would land on
+}
+
+// Fading cells left along a hard dropped piece's path
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct DropTrail {
+    pub enabled: bool,
+    pub lifetime: f32, // seconds for a trail to fade out
+    pub alpha: f32,    // opacity when a trail is fresh
+}
+
+impl Default for DropTrail {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            lifetime: 0.15,
+            alpha: 0.4,
+        }
+    }
+}
+
+// A faint outline of the next piece where it will spawn, while a piece falls
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct SpawnPreview {
+    pub enabled: bool,
+    pub alpha: f32,
+    pub spin: bool,      // the outline slowly turns about the piece's center
+    pub spin_speed: f32, // turns per second
+}
+
+impl Default for SpawnPreview {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            alpha: 0.2,
+            spin: false,
+            spin_speed: 0.25,
+        }
+    }
+}
+
+// Each board's color slowly drifts through hue, from a start set by its
+// seed, so a wall of boards shimmers. Locked cells keep the board's own
+// color unless `cells` is on.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ColorDrift {
+    pub enabled: bool,
+    pub speed: f32, // degrees of hue per second
+    pub cells: bool,
+}
+
+impl Default for ColorDrift {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            speed: 6.0,
+            cells: false,
+        }
+    }
+}
+
+// The active piece pulses brighter while it locks, faster as the lock nears
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct LockFlash {
+    pub enabled: bool,
+    pub pulses: f32,     // flashes over the whole lock delay
+    pub brightness: f32, // how far toward white the piece goes at the end, 0 to 1
+}
+
+impl Default for LockFlash {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            pulses: 3.0,
+            brightness: 0.6,
+        }
+    }
+}
+
+// Cleared cells burst outward as fragments that fall and fade
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ClearBurst {
+    pub enabled: bool,
+    pub lifetime: f32, // seconds for a fragment to fade out
+    pub speed: f32,    // outward speed at the board's edges, in pixels per second
+    pub gravity: f32,  // pixels per second squared
+}
+
+impl Default for ClearBurst {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            lifetime: 0.8,
+            speed: 400.0,
+            gravity: 1200.0,
+        }
+    }
+}
+
+// Received garbage slides the stack up instead of jumping it there
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct GarbageRise {
+    pub enabled: bool,
+    pub duration: f32, // seconds for the stack to reach its new height
+}
+
+impl Default for GarbageRise {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            duration: 0.15,
+        }
+    }
+}
+
+// A soft glow behind the active piece's cells, so it stands out from the stack
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct PieceGlow {
+    pub enabled: bool,
+    pub scale: f32, // glow size relative to a cell
+    pub alpha: f32,
+}
+
+impl Default for PieceGlow {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            scale: 1.6,
+            alpha: 0.25,
+        }
+    }
+}
+
+// Tint locked cells by how long they've been on the board
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct AgeHeatmap {
+    pub enabled: bool,
+    pub max_age: u64,   // frames until a cell reaches the full shift
+    pub hue_shift: f32, // degrees the hue moves by at max_age
+}
+
+impl Default for AgeHeatmap {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_age: 1800,
+            hue_shift: 120.0,
+        }
+    }
+}
+
+// What each board draws behind its cells. Colors are rgb, 0 to 1.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BoardBackground {
+    #[default]
+    None,
+    Solid([f32; 3]),
+    Gradient {
+        top: [f32; 3],
+        bottom: [f32; 3],
+    },
+    Image {
+        path: String,
+        fallback: [f32; 3], // drawn instead if the image can't be loaded
+    },
 }
 
 #[derive(Debug, Deserialize)]
@@ -31,6 +656,38 @@ pub struct WindowConfig {
 pub struct FrameRecorderConfig {
     pub frame_limit: u32,
     pub fps: u32,
+    pub width: u32,
+    pub height: u32,
+    #[serde(default)]
+    pub region: RecordRegion,
+    #[serde(default)]
+    pub format: RecordFormat,
+    #[serde(default)]
+    pub session_folders: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordFormat {
+    #[default]
+    Png, // numbered PNG sequence
+    Gif, // one animated GIF, needs the "gif" feature
+}
+
+// Part of the scene the frame recorder keeps
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordRegion {
+    #[default]
+    Window, // everything
+    Board(String), // a single board by id
+    Rect {
+        // in texture pixels, origin top-left
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
 }
 
 #[derive(Debug, Deserialize)]
@@ -41,9 +698,141 @@ pub struct SpeedConfig {
 #[derive(Debug, Deserialize)]
 pub struct PathConfig {
     pub output_directory: String,
+    #[serde(default)]
+    pub piece_set: Option<String>, // data file of piece shapes and colors
+}
+
+// A piece set data file: [[piece]] tables, each replacing one standard piece
+#[derive(Debug, Default, Deserialize)]
+pub struct PieceSetFile {
+    #[serde(default)]
+    pub piece: Vec<PieceDefFile>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PieceDefFile {
+    #[serde(rename = "type")]
+    pub typ: String, // "I", "J", "L", "S", "Z", "T" or "O"
+    pub rotations: Vec<Vec<[isize; 2]>>, // [x, y] cells from the bottom left, per rotation
+    #[serde(default)]
+    pub color: Option<[f32; 3]>, // rgb, 0 to 1
 }
 
 #[derive(Debug, Deserialize)]
 pub struct OscConfig {
     pub rx_port: u16,
+    #[serde(default)]
+    pub send: Option<OscSendConfig>, // column heights sent out, when set
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OscSendConfig {
+    pub addr: String,         // host:port to send to
+    pub interval: f32,        // minimum seconds between sends
+    pub resync_interval: f32, // seconds between sends of every column
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AttractConfig {
+    pub idle_timeout: f32,
+    pub demo_seed: u64,
+    #[serde(default)]
+    pub ai_weights: AiWeights,
+    #[serde(default)]
+    pub personality_spread: f32,
+    #[serde(default)]
+    pub ai_skill: AiSkill,
+}
+
+// Deliberate imperfection, so demo games look more human
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct AiSkill {
+    pub mistake_rate: f32,   // chance of settling for a worse placement, 0 to 1
+    pub reaction_delay: f32, // seconds between inputs
+}
+
+// Heuristic weights for the AI's board evaluation
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct AiWeights {
+    pub aggregate_height: f32,
+    pub lines: f32,
+    pub holes: f32,
+    pub bumpiness: f32,
+    pub well_depth: f32,
+}
+
+impl Default for AiWeights {
+    // Yiyuan Lee's tuned weights. The well depth penalty is El-Tetris'
+    // well weight, scaled by its ratio to the El-Tetris hole weight.
+    fn default() -> Self {
+        Self {
+            aggregate_height: -0.510066,
+            lines: 0.760666,
+            holes: -0.35663,
+            bumpiness: -0.184483,
+            well_depth: -0.15285,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WsConfig {
+    pub port: u16,
+    pub broadcast_interval: f32,
+}
+
+// Versus play: line clears send garbage to the other boards
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct VersusConfig {
+    pub enabled: bool,
+    pub routing: AttackRouting,
+    pub seed: u64,      // for random targets and garbage holes
+    pub countdown: f32, // seconds boards wait, together, before a game starts
+}
+
+// Which opponent a board's garbage goes to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttackRouting {
+    #[default]
+    RoundRobin, // each opponent in turn
+    Random,
+    Targeted, // the opponent with the highest score
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_master_preset() {
+        let file = BoardConfigFile {
+            width: 10,
+            height: 20,
+            preset: Preset::named("Master").unwrap(),
+            lock_delay: Some(0.4),
+            ..BoardConfigFile::default()
+        };
+        let board = BoardConfig::from(file);
+
+        // 20G: pieces go straight down, with no curve to slow them
+        assert_eq!(board.gravity, GravityMode::Instant);
+        assert!(board.gravity_interval <= 1.0 / 60.0);
+        assert!(board.gravity_curve.is_empty());
+        assert!(board.das.delay < DasConfig::default().delay);
+        assert_eq!(board.spawn_delay, 0.25);
+
+        // but a field set in [board] wins over the preset
+        assert_eq!(board.lock_delay, 0.4);
+        let file = BoardConfigFile {
+            preset: Preset::Master,
+            spawn_delay: Some(0.0),
+            ..BoardConfigFile::default()
+        };
+        assert_eq!(BoardConfig::from(file).spawn_delay, 0.0);
+        assert_eq!(Preset::named("unknown"), None);
+    }
 }
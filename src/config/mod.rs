@@ -3,6 +3,10 @@ pub mod config_types;
 
 pub use config_load::Config;
 pub use config_types::{
-    BoardConfig, FrameRecorderConfig, OscConfig, PathConfig, RenderConfig, SpeedConfig,
-    WindowConfig,
+    AgeHeatmap, AiSkill, AiWeights, Anchor, AttackRouting, AttractConfig, BoardBackground,
+    BoardConfig, ClearBurst, ClearGravity, ColorDrift, DasConfig, DigConfig, DropTrail,
+    FrameRecorderConfig, GarbageRise, GravityMode, GravityPoint, KickTable, LandingGuide,
+    LockFlash, LockMode, OscConfig, OscSendConfig, PathConfig, PieceDefFile, PieceGlow,
+    PieceSetFile, Preset, PresetSettings, Randomizer, RecordFormat, RecordRegion, RenderConfig,
+    ScoringConfig, SpawnPreview, SpeedConfig, VersusConfig, WindowConfig, WsConfig,
 };
@@ -1,5 +1,8 @@
+pub mod ai;
 pub mod config;
 pub mod effects;
 pub mod models;
+pub mod net;
+pub mod recording;
 pub mod utils;
 pub mod views;
@@ -2,25 +2,43 @@
 
 use gameover2025::{
     config::*,
-    views::{BackgroundManager, BoardInstance, PlayerInput},
+    net::OscSender,
+    recording::FrameRecorder,
+    views::{BackgroundManager, GameManager, KeySampler, PlayerInput, RenderOptions},
 };
-use nannou::prelude::*;
+use nannou::{prelude::*, rand::Rng};
 use nnpipe::*;
-use std::{collections::HashMap, time::Instant};
+
+#[cfg(feature = "ws")]
+use gameover2025::net::WsServer;
+use std::{path::Path, rc::Rc, time::Instant};
+
+// Board frames kept per board while recording: 10 minutes at 60fps
+const FRAME_STREAM_CAPACITY: usize = 36000;
 
 struct Model {
     // Tetris Boards
-    boards: HashMap<String, BoardInstance>,
-    board_config: BoardConfig,
+    game: GameManager,
 
     // Background
     background: BackgroundManager,
 
     // Player input pending update
-    player_input: Option<PlayerInput>,
+    player_input: Vec<PlayerInput>, // every key event since the last frame
+    keys: KeySampler,               // game keys held, to drop OS key repeats
+
+    // Browser dashboard feed
+    #[cfg(feature = "ws")]
+    ws_server: Option<WsServer>,
 
-    // Random
-    rng: nannou::rand::rngs::ThreadRng,
+    // Column heights out to OSC receivers
+    osc_sender: Option<OscSender>,
+
+    // Where recordings are written
+    output_directory: String,
+
+    // Records the rendered scene to PNG
+    frame_recorder: FrameRecorder,
 
     // Nannou API
     draw: nannou::Draw,
@@ -44,7 +62,7 @@ struct Model {
 
 fn model(app: &App) -> Model {
     // Load config
-    let config = Config::load().expect("\nGameOver: FAILED TO LOAD CONFIG.TOML\n");
+    let mut config = Config::load().expect("\nGameOver: FAILED TO LOAD CONFIG.TOML\n");
 
     // Create window
     let window_id = app
@@ -54,6 +72,7 @@ fn model(app: &App) -> Model {
         .msaa_samples(1)
         .view(view)
         .key_pressed(key_pressed)
+        .key_released(key_released)
         .build()
         .unwrap();
 
@@ -103,15 +122,61 @@ fn model(app: &App) -> Model {
         dst_format,
     );
 
+    // Set up the frame recorder
+    let frame_recorder = FrameRecorder::new(
+        device,
+        &texture,
+        &config.frame_recorder,
+        &config.resolve_output_dir(),
+    );
+
+    #[cfg(feature = "ws")]
+    let ws_server = match WsServer::start(config.ws.port, config.ws.broadcast_interval) {
+        Ok(server) => Some(server),
+        Err(e) => {
+            println!("GameOver: failed to start WebSocket server: {}", e);
+            None
+        }
+    };
+
+    let osc_sender = config.osc.send.as_ref().and_then(|send| {
+        OscSender::connect(&send.addr, send.interval, send.resync_interval)
+            .map_err(|e| println!("GameOver: failed to start OSC sender: {}", e))
+            .ok()
+    });
+
+    // Every board plays with the piece set, so load it before the first
+    match config.load_piece_set() {
+        Ok(Some(set)) => config.board.pieces = Rc::new(set),
+        Ok(None) => {}
+        Err(e) => println!(
+            "GameOver: ignoring piece set, using the standard pieces: {}",
+            e
+        ),
+    }
+
+    // One seed for the whole session; every board's comes from it
+    let seed = nannou::rand::thread_rng().gen();
+    println!("GameOver: session seed {}", seed);
+    let mut game = GameManager::new(config.board, config.attract).with_seed(seed);
+    game.set_render_options(RenderOptions::load(app, &config.rendering));
+    game.set_versus(&config.versus);
+
     Model {
-        boards: HashMap::new(),
-        board_config: config.board,
+        game,
 
         background: BackgroundManager::new(rgb(0.05, 0.03, 0.0)),
 
-        player_input: None,
+        player_input: Vec::new(),
+        keys: KeySampler::default(),
 
-        rng: nannou::rand::thread_rng(),
+        #[cfg(feature = "ws")]
+        ws_server,
+
+        osc_sender,
+
+        output_directory: config.paths.output_directory,
+        frame_recorder,
 
         draw,
         draw_renderer,
@@ -132,27 +197,13 @@ fn model(app: &App) -> Model {
 
 impl Model {
     fn make_board(&mut self, id: &str, location: Vec2) {
-        let config = &self.board_config;
-        let board = BoardInstance::new(
-            id,
-            location,
-            config.width,
-            config.height,
-            config.cell_size,
-            config.gravity_interval,
-            config.lock_delay,
-        );
-        self.boards.insert(board.id.to_owned(), board);
-        println!("\n<------ Board Created: <{}> ----->", id);
-        println!(
-            "size: {}x{} blocks\nlocation: {}\n",
-            config.width, config.height, location
-        );
+        let seed = self.game.board_seed(id);
+        self.game.make_board(id, location, seed);
     }
 }
 
 fn main() {
-    nannou::app(model).update(update).run();
+    nannou::app(model).update(update).exit(exit).run();
 }
 
 fn update(app: &App, model: &mut Model, _update: Update) {
@@ -170,12 +221,19 @@ fn update(app: &App, model: &mut Model, _update: Update) {
     model.background.draw(&model.draw, app.time);
 
     // Update & draw the boards
-    for board in model.boards.values_mut() {
-        board.update(dt, &model.player_input, &mut model.rng);
-        board.draw(&model.draw);
-    }
+    model.game.update(dt, &model.player_input);
+    model.game.draw(&model.draw);
 
-    model.player_input = None;
+    model.player_input.clear();
+
+    // Send the dashboards a snapshot
+    #[cfg(feature = "ws")]
+    if let Some(server) = model.ws_server.as_mut() {
+        server.update(dt, || model.game.snapshot());
+    }
+    if let Some(sender) = model.osc_sender.as_mut() {
+        sender.update(dt, &model.game);
+    }
 
     // Handle FPS and origin display
     if model.verbose {
@@ -184,6 +242,18 @@ fn update(app: &App, model: &mut Model, _update: Update) {
     }
 
     render_and_post(app, model);
+
+    // Record the finished frame
+    let window = app.main_window();
+    model
+        .frame_recorder
+        .capture(window.device(), window.queue(), dt, model.game.boards());
+}
+
+fn exit(app: &App, mut model: Model) {
+    // Let the recorder finish writing its frames
+    let window = app.main_window();
+    model.frame_recorder.stop(window.device());
 }
 
 fn view(_app: &App, model: &Model, frame: Frame) {
@@ -197,40 +267,76 @@ fn view(_app: &App, model: &Model, frame: Frame) {
 
 // ******************************* Key Capture *****************************
 
-fn key_pressed(app: &App, model: &mut Model, key: Key) {
+// The input a game key sends when it goes down
+fn game_key(key: Key) -> Option<PlayerInput> {
     match key {
-        Key::Left => model.player_input = Some(PlayerInput::L),
-        Key::Right => model.player_input = Some(PlayerInput::R),
-        Key::Up => model.player_input = Some(PlayerInput::Rotate),
-        Key::Space => model.player_input = Some(PlayerInput::HardDrop),
-        Key::Return => model.player_input = Some(PlayerInput::Pause),
-        Key::Key1 => model.player_input = Some(PlayerInput::SaveState),
-        Key::Key2 => model.player_input = Some(PlayerInput::ResumeState),
+        Key::Left => Some(PlayerInput::LHold),
+        Key::Right => Some(PlayerInput::RHold),
+        Key::Up => Some(PlayerInput::RotateHold),
+        Key::Down => Some(PlayerInput::SoftDrop),
+        Key::Space => Some(PlayerInput::HardDrop),
+        Key::C => Some(PlayerInput::Hold),
+        Key::Return => Some(PlayerInput::Pause),
+        Key::Key1 => Some(PlayerInput::SaveState),
+        Key::Key2 => Some(PlayerInput::ResumeState),
+        _ => None,
+    }
+}
 
+fn key_pressed(app: &App, model: &mut Model, key: Key) {
+    if let Some(input) = game_key(key) {
+        model.player_input.extend(model.keys.press(input));
+        return;
+    }
+
+    match key {
         Key::G => {
-            model.make_board(
-                "board1",
-                vec2(
-                    (model.board_config.width as f32 * model.board_config.cell_size / -2.0) - 100.0,
-                    0.0,
-                ),
-            );
-            model.make_board(
-                "board2",
-                vec2(
-                    (model.board_config.width as f32 * model.board_config.cell_size / 2.0) + 100.0,
-                    0.0,
-                ),
-            );
+            let board_config = model.game.board_config();
+            let half_width = board_config.width as f32 * board_config.cell_size / 2.0;
+            model.make_board("board1", vec2(-half_width - 100.0, 0.0));
+            model.make_board("board2", vec2(half_width + 100.0, 0.0));
+        }
+        Key::R => {
+            if model.game.is_recording_frames() {
+                let dir = Path::new(&model.output_directory).join("board_frames");
+                if let Err(e) = model.game.stop_recording_frames(&dir) {
+                    println!("Failed to save board frames: {}", e);
+                }
+            } else {
+                model.game.start_recording_frames(FRAME_STREAM_CAPACITY);
+            }
+        }
+        Key::V => {
+            let window = app.main_window();
+            if model.frame_recorder.is_recording() {
+                model.frame_recorder.stop(window.device());
+            } else {
+                model.frame_recorder.start();
+            }
         }
         Key::P => {
             model.verbose = !model.verbose;
             init_fps(app, model);
         }
+        Key::F => {
+            let frozen = !model.game.is_frozen();
+            model.game.set_frozen(frozen);
+            println!(
+                "GameOver: game {}",
+                if frozen { "frozen" } else { "running" }
+            );
+        }
+        Key::S if model.game.is_frozen() => model.game.step(),
         _ => {}
     }
 }
 
+fn key_released(_app: &App, model: &mut Model, key: Key) {
+    if let Some(input) = game_key(key) {
+        model.player_input.extend(model.keys.release(input));
+    }
+}
+
 // ******************************* Rendering and Capture *****************************
 fn render_and_post(app: &App, model: &mut Model) {
     // Get the window device and queue
@@ -296,8 +402,8 @@ fn draw_fps(model: &Model) {
 
 fn draw_score(model: &Model) {
     let draw = &model.draw;
-    let mut count = model.boards.len();
-    for (board_name, board) in &model.boards {
+    let mut count = model.game.boards().len();
+    for (board_name, board) in model.game.boards() {
         draw.text(&format!("{}: {:?}", board_name, board.score()))
             .x_y(900.0, 520.0 - (25.0 * count as f32))
             .color(RED)
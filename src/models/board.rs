@@ -3,9 +3,31 @@
 // The Tetris Board model
 // handles grid state and piece positioning
 
-use crate::views::{BoardPosition, PieceInstance, RotationDirection};
-
-const DEBUG: bool = true;
+use crate::{
+    models::{PieceSet, PieceShape, PieceType, WallKicks},
+    views::{BoardPosition, RotationDirection},
+};
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    rc::Rc,
+};
+
+const DEBUG: bool = false;
+
+// awarded when a line clear leaves the board empty
+const PERFECT_CLEAR_BONUS: usize = 3000;
+
+// Version of the binary encoding produced by Board::encode
+const ENCODING_VERSION: u8 = 2;
+// version u8, width u16, height u16, score u64
+const ENCODING_HEADER_LEN: usize = 13;
+// the encoding holds each side in a u16
+const MAX_SIDE: usize = u16::MAX as usize;
+
+// 64-bit FNV-1a, for board checksums
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
 
 #[derive(PartialEq)]
 pub enum PlaceResult {
@@ -15,33 +37,179 @@ pub enum PlaceResult {
     PlaceBad,
 }
 
+// What committing a piece would do, from Board::simulate_commit
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimResult {
+    pub filled_rows: Vec<isize>, // rows the piece completes, in commit_piece order
+    pub col_heights: Vec<isize>, // col scores after the commit
+}
+
+// Rows removed by a line clear, one step per link of a cascade chain
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClearChain {
+    pub steps: Vec<Vec<isize>>, // rows removed by each step, as they were when removed
+}
+
+impl ClearChain {
+    // How many times rows were cleared in a row
+    pub fn depth(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn rows_cleared(&self) -> usize {
+        self.steps.iter().map(Vec::len).sum()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum DecodeError {
+    Truncated { expected: usize, actual: usize },
+    UnsupportedVersion(u8),
+    InvalidSize { width: usize, height: usize },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::Truncated { expected, actual } => {
+                write!(
+                    f,
+                    "board data truncated: expected {} bytes, got {}",
+                    expected, actual
+                )
+            }
+            DecodeError::UnsupportedVersion(version) => {
+                write!(f, "unsupported board encoding version {}", version)
+            }
+            DecodeError::InvalidSize { width, height } => {
+                write!(f, "invalid board size {}x{}", width, height)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+#[derive(Debug, PartialEq)]
+pub enum MaskError {
+    WrongSize { width: usize, height: usize }, // size of the ascii given
+    BadCell(char),
+}
+
+impl fmt::Display for MaskError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MaskError::WrongSize { width, height } => {
+                write!(f, "mask is {}x{}, not the board's size", width, height)
+            }
+            MaskError::BadCell(c) => {
+                write!(f, "unexpected '{}' in mask, use '#' or '.'", c)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MaskError {}
+
+// A board needs at least one cell each way, and no more than fit in its encoding
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SizeError {
+    pub width: usize,
+    pub height: usize,
+}
+
+impl fmt::Display for SizeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a board can't be {}x{} cells", self.width, self.height)
+    }
+}
+
+impl std::error::Error for SizeError {}
+
+// A query for rows, columns or cells the board doesn't have
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BoardError {
+    NoSuchRow(isize),
+    NoSuchColumn(isize),
+    OffBoard(BoardPosition),
+}
+
+impl fmt::Display for BoardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BoardError::NoSuchRow(row) => write!(f, "no row {} on the board", row),
+            BoardError::NoSuchColumn(col) => write!(f, "no column {} on the board", col),
+            BoardError::OffBoard(pos) => write!(f, "({}, {}) is off the board", pos.x, pos.y),
+        }
+    }
+}
+
+impl std::error::Error for BoardError {}
+
+#[derive(Clone, Debug)]
 pub struct Board {
     pub width: isize,                // overall width in cells
     pub height: isize,               // overall height in cells
     state: BoardState,               // grid state
     backup_state: BoardState,        // previous grid state for testing positions
     saved_state: Option<BoardState>, // saved state for pausing
+    dirty: DirtyCells,               // cells changed since the last take_dirty
+    ticks: u64,                      // clock for cell ages, advanced by tick
+    kicks: WallKicks,                // tried when a rotation is blocked
+    pieces: Rc<PieceSet>,            // the shapes pieces take
+    mirror_kicks: bool,              // kicks tried again mirrored when none fit
+    topped_out: bool,                // a piece locked with cells off the board
+}
+
+// Boards are equal when the same cells are filled and blocked. Scores,
+// cell ages, saved states and dirty cells don't count.
+impl PartialEq for Board {
+    fn eq(&self, other: &Self) -> bool {
+        self.width == other.width
+            && self.height == other.height
+            && self.state.grid == other.state.grid
+            && self.state.blocked == other.state.blocked
+    }
+}
+
+impl Eq for Board {}
+
+impl Hash for Board {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.width.hash(state);
+        self.height.hash(state);
+        self.state.grid.hash(state);
+        self.state.blocked.hash(state);
+    }
 }
 
 impl Board {
-    pub fn new(width: usize, height: usize) -> Self {
+    pub fn new(width: usize, height: usize) -> Result<Self, SizeError> {
+        check_size(width, height)?;
+
         let prev_state = BoardState::new(width, height);
-        Self {
+        Ok(Self {
             width: width as isize,
             height: height as isize,
             state: prev_state.clone(),
             backup_state: prev_state,
             saved_state: None,
-        }
+            dirty: DirtyCells::all(width, height),
+            ticks: 0,
+            kicks: WallKicks::Srs,
+            pieces: Rc::default(),
+            mirror_kicks: false,
+            topped_out: false,
+        })
     }
 
     /************************ Piece Placement *******************************/
 
     // Check validity of desired piece placement, returns result of placement
-    pub fn try_place(&mut self, piece: &PieceInstance, board_pos: BoardPosition) -> PlaceResult {
+    pub fn try_place(&mut self, piece: &PieceShape, board_pos: BoardPosition) -> PlaceResult {
         // First check if the piece's position is valid
 
-        for &(dx, dy) in piece.cells() {
+        for &(dx, dy) in piece.cells(&self.pieces) {
             let cell_pos = BoardPosition {
                 x: board_pos.x + dx,
                 y: board_pos.y + dy,
@@ -74,7 +242,7 @@ impl Board {
         self.backup_state = self.state.clone();
 
         // Check if cells would be filled
-        let mut test_piece = piece.clone();
+        let mut test_piece = *piece;
         test_piece.position = board_pos;
         let row_filled = self.fills_row(&test_piece);
 
@@ -94,15 +262,15 @@ impl Board {
 
     pub fn try_rotation(
         &mut self,
-        piece: &PieceInstance,
+        piece: &PieceShape,
         rotation_direction: &RotationDirection,
     ) -> Option<BoardPosition> {
         // Save current position
         let current_pos = piece.position;
 
         // Create a test piece with the new rotation
-        let mut test_piece = piece.clone();
-        test_piece.rotate(rotation_direction);
+        let mut test_piece = *piece;
+        test_piece.rotate(rotation_direction, &self.pieces);
 
         // First, try rotation at the current position (no wall kick needed)
         if self.try_place(&test_piece, current_pos) == PlaceResult::PlaceOk {
@@ -112,13 +280,25 @@ impl Board {
             return Some(current_pos);
         }
 
-        // Get wall kick offsets for this rotation transition
-        let offsets = test_piece
-            .typ
-            .wall_kick_offsets(piece.rot_idx, test_piece.rot_idx);
+        // ARS won't kick a J, L or T that's held up in its center column
+        if self.kicks == WallKicks::Ars && self.center_column_blocked(&test_piece, current_pos) {
+            return None;
+        }
+
+        // Get wall kick offsets for this rotation transition; the table
+        // is cloned, cheaply, so the board can be tried while reading it
+        let kicks = self.kicks.clone();
+        let offsets = kicks.offsets(test_piece.typ, piece.rot_idx, test_piece.rot_idx);
 
-        // Try each offset, skipping [0,0] that was already tried)
-        for &(dx, dy) in offsets.iter().skip(1) {
+        // Mirrored kicks follow the table's own, if they're on
+        let mirrored = offsets
+            .iter()
+            .map(|&(dx, dy)| (-dx, dy))
+            .filter(|offset| self.mirror_kicks && !offsets.contains(offset));
+
+        // Try each offset, skipping [0,0] that was already tried
+        let tried: Vec<(isize, isize)> = offsets.iter().copied().chain(mirrored).collect();
+        for &(dx, dy) in tried.iter().filter(|&&offset| offset != (0, 0)) {
             let test_pos = BoardPosition {
                 x: current_pos.x + dx,
                 y: current_pos.y + dy,
@@ -142,42 +322,127 @@ impl Board {
         None
     }
 
-    // Quick check that a piece would fill a row
-    fn fills_row(&mut self, piece: &PieceInstance) -> bool {
-        piece.cells().iter().any(|&(dx, dy)| {
-            let cell_pos = BoardPosition {
-                x: piece.position.x + dx,
-                y: piece.position.y + dy,
-            };
+    // The ARS center column rule: the highest cell in the way of a rotated J, L
+    // or T, taking the leftmost in a row, is in the middle of its 3-wide box
+    fn center_column_blocked(&self, piece: &PieceShape, pos: BoardPosition) -> bool {
+        if !matches!(piece.typ, PieceType::J | PieceType::L | PieceType::T) {
+            return false;
+        }
+        piece
+            .cells(&self.pieces)
+            .iter()
+            .filter(|&&(dx, dy)| {
+                self.is_cell_filled(BoardPosition {
+                    x: pos.x + dx,
+                    y: pos.y + dy,
+                })
+            })
+            .max_by_key(|&&(dx, dy)| (dy, -dx))
+            .is_some_and(|&(dx, _)| dx == 1)
+    }
 
-            matches!(self.fill_cell(cell_pos), PlaceResult::RowFilled)
-        })
+    // A piece has locked with cells off the board since the last reset
+    pub fn topped_out(&self) -> bool {
+        self.topped_out
     }
 
-    // commit all cells of a pre-validated piece, returns any a Vec of any filled rows
-    pub fn commit_piece(&mut self, piece: &PieceInstance) -> Option<Vec<isize>> {
+    pub fn set_wall_kicks(&mut self, kicks: WallKicks) {
+        self.kicks = kicks;
+    }
+
+    pub fn set_mirror_kicks(&mut self, mirror_kicks: bool) {
+        self.mirror_kicks = mirror_kicks;
+    }
+
+    pub fn set_pieces(&mut self, pieces: Rc<PieceSet>) {
+        self.pieces = pieces;
+    }
+
+    pub fn pieces(&self) -> &PieceSet {
+        &self.pieces
+    }
+
+    // Quick check that a piece would fill a row
+    fn fills_row(&mut self, piece: &PieceShape) -> bool {
+        piece
+            .board_cells(&self.pieces)
+            .into_iter()
+            .any(|cell_pos| matches!(self.fill_cell(cell_pos), PlaceResult::RowFilled))
+    }
+
+    // commit all cells of a pre-validated piece, returns any a Vec of any filled rows.
+    // A cell that lands above the top tops the board out. Cells past the
+    // side walls or the floor mean the piece was never valid, and are dropped.
+    pub fn commit_piece(&mut self, piece: &PieceShape) -> Option<Vec<isize>> {
         let filled_rows = piece
-            .cells()
-            .iter()
-            .filter_map(|&(dx, dy)| {
-                let cell_pos = BoardPosition {
-                    x: piece.position.x + dx,
-                    y: piece.position.y + dy,
-                };
+            .board_cells(&self.pieces)
+            .into_iter()
+            .filter_map(|cell_pos| {
+                self.dirty.mark(cell_pos);
 
                 // Remember the y-index of each row that has been filled
-                (self.fill_cell(cell_pos) == PlaceResult::RowFilled).then_some(cell_pos.y)
+                match self.fill_cell(cell_pos) {
+                    PlaceResult::RowFilled => Some(cell_pos.y),
+                    PlaceResult::OutOfBounds if cell_pos.y >= self.height => {
+                        self.topped_out = true;
+                        None
+                    }
+                    PlaceResult::OutOfBounds => {
+                        println!(
+                            "GameOver: dropped piece cell outside the board at {:?}",
+                            cell_pos
+                        );
+                        None
+                    }
+                    PlaceResult::PlaceOk | PlaceResult::PlaceBad => None,
+                }
             })
             .collect::<Vec<isize>>();
 
         (!filled_rows.is_empty()).then_some(filled_rows)
     }
 
+    // Same outcome as commit_piece, without touching the board
+    pub fn simulate_commit(&self, piece: &PieceShape) -> SimResult {
+        let mut col_heights = self.state.col_score.clone();
+        let mut filled_rows = Vec::new();
+        let mut added: Vec<(isize, isize)> = Vec::with_capacity(4); // (row, cells added)
+
+        for pos in piece.board_cells(&self.pieces) {
+            if self.idx(pos.x, pos.y).is_none() {
+                continue;
+            }
+
+            let height = &mut col_heights[pos.x as usize];
+            *height = (*height).max(pos.y + 1);
+
+            let count = match added.iter_mut().find(|(row, _)| *row == pos.y) {
+                Some((_, count)) => {
+                    *count += 1;
+                    *count
+                }
+                None => {
+                    added.push((pos.y, 1));
+                    1
+                }
+            };
+            if self.state.row_score[pos.y as usize] + count == self.width {
+                filled_rows.push(pos.y);
+            }
+        }
+
+        SimResult {
+            filled_rows,
+            col_heights,
+        }
+    }
+
     // Fill the cell in the Grid abstraction & update the col/row scores
     fn fill_cell(&mut self, pos: BoardPosition) -> PlaceResult {
         self.idx(pos.x, pos.y)
             .map(|idx| {
                 self.state.grid[idx] = true;
+                self.state.filled_at[idx] = self.ticks;
                 self.state.update_col_score(pos);
 
                 // Notice if the row has been filled while updating row score
@@ -191,26 +456,89 @@ impl Board {
             .unwrap_or(PlaceResult::OutOfBounds)
     }
 
+    // Blocked cells count as filled
     pub fn is_cell_filled(&self, pos: BoardPosition) -> bool {
         self.idx(pos.x, pos.y)
-            .map(|idx| self.state.grid[idx])
+            .map(|idx| self.state.grid[idx] || self.state.blocked[idx])
+            .unwrap_or(false)
+    }
+
+    pub fn is_cell_blocked(&self, pos: BoardPosition) -> bool {
+        self.idx(pos.x, pos.y)
+            .map(|idx| self.state.blocked[idx])
             .unwrap_or(false)
     }
 
+    /************************ Mask *******************************/
+
+    // Block cells permanently, from ascii rows drawn top to bottom:
+    // '#' is blocked, '.' is open. Pieces can't enter blocked cells, and
+    // they count towards full rows but are never cleared.
+    pub fn set_mask_ascii(&mut self, ascii: &str) -> Result<(), MaskError> {
+        let rows: Vec<&str> = ascii
+            .lines()
+            .map(str::trim)
+            .filter(|row| !row.is_empty())
+            .collect();
+
+        let width = rows
+            .iter()
+            .map(|row| row.chars().count())
+            .max()
+            .unwrap_or(0);
+        if rows.len() != self.height as usize
+            || rows
+                .iter()
+                .any(|row| row.chars().count() != self.width as usize)
+        {
+            return Err(MaskError::WrongSize {
+                width,
+                height: rows.len(),
+            });
+        }
+
+        let mut blocked = vec![false; self.state.grid.len()];
+        for (i, row) in rows.iter().enumerate() {
+            let y = self.height - 1 - i as isize;
+            for (x, c) in row.chars().enumerate() {
+                match c {
+                    '#' => blocked[self.idx(x as isize, y).unwrap_or_default()] = true,
+                    '.' => {}
+                    c => return Err(MaskError::BadCell(c)),
+                }
+            }
+        }
+
+        // Anything under the mask is gone
+        for (idx, &blocked) in blocked.iter().enumerate() {
+            if blocked {
+                self.state.grid[idx] = false;
+            }
+        }
+        self.state.blocked = blocked;
+        self.state.recompute_scores(self.width as usize);
+        self.dirty = DirtyCells::all(self.width as usize, self.height as usize);
+        Ok(())
+    }
+
+    fn has_mask(&self) -> bool {
+        self.state.blocked.contains(&true)
+    }
+
     /************************ Piece Drop *******************************/
 
     // Find the lowest legal place for piece in its current x-position
     // This is the normal route and uses a quick calculation using col_score
-    pub fn calculate_drop(&mut self, piece: &PieceInstance) -> (BoardPosition, PlaceResult) {
+    pub fn calculate_drop(&mut self, piece: &PieceShape) -> (BoardPosition, PlaceResult) {
         // Use brute force method if piece is below overhang (col_score not useful)
         if self.is_below_overhang(piece) {
             return self.slow_calculate_drop(piece);
         }
 
-        let skirt = piece.typ.skirt(piece.rot_idx);
+        let skirt = self.pieces.skirt(piece.typ, piece.rot_idx);
 
         // Calculate grid min/max x
-        let (min_dx, max_dx) = piece.typ.minmax_x(piece.rot_idx);
+        let (min_dx, max_dx) = self.pieces.minmax_x(piece.typ, piece.rot_idx);
 
         // Find the max drop height valid for all cells of the piece:
         let mut max_required_y = isize::MIN;
@@ -258,7 +586,7 @@ impl Board {
 
     // For pieces below an overhang, col_score won't work, so step through each
     // cell position and check for the drop height.
-    fn slow_calculate_drop(&mut self, piece: &PieceInstance) -> (BoardPosition, PlaceResult) {
+    fn slow_calculate_drop(&mut self, piece: &PieceShape) -> (BoardPosition, PlaceResult) {
         if DEBUG {
             println!("Piece below overhang, starting brute force drop calculation.")
         }
@@ -296,7 +624,7 @@ impl Board {
     // and try again until no collisions remain.
     fn verify_drop_location(
         &mut self,
-        piece: &PieceInstance,
+        piece: &PieceShape,
         mut pos: BoardPosition,
     ) -> (BoardPosition, PlaceResult) {
         loop {
@@ -321,13 +649,8 @@ impl Board {
         }
     }
 
-    fn is_below_overhang(&self, piece: &PieceInstance) -> bool {
-        piece.cells().iter().any(|&(dx, dy)| {
-            let cell_pos = BoardPosition {
-                x: piece.position.x + dx,
-                y: piece.position.y + dy,
-            };
-
+    fn is_below_overhang(&self, piece: &PieceShape) -> bool {
+        piece.board_cells(&self.pieces).into_iter().any(|cell_pos| {
             // Check if this cell is below an overhang
             if let Some(score) = self.col_score(cell_pos.x) {
                 return cell_pos.y < (score - 1);
@@ -337,8 +660,48 @@ impl Board {
         })
     }
 
+    /************************ Garbage ***************************/
+
+    // Push rows in from the bottom, lifting the stack. rows[0] ends up lowest.
+    // Returns false if filled cells were pushed off the top.
+    pub fn add_garbage(&mut self, rows: &[Vec<bool>]) -> bool {
+        let count = rows.len().min(self.height as usize) as isize;
+        let fits = self
+            .state
+            .col_score
+            .iter()
+            .all(|&h| h + count <= self.height);
+
+        for y in (0..self.height).rev() {
+            for x in 0..self.width {
+                let pos = BoardPosition { x, y };
+                let filled_at = if y >= count {
+                    self.filled_at(BoardPosition { x, y: y - count })
+                } else {
+                    let filled = rows[y as usize].get(x as usize).copied().unwrap_or(false);
+                    filled.then_some(self.ticks)
+                };
+                self.set_cell_aged(pos, filled_at);
+            }
+        }
+
+        self.state.recompute_scores(self.width as usize);
+        fits
+    }
+
     /************************ Row clearing functions ***************************/
 
+    // Empty the whole grid. The player score is kept.
+    pub fn clear(&mut self) {
+        for idx in 0..self.state.grid.len() {
+            if let Some((x, y)) = self.de_idx(idx) {
+                self.set_cell(BoardPosition { x, y }, false);
+            }
+        }
+        // The mask stays, and still counts
+        self.state.recompute_scores(self.width as usize);
+    }
+
     // Orchestrate row clearing and sliding on RowFilled
     pub fn clear_rows(&mut self, rows: &[isize]) {
         // Sort rows in descending order
@@ -349,6 +712,11 @@ impl Board {
             println!("Rows to clear: {:?}", sorted_rows)
         }
 
+        if self.has_mask() {
+            self.clear_rows_masked(&sorted_rows);
+            return;
+        }
+
         // Move rows by an amount depending on how many rows were cleared below them
         self.handle_sliding(&sorted_rows);
 
@@ -358,6 +726,148 @@ impl Board {
         }
     }
 
+    // With a mask, each column settles on its own. Blocked cells stay put and
+    // hold up what sits on them; cells between them fall by the cleared rows below.
+    fn clear_rows_masked(&mut self, rows: &[isize]) {
+        for x in 0..self.width {
+            let mut fall = 0;
+            for y in 0..self.height {
+                let pos = BoardPosition { x, y };
+                if self.is_cell_blocked(pos) {
+                    fall = 0;
+                } else if rows.contains(&y) {
+                    self.set_cell(pos, false);
+                    fall += 1;
+                } else if fall > 0 {
+                    let filled_at = self.filled_at(pos);
+                    self.set_cell_aged(BoardPosition { x, y: y - fall }, filled_at);
+                    self.set_cell(pos, false);
+                }
+            }
+        }
+        self.state.recompute_scores(self.width as usize);
+    }
+
+    // Clear rows, then let each connected group of remaining cells fall
+    // on its own until it lands, so overhangs can drop into gaps below.
+    // Falling groups can complete more rows, which clear again in a chain.
+    pub fn clear_rows_sticky(&mut self, rows: &[isize]) -> ClearChain {
+        let mut chain = ClearChain::default();
+        let mut rows = rows.to_vec();
+
+        while !rows.is_empty() {
+            if DEBUG {
+                println!(
+                    "Rows to clear (sticky, step {}): {:?}",
+                    chain.depth() + 1,
+                    rows
+                )
+            }
+
+            for &row in &rows {
+                self.clear_row(row);
+            }
+
+            // A group can land on one that moves later, so settle until nothing falls
+            while self.drop_groups() {}
+
+            self.state.recompute_scores(self.width as usize);
+            chain.steps.push(rows);
+            rows = self.full_rows();
+        }
+        chain
+    }
+
+    // Drop floating cells to rest, as naive clear gravity would: every row
+    // above an empty one falls as if the empty row had been cleared
+    pub fn settle(&mut self) {
+        let empty_rows: Vec<isize> = (0..self.height)
+            .filter(|&y| self.state.row_score[y as usize] == 0)
+            .collect();
+
+        // Column by column, which leaves any mask where it is
+        self.clear_rows_masked(&empty_rows);
+    }
+
+    // Drop floating cells to rest, as sticky clear gravity would. Rows
+    // the groups fill on landing are left for the caller to clear.
+    pub fn settle_sticky(&mut self) {
+        while self.drop_groups() {}
+        self.state.recompute_scores(self.width as usize);
+    }
+
+    // Rows with every cell filled
+    fn full_rows(&self) -> Vec<isize> {
+        (0..self.height)
+            .filter(|&y| self.state.row_score[y as usize] == self.width)
+            .collect()
+    }
+
+    // Drop each connected group as far as it goes, lowest group first.
+    // Returns true if any group moved.
+    fn drop_groups(&mut self) -> bool {
+        let mut groups = self.connected_groups();
+        groups.sort_by_key(|group| group.iter().map(|pos| pos.y).min());
+
+        let mut moved = false;
+        for group in groups {
+            let ages: Vec<Option<u64>> = group.iter().map(|&pos| self.filled_at(pos)).collect();
+            for &pos in &group {
+                self.set_cell(pos, false);
+            }
+
+            let mut fall = 0;
+            while group.iter().all(|pos| {
+                let below = pos.y - fall - 1;
+                below >= 0 && !self.is_cell_filled(BoardPosition { x: pos.x, y: below })
+            }) {
+                fall += 1;
+            }
+
+            for (&pos, filled_at) in group.iter().zip(ages) {
+                let landed = BoardPosition {
+                    x: pos.x,
+                    y: pos.y - fall,
+                };
+                self.set_cell_aged(landed, filled_at);
+            }
+            moved |= fall > 0;
+        }
+        moved
+    }
+
+    // Filled cells split into 4-connected groups
+    fn connected_groups(&self) -> Vec<Vec<BoardPosition>> {
+        let mut seen = vec![false; self.state.grid.len()];
+        let mut groups = Vec::new();
+
+        for start in 0..self.state.grid.len() {
+            if !self.state.grid[start] || seen[start] {
+                continue;
+            }
+            seen[start] = true;
+
+            let mut group = Vec::new();
+            let mut stack = vec![start];
+            while let Some(idx) = stack.pop() {
+                let x = (idx % self.width as usize) as isize;
+                let y = (idx / self.width as usize) as isize;
+                group.push(BoardPosition { x, y });
+
+                for (nx, ny) in [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)] {
+                    if let Some(n) = self.idx(nx, ny) {
+                        if self.state.grid[n] && !seen[n] {
+                            seen[n] = true;
+                            stack.push(n);
+                        }
+                    }
+                }
+            }
+            groups.push(group);
+        }
+        groups
+    }
+
     // Handle row sliding based on a row's position
     fn handle_sliding(&mut self, cleared_rows: &[isize]) {
         if cleared_rows.is_empty() {
@@ -412,21 +922,22 @@ impl Board {
             // Now that the cells have been moved, clear the row.
             self.clear_row(row)
         }
+
+        // Finally, empty the rows vacated at the top of the stack. Needed when
+        // nothing sits above the cleared rows to slide down into them.
+        for row in (highest_filled_row - count).max(0)..highest_filled_row {
+            self.clear_row(row);
+        }
     }
 
     // Slide down an individual row, copying grid and row score
     fn slide_row_down(&mut self, row: isize, slide_val: isize) {
         let target_y = row - slide_val;
 
-        // Clear the target row
-        self.clear_row(target_y);
-
         // Move each cell to the target row
         for x in 0..self.width {
-            let source_cell = self.is_cell_filled(BoardPosition { x, y: row });
-            if let Some(idx) = self.idx(x, target_y) {
-                self.state.grid[idx] = source_cell;
-            }
+            let filled_at = self.filled_at(BoardPosition { x, y: row });
+            self.set_cell_aged(BoardPosition { x, y: target_y }, filled_at);
         }
 
         // Update row score by copying the old score to the new row
@@ -438,13 +949,31 @@ impl Board {
     // Clear a row completely
     fn clear_row(&mut self, row: isize) {
         for x in 0..self.width {
-            if let Some(idx) = self.idx(x, row) {
-                self.state.grid[idx] = false;
-            }
+            self.set_cell(BoardPosition { x, y: row }, false);
         }
 
         if row >= 0 && row < self.height {
-            self.state.reset_row_score(row);
+            self.state.reset_row_score(row, self.width as usize);
+        }
+    }
+
+    // Write a grid cell directly, marking it dirty if it changed.
+    // A newly filled cell starts at age 0. Blocked cells are left alone.
+    fn set_cell(&mut self, pos: BoardPosition, filled: bool) {
+        if let Some(idx) = self.idx(pos.x, pos.y) {
+            if !self.state.blocked[idx] && self.state.grid[idx] != filled {
+                self.state.grid[idx] = filled;
+                self.state.filled_at[idx] = self.ticks;
+                self.dirty.mark(pos);
+            }
+        }
+    }
+
+    // Write a cell along with the tick it was filled at, so moved cells keep their age
+    fn set_cell_aged(&mut self, pos: BoardPosition, filled_at: Option<u64>) {
+        self.set_cell(pos, filled_at.is_some());
+        if let (Some(idx), Some(filled_at)) = (self.idx(pos.x, pos.y), filled_at) {
+            self.state.filled_at[idx] = filled_at;
         }
     }
 
@@ -476,26 +1005,40 @@ impl Board {
         self.state.player_score()
     }
 
-    pub fn score_cleared_rows(&mut self, number_of_rows: usize) -> usize {
-        let delta: usize = match number_of_rows {
-            1 => 100,
-            2 => 200,
-            3 => 500,
-            4 => 800,
-            _ => 0, // anything else is not a valid row clearing
-        };
+    // `points` are for clearing 1 to 4 rows at once
+    pub fn score_cleared_rows(&mut self, number_of_rows: usize, points: &[usize; 4]) -> usize {
+        self.add_score(line_clear_points(number_of_rows, points))
+    }
+
+    // Each step of a cascade scores its rows times its depth in the chain
+    pub fn score_clear_chain(&mut self, chain: &ClearChain, points: &[usize; 4]) -> usize {
+        let delta = chain
+            .steps
+            .iter()
+            .enumerate()
+            .map(|(i, rows)| line_clear_points(rows.len(), points) * (i + 1))
+            .sum();
 
         self.add_score(delta)
     }
 
-    pub fn score_piece(&mut self, piece: &PieceInstance, hard_drop: bool) -> usize {
+    pub fn score_piece(&mut self, piece: &PieceShape, hard_drop: bool) -> usize {
         if hard_drop {
-            self.add_score(piece.cells().len() * 2)
+            self.add_score(piece.cells(&self.pieces).len() * 2)
         } else {
-            self.add_score(piece.cells().len())
+            self.add_score(piece.cells(&self.pieces).len())
         }
     }
 
+    // a point for each row the player soft-dropped the piece
+    pub fn score_soft_drop(&mut self, rows: usize) -> usize {
+        self.add_score(rows)
+    }
+
+    pub fn score_perfect_clear(&mut self) -> usize {
+        self.add_score(PERFECT_CLEAR_BONUS)
+    }
+
     pub fn add_score(&mut self, delta: usize) -> usize {
         self.state.add_score(delta)
     }
@@ -520,7 +1063,7 @@ impl Board {
         Some((y * self.width + x) as usize)
     }
 
-    fn _de_idx(&self, index: usize) -> Option<(isize, isize)> {
+    fn de_idx(&self, index: usize) -> Option<(isize, isize)> {
         if index >= self.state.grid.len() {
             return None;
         }
@@ -529,26 +1072,243 @@ impl Board {
         Some((x, y))
     }
 
+    // Filled cells in a row, None off the board
     pub fn row_score(&self, row: isize) -> Option<isize> {
+        self.try_row_score(row).ok()
+    }
+
+    pub fn try_row_score(&self, row: isize) -> Result<isize, BoardError> {
         if row >= self.height || row < 0 {
-            println!("Warning: out-of-bounds y: {}", row);
-            return None;
+            return Err(BoardError::NoSuchRow(row));
         }
-        Some(self.state.row_score[row as usize])
+        Ok(self.state.row_score[row as usize])
     }
 
+    // Height of a column, None off the board
     pub fn col_score(&self, col: isize) -> Option<isize> {
+        self.try_col_score(col).ok()
+    }
+
+    pub fn try_col_score(&self, col: isize) -> Result<isize, BoardError> {
         if col >= self.width || col < 0 {
-            println!("Warning: out-of-bounds x: {}", col);
-            return None;
+            return Err(BoardError::NoSuchColumn(col));
         }
-        Some(self.state.col_score[col as usize])
+        Ok(self.state.col_score[col as usize])
+    }
+
+    // Whether a cell is filled, blocked cells counting, telling cells off
+    // the board apart from empty ones as is_cell_filled doesn't
+    pub fn cell(&self, pos: BoardPosition) -> Result<bool, BoardError> {
+        self.idx(pos.x, pos.y)
+            .map(|idx| self.state.grid[idx] || self.state.blocked[idx])
+            .ok_or(BoardError::OffBoard(pos))
+    }
+
+    // Ticks since a filled cell was filled, None for an empty cell
+    pub fn cell_age(&self, pos: BoardPosition) -> Option<u64> {
+        self.filled_at(pos).map(|filled_at| self.ticks - filled_at)
+    }
+
+    fn filled_at(&self, pos: BoardPosition) -> Option<u64> {
+        self.idx(pos.x, pos.y)
+            .filter(|&idx| self.state.grid[idx])
+            .map(|idx| self.state.filled_at[idx])
+    }
+
+    // Advance the clock that cell ages count
+    pub fn tick(&mut self) {
+        self.ticks += 1;
+    }
+
+    // Empty cells below the top of their column
+    pub fn hole_count(&self) -> usize {
+        self.state
+            .col_score
+            .iter()
+            .enumerate()
+            .map(|(x, &height)| {
+                (0..height)
+                    .filter(|&y| !self.is_cell_filled(BoardPosition { x: x as isize, y }))
+                    .count()
+            })
+            .sum()
+    }
+
+    // Column and depth of the deepest 1-wide well: a column lower than both
+    // neighbours, the walls counting as full height. Ties go to the leftmost.
+    pub fn deepest_well(&self) -> Option<(isize, isize)> {
+        let heights = &self.state.col_score;
+        (0..heights.len())
+            .map(|x| {
+                let left = if x == 0 { self.height } else { heights[x - 1] };
+                let right = heights.get(x + 1).copied().unwrap_or(self.height);
+                (x as isize, left.min(right) - heights[x])
+            })
+            .filter(|&(_, depth)| depth > 0)
+            .fold(
+                None,
+                |deepest: Option<(isize, isize)>, well| match deepest {
+                    Some((_, depth)) if depth >= well.1 => deepest,
+                    _ => Some(well),
+                },
+            )
+    }
+
+    // Filled cells, not counting the mask
+    pub fn filled_count(&self) -> usize {
+        self.state.grid.iter().filter(|&&filled| filled).count()
+    }
+
+    // Share of the cells outside the mask that are filled, 0 to 1
+    pub fn fill_ratio(&self) -> f32 {
+        let blocked = self
+            .state
+            .blocked
+            .iter()
+            .filter(|&&blocked| blocked)
+            .count();
+        let open = self.state.grid.len() - blocked;
+        if open == 0 {
+            return 0.0;
+        }
+        self.filled_count() as f32 / open as f32
     }
 
     pub fn col_score_all(&self) -> &Vec<isize> {
         &self.state.col_score
     }
 
+    // Height of each column's surface, one above its top filled or blocked
+    // cell and 0 if it has none, left to right. Read off the grid itself,
+    // so it holds whatever clears have done to the col scores.
+    pub fn surface_profile(&self) -> Vec<isize> {
+        (0..self.width)
+            .map(|x| {
+                (0..self.height)
+                    .rev()
+                    .find(|&y| self.is_cell_filled(BoardPosition { x, y }))
+                    .map_or(0, |y| y + 1)
+            })
+            .collect()
+    }
+
+    /************************ Binary encoding *******************************/
+
+    // FNV-1a over the dimensions and each cell's filled and blocked flags, for
+    // peers to compare boards every tick. Same board, same value, on any machine.
+    pub fn checksum(&self) -> u64 {
+        let mut hash = FNV_OFFSET;
+        let mut feed = |byte: u8| {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        };
+
+        for dim in [self.width, self.height] {
+            (dim as u32).to_le_bytes().into_iter().for_each(&mut feed);
+        }
+        for (&filled, &blocked) in self.state.grid.iter().zip(&self.state.blocked) {
+            feed(filled as u8 | (blocked as u8) << 1);
+        }
+        hash
+    }
+
+    // Compact encoding for sending a board over the network:
+    // version u8, width u16, height u16, score u64 (all little endian),
+    // then the grid and the mask, each packed 8 cells per byte in row order.
+    pub fn encode(&self) -> Vec<u8> {
+        let grid = &self.state.grid;
+        let mut bytes = Vec::with_capacity(ENCODING_HEADER_LEN + 2 * grid.len().div_ceil(8));
+
+        // A board is never made bigger than MAX_SIDE, so the sides fit
+        bytes.push(ENCODING_VERSION);
+        bytes.extend_from_slice(&(self.width as u16).to_le_bytes());
+        bytes.extend_from_slice(&(self.height as u16).to_le_bytes());
+        bytes.extend_from_slice(&(self.score() as u64).to_le_bytes());
+
+        for cells in [grid, &self.state.blocked] {
+            for chunk in cells.chunks(8) {
+                let byte = chunk
+                    .iter()
+                    .enumerate()
+                    .fold(0u8, |acc, (i, &set)| acc | ((set as u8) << i));
+                bytes.push(byte);
+            }
+        }
+
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Board, DecodeError> {
+        if bytes.len() < ENCODING_HEADER_LEN {
+            return Err(DecodeError::Truncated {
+                expected: ENCODING_HEADER_LEN,
+                actual: bytes.len(),
+            });
+        }
+
+        let version = bytes[0];
+        if version != ENCODING_VERSION {
+            return Err(DecodeError::UnsupportedVersion(version));
+        }
+
+        let width = u16::from_le_bytes([bytes[1], bytes[2]]) as usize;
+        let height = u16::from_le_bytes([bytes[3], bytes[4]]) as usize;
+
+        // Check the cells are all there before making room for them
+        let cell_count = width * height;
+        let packed_len = cell_count.div_ceil(8);
+        let expected = ENCODING_HEADER_LEN + 2 * packed_len;
+        if bytes.len() < expected {
+            return Err(DecodeError::Truncated {
+                expected,
+                actual: bytes.len(),
+            });
+        }
+        let mut board =
+            Board::new(width, height).map_err(|_| DecodeError::InvalidSize { width, height })?;
+
+        let mut score = [0u8; 8];
+        score.copy_from_slice(&bytes[5..ENCODING_HEADER_LEN]);
+        let score = u64::from_le_bytes(score) as usize;
+
+        let (grid, mask) = bytes[ENCODING_HEADER_LEN..expected].split_at(packed_len);
+        for idx in 0..cell_count {
+            board.state.grid[idx] = grid[idx / 8] & (1 << (idx % 8)) != 0;
+            board.state.blocked[idx] = mask[idx / 8] & (1 << (idx % 8)) != 0;
+        }
+        board.state.recompute_scores(width);
+        board.state.add_score(score);
+        board.backup_state = board.state.clone();
+
+        Ok(board)
+    }
+
+    // Back to a fresh board: empty grid, zero score, nothing saved
+    pub fn reset(&mut self) {
+        self.clear();
+        self.state.player_score = 0;
+        self.saved_state = None;
+        self.topped_out = false;
+    }
+
+    // Change the board's size. Cells that still fit keep their place from
+    // the bottom left, and the rest are dropped.
+    pub fn resize(&mut self, width: usize, height: usize) -> Result<(), SizeError> {
+        check_size(width, height)?;
+
+        let old_width = self.width as usize;
+        self.state = self.state.resized(old_width, width, height);
+        self.backup_state = self.state.clone();
+        self.saved_state = self
+            .saved_state
+            .as_ref()
+            .map(|saved| saved.resized(old_width, width, height));
+        self.width = width as isize;
+        self.height = height as isize;
+        self.dirty = DirtyCells::all(width, height);
+        Ok(())
+    }
+
     pub fn save_state(&mut self) {
         self.saved_state = Some(self.state.clone());
     }
@@ -556,13 +1316,109 @@ impl Board {
     pub fn resume_state(&mut self) {
         if let Some(state) = &self.saved_state {
             self.state = state.clone();
+            self.dirty = DirtyCells::all(self.width as usize, self.height as usize);
+        }
+    }
+
+    /************************ Dirty cell tracking *******************************/
+
+    // Cells whose contents changed since the last call, for renderers that
+    // only redraw what changed. A new board starts with every cell dirty.
+    pub fn take_dirty(&mut self) -> Vec<BoardPosition> {
+        self.dirty.take()
+    }
+
+    // Cells filled differently here than in `other`, with their value here,
+    // bottom row first. Cells `other` doesn't have count as empty there.
+    pub fn diff(&self, other: &Board) -> Vec<(BoardPosition, bool)> {
+        (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| BoardPosition { x, y }))
+            .map(|pos| (pos, self.is_cell_filled(pos)))
+            .filter(|&(pos, filled)| filled != other.is_cell_filled(pos))
+            .collect()
+    }
+
+    // The active piece lives outside the grid, so the owner reports its moves.
+    // Marks the cells the piece vacated and the ones it now occupies.
+    pub fn mark_piece_moved(&mut self, from: Option<&PieceShape>, to: Option<&PieceShape>) {
+        let cells = |piece: Option<&PieceShape>| -> Vec<BoardPosition> {
+            piece
+                .map(|piece| piece.board_cells(&self.pieces).to_vec())
+                .unwrap_or_default()
+        };
+        let (from, to) = (cells(from), cells(to));
+
+        for pos in from.iter().filter(|pos| !to.contains(pos)) {
+            self.dirty.mark(*pos);
+        }
+        for pos in to.iter().filter(|pos| !from.contains(pos)) {
+            self.dirty.mark(*pos);
         }
     }
 }
 
+// Set of changed cells, kept both as flags (for de-duplication) and in order
+#[derive(Debug, Clone)]
+struct DirtyCells {
+    width: isize,
+    height: isize,
+    flags: Vec<bool>,
+    cells: Vec<BoardPosition>,
+}
+
+impl DirtyCells {
+    fn all(width: usize, height: usize) -> Self {
+        let (width, height) = (width as isize, height as isize);
+        Self {
+            width,
+            height,
+            flags: vec![true; (width * height) as usize],
+            cells: (0..height)
+                .flat_map(|y| (0..width).map(move |x| BoardPosition { x, y }))
+                .collect(),
+        }
+    }
+
+    fn mark(&mut self, pos: BoardPosition) {
+        if pos.x < 0 || pos.y < 0 || pos.x >= self.width || pos.y >= self.height {
+            return;
+        }
+        let flag = &mut self.flags[(pos.y * self.width + pos.x) as usize];
+        if !*flag {
+            *flag = true;
+            self.cells.push(pos);
+        }
+    }
+
+    fn take(&mut self) -> Vec<BoardPosition> {
+        for pos in &self.cells {
+            self.flags[(pos.y * self.width + pos.x) as usize] = false;
+        }
+        std::mem::take(&mut self.cells)
+    }
+}
+
+fn check_size(width: usize, height: usize) -> Result<(), SizeError> {
+    if width == 0 || height == 0 || width > MAX_SIDE || height > MAX_SIDE {
+        return Err(SizeError { width, height });
+    }
+    Ok(())
+}
+
+fn line_clear_points(number_of_rows: usize, points: &[usize; 4]) -> usize {
+    // anything but 1 to 4 rows is not a valid row clearing
+    number_of_rows
+        .checked_sub(1)
+        .and_then(|i| points.get(i))
+        .copied()
+        .unwrap_or(0)
+}
+
 #[derive(Debug, Clone)]
 struct BoardState {
     grid: Vec<bool>,       // which cells are filled
+    filled_at: Vec<u64>,   // tick each cell was filled at
+    blocked: Vec<bool>,    // cells masked off for good
     player_score: usize,   // player score
     row_score: Vec<isize>, // how many cells are filled in each row
     col_score: Vec<isize>, // height of the highest UNfilled cell of each col
@@ -572,14 +1428,57 @@ impl BoardState {
     pub fn new(width: usize, height: usize) -> Self {
         Self {
             grid: vec![false; width * height],
+            filled_at: vec![0; width * height],
+            blocked: vec![false; width * height],
             player_score: 0,
             row_score: vec![0; height],
             col_score: vec![0; width],
         }
     }
 
-    pub fn reset_row_score(&mut self, row: isize) {
-        self.row_score[row as usize] = 0;
+    // A copy at a new size, anchored at the bottom left
+    fn resized(&self, old_width: usize, width: usize, height: usize) -> Self {
+        let mut state = Self::new(width, height);
+        state.player_score = self.player_score;
+
+        let old_height = self.grid.len() / old_width.max(1);
+        for y in 0..height.min(old_height) {
+            for x in 0..width.min(old_width) {
+                let (from, to) = (y * old_width + x, y * width + x);
+                state.grid[to] = self.grid[from];
+                state.filled_at[to] = self.filled_at[from];
+                state.blocked[to] = self.blocked[from];
+            }
+        }
+        state.recompute_scores(width);
+        state
+    }
+
+    // Rebuild row and col scores from the grid
+    pub fn recompute_scores(&mut self, width: usize) {
+        self.row_score.iter_mut().for_each(|score| *score = 0);
+        self.col_score.iter_mut().for_each(|score| *score = 0);
+
+        for idx in 0..self.grid.len() {
+            if !self.grid[idx] && !self.blocked[idx] {
+                continue;
+            }
+            let pos = BoardPosition {
+                x: (idx % width) as isize,
+                y: (idx / width) as isize,
+            };
+            self.update_row_score(pos);
+            self.update_col_score(pos);
+        }
+    }
+
+    // An emptied row still has its blocked cells
+    pub fn reset_row_score(&mut self, row: isize, width: usize) {
+        let start = row as usize * width;
+        self.row_score[row as usize] = self.blocked[start..start + width]
+            .iter()
+            .filter(|&&blocked| blocked)
+            .count() as isize;
     }
 
     pub fn update_row_score(&mut self, pos: BoardPosition) -> isize {
@@ -603,3 +1502,623 @@ impl BoardState {
         delta
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::hash_map::DefaultHasher;
+
+    fn test_board() -> Board {
+        let mut board = Board::new(10, 20).unwrap();
+        let mut piece = PieceShape::new(PieceType::L, BoardPosition { x: 0, y: 0 });
+        board.commit_piece(&piece);
+        piece.typ = PieceType::I;
+        piece.rot_idx = 1;
+        piece.position = BoardPosition { x: 5, y: 0 };
+        board.commit_piece(&piece);
+        board.add_score(1234);
+        board
+    }
+
+    #[test]
+    fn test_encode_decode_round_trip() {
+        let board = test_board();
+        let bytes = board.encode();
+        assert_eq!(bytes.len(), ENCODING_HEADER_LEN + 2 * 25);
+
+        let decoded = Board::decode(&bytes).unwrap();
+        assert_eq!(decoded, board);
+        assert_eq!(decoded.score(), 1234);
+        assert_eq!(decoded.col_score_all(), board.col_score_all());
+        for y in 0..board.height {
+            assert_eq!(decoded.row_score(y), board.row_score(y));
+        }
+
+        // Empty boards survive too
+        let empty = Board::decode(&Board::new(7, 3).unwrap().encode()).unwrap();
+        assert_eq!((empty.width, empty.height), (7, 3));
+        assert_eq!(empty.col_score_all(), &vec![0; 7]);
+
+        // The mask goes along with the cells
+        let mut masked = Board::new(4, 3).unwrap();
+        masked.set_mask_ascii("#...\n....\n...#").unwrap();
+        masked.fill_cell(BoardPosition { x: 1, y: 0 });
+        let decoded = Board::decode(&masked.encode()).unwrap();
+        assert_eq!(decoded, masked);
+        assert_eq!(decoded.checksum(), masked.checksum());
+        assert!(decoded.is_cell_blocked(BoardPosition { x: 0, y: 2 }));
+        assert!(decoded.is_cell_blocked(BoardPosition { x: 3, y: 0 }));
+        assert!(!decoded.is_cell_blocked(BoardPosition { x: 1, y: 0 }));
+        assert_eq!(decoded.col_score_all(), masked.col_score_all());
+    }
+
+    #[test]
+    fn test_resize() {
+        // A full bottom row, a cell on the next, and one in the top right
+        let mut board = Board::new(4, 4).unwrap();
+        for x in 0..4 {
+            board.fill_cell(BoardPosition { x, y: 0 });
+        }
+        board.fill_cell(BoardPosition { x: 0, y: 1 });
+        board.fill_cell(BoardPosition { x: 3, y: 3 });
+
+        // Growing keeps every cell where it was
+        board.resize(6, 6).unwrap();
+        assert_eq!((board.width, board.height), (6, 6));
+        assert_eq!(board.filled_count(), 6);
+        assert!(board.is_cell_filled(BoardPosition { x: 3, y: 3 }));
+        assert_eq!(board.state.row_score[0], 4);
+        assert_eq!(board.col_score_all(), &vec![2, 1, 1, 4, 0, 0]);
+
+        // Shrinking keeps the bottom rows and drops what's off the edge
+        board.resize(3, 2).unwrap();
+        assert_eq!(board.filled_count(), 4);
+        assert_eq!(board.state.row_score, vec![3, 1]);
+        assert_eq!(board.col_score_all(), &vec![2, 1, 1]);
+        assert_eq!(board.take_dirty().len(), 6);
+    }
+
+    #[test]
+    fn test_degenerate_size() {
+        for (width, height) in [(0, 20), (10, 0), (0, 0)] {
+            assert_eq!(
+                Board::new(width, height).err(),
+                Some(SizeError { width, height })
+            );
+        }
+        assert!(Board::new(MAX_SIDE + 1, 1).is_err());
+        let mut board = Board::new(4, 4).unwrap();
+        assert!(board.resize(0, 4).is_err());
+        assert!(board.resize(4, MAX_SIDE + 1).is_err());
+        assert_eq!((board.width, board.height), (4, 4));
+
+        // A single cell is a board: it fills, and fills its row
+        let mut board = Board::new(1, 1).unwrap();
+        assert_eq!(board.midpoint_x(), 0);
+        assert!(matches!(
+            board.fill_cell(BoardPosition { x: 0, y: 0 }),
+            PlaceResult::RowFilled
+        ));
+        assert_eq!(board.col_score_all(), &vec![1]);
+    }
+
+    #[test]
+    fn test_checksum() {
+        let mut board = test_board();
+        let mut copy = Board::decode(&board.encode()).unwrap();
+        assert_eq!(board.checksum(), copy.checksum());
+
+        // Ages and scores don't count, but any one cell does
+        copy.tick();
+        copy.add_score(100);
+        assert_eq!(board.checksum(), copy.checksum());
+        let pos = BoardPosition { x: 9, y: 19 };
+        copy.set_cell(pos, true);
+        assert_ne!(board.checksum(), copy.checksum());
+
+        // and so does the shape of the same cells
+        assert_ne!(
+            Board::new(10, 20).unwrap().checksum(),
+            Board::new(20, 10).unwrap().checksum()
+        );
+        board.set_cell(pos, true);
+        assert_eq!(board.checksum(), copy.checksum());
+    }
+
+    #[test]
+    fn test_board_equality() {
+        let hash = |board: &Board| {
+            let mut hasher = DefaultHasher::new();
+            board.hash(&mut hasher);
+            hasher.finish()
+        };
+
+        // Same cells, different scores, ages and dirty cells
+        let board = test_board();
+        let mut other = test_board();
+        other.tick();
+        other.add_score(1);
+        other.take_dirty();
+        assert_eq!(board, other);
+        assert_eq!(hash(&board), hash(&other));
+
+        // One cell apart
+        other.set_cell(BoardPosition { x: 9, y: 19 }, true);
+        assert_ne!(board, other);
+        assert_ne!(hash(&board), hash(&other));
+        assert_ne!(Board::new(10, 20).unwrap(), Board::new(20, 10).unwrap());
+    }
+
+    #[test]
+    fn test_custom_kick_table() {
+        // Everything filled but the last column, a flat T at (1, 5),
+        // and room for the T rotated clockwise 3 cells to its right
+        let mut board = Board::new(10, 12).unwrap();
+        for y in 0..board.height {
+            for x in 0..board.width - 1 {
+                board.set_cell(BoardPosition { x, y }, true);
+            }
+        }
+        let pieces = PieceSet::default();
+        let piece = PieceShape::new(PieceType::T, BoardPosition { x: 1, y: 5 });
+        let mut rotated = piece;
+        rotated.rotate(&RotationDirection::Cw, &pieces);
+        for (cells, x) in [(piece.cells(&pieces), 1), (rotated.cells(&pieces), 4)] {
+            for &(dx, dy) in cells {
+                board.set_cell(
+                    BoardPosition {
+                        x: x + dx,
+                        y: 5 + dy,
+                    },
+                    false,
+                );
+            }
+        }
+
+        // No SRS kick reaches that far
+        assert_eq!(board.try_rotation(&piece, &RotationDirection::Cw), None);
+
+        // A table with a long kick for 0>>1 gets there
+        let mut jlszt = vec![vec![[0, 0]]; 8];
+        jlszt[0].push([3, 0]);
+        let i = vec![vec![[0, 0]]; 8];
+        board.set_wall_kicks(WallKicks::custom(&jlszt, &i).unwrap());
+        assert_eq!(
+            board.try_rotation(&piece, &RotationDirection::Cw),
+            Some(BoardPosition { x: 4, y: 5 })
+        );
+
+        // Tables must cover all 8 transitions
+        let err = WallKicks::custom(&jlszt[..7], &i).unwrap_err();
+        assert_eq!((err.pieces, err.transitions), ("JLSZT", 7));
+    }
+
+    #[test]
+    fn test_ars_rotation() {
+        // A flat T on the floor, with a cell over its middle column
+        let mut board = Board::new(10, 10).unwrap();
+        board.set_cell(BoardPosition { x: 4, y: 2 }, true);
+        let piece = PieceShape::new(PieceType::T, BoardPosition { x: 3, y: 0 });
+
+        // SRS kicks it one to the left
+        assert_eq!(
+            board.try_rotation(&piece, &RotationDirection::Cw),
+            Some(BoardPosition { x: 2, y: 0 })
+        );
+
+        // ARS refuses, as the cell in the way is in the center column
+        board.set_wall_kicks(WallKicks::Ars);
+        assert_eq!(board.try_rotation(&piece, &RotationDirection::Cw), None);
+
+        // Off center it kicks, to the left as the cell blocks the right too
+        board.set_cell(BoardPosition { x: 4, y: 2 }, false);
+        board.set_cell(BoardPosition { x: 5, y: 1 }, true);
+        assert_eq!(
+            board.try_rotation(&piece, &RotationDirection::Cw),
+            Some(BoardPosition { x: 2, y: 0 })
+        );
+    }
+
+    #[test]
+    fn test_deepest_well() {
+        // Four rows full but for the open columns, then a shallower dip at column 2
+        let board = |open: &[isize]| {
+            let mut board = Board::new(10, 20).unwrap();
+            for y in 0..4 {
+                for x in (0..10).filter(|x| !open.contains(x)) {
+                    board.fill_cell(BoardPosition { x, y });
+                }
+            }
+            for x in [0, 1, 3] {
+                board.fill_cell(BoardPosition { x, y: 4 });
+            }
+            board
+        };
+        assert_eq!(board(&[6]).deepest_well(), Some((6, 4)));
+
+        // A 2-wide gap isn't a well of either column
+        assert_eq!(board(&[6, 7]).deepest_well(), Some((2, 1)));
+        assert_eq!(Board::new(10, 20).unwrap().deepest_well(), None);
+    }
+
+    #[test]
+    fn test_decode_bad_data() {
+        let bytes = test_board().encode();
+
+        for len in [0, 4, ENCODING_HEADER_LEN, bytes.len() - 1] {
+            assert!(matches!(
+                Board::decode(&bytes[..len]),
+                Err(DecodeError::Truncated { .. })
+            ));
+        }
+
+        // A header claiming a huge board is short long before it's built
+        let mut huge = bytes[..ENCODING_HEADER_LEN].to_vec();
+        huge[1..5].copy_from_slice(&[0xff; 4]);
+        assert!(matches!(
+            Board::decode(&huge),
+            Err(DecodeError::Truncated { .. })
+        ));
+
+        let mut future = bytes.clone();
+        future[0] = ENCODING_VERSION + 1;
+        assert_eq!(
+            Board::decode(&future).err(),
+            Some(DecodeError::UnsupportedVersion(ENCODING_VERSION + 1))
+        );
+    }
+
+    // Board from rows drawn top to bottom, '#' filled
+    fn board_from_rows(rows: &[&str]) -> Board {
+        let mut board = Board::new(rows[0].len(), rows.len()).unwrap();
+        for (i, row) in rows.iter().enumerate() {
+            let y = (rows.len() - 1 - i) as isize;
+            for (x, c) in row.chars().enumerate() {
+                if c == '#' {
+                    board.fill_cell(BoardPosition { x: x as isize, y });
+                }
+            }
+        }
+        board
+    }
+
+    fn rows_of(board: &Board) -> Vec<String> {
+        (0..board.height)
+            .rev()
+            .map(|y| {
+                (0..board.width)
+                    .map(|x| {
+                        let pos = BoardPosition { x, y };
+                        match (board.is_cell_blocked(pos), board.is_cell_filled(pos)) {
+                            (true, _) => 'X',
+                            (false, true) => '#',
+                            (false, false) => '.',
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_sticky_gravity_drops_overhangs() {
+        // Clearing the full row leaves the right-hand pair floating over a gap
+        let rows = ["....", "..##", "####", "#..."];
+
+        let mut naive = board_from_rows(&rows);
+        naive.clear_rows(&[1]);
+        assert_eq!(rows_of(&naive), ["....", "....", "..##", "#..."]);
+
+        let mut sticky = board_from_rows(&rows);
+        sticky.clear_rows_sticky(&[1]);
+        assert_eq!(rows_of(&sticky), ["....", "....", "....", "#.##"]);
+        assert_eq!(sticky.col_score_all(), &vec![1, 0, 1, 1]);
+        assert_eq!(sticky.row_score(0), Some(3));
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut board = test_board();
+        assert!(board.filled_count() > 0);
+        board.take_dirty();
+
+        board.clear();
+        assert_eq!(board.filled_count(), 0);
+        assert_eq!(board.col_score_all(), &vec![0; 10]);
+        for y in 0..board.height {
+            assert_eq!(board.row_score(y), Some(0));
+        }
+        assert_eq!(board.score(), 1234);
+        assert_eq!(board.take_dirty().len(), 8);
+    }
+
+    // Like board_from_rows, with 'X' for blocked cells
+    fn masked_board(rows: &[&str]) -> Board {
+        let mut board = board_from_rows(rows);
+        let mask: Vec<String> = rows
+            .iter()
+            .map(|row| row.replace('#', ".").replace('X', "#"))
+            .collect();
+        board.set_mask_ascii(&mask.join("\n")).unwrap();
+        board
+    }
+
+    #[test]
+    fn test_mask_placement() {
+        let mut board = masked_board(&["....", "....", ".X..", "XX.."]);
+        let piece = PieceShape::new(PieceType::O, BoardPosition { x: 0, y: 2 });
+        assert_eq!(board.col_score_all(), &vec![1, 2, 0, 0]);
+        assert_eq!(board.filled_count(), 0);
+
+        // Blocked cells collide like filled ones
+        assert!(board.try_place(&piece, BoardPosition { x: 0, y: 1 }) == PlaceResult::PlaceBad);
+        assert_eq!(board.calculate_drop(&piece).0, BoardPosition { x: 0, y: 2 });
+
+        // and count towards a full row
+        let mut piece = piece;
+        piece.position = BoardPosition { x: 2, y: 0 };
+        assert!(board.try_place(&piece, piece.position) == PlaceResult::RowFilled);
+        assert_eq!(board.commit_piece(&piece), Some(vec![0]));
+
+        assert_eq!(
+            board.set_mask_ascii("...\n"),
+            Err(MaskError::WrongSize {
+                width: 3,
+                height: 1
+            })
+        );
+        assert_eq!(
+            board.set_mask_ascii("....\n....\n.o..\n...."),
+            Err(MaskError::BadCell('o'))
+        );
+    }
+
+    #[test]
+    fn test_masked_row_clears() {
+        let rows = ["#...", "X..#", "####", "X###"];
+
+        // The full rows go, the blocked cells in them stay and hold up what's above
+        let mut board = masked_board(&rows);
+        board.clear_rows(&[0, 1]);
+        assert_eq!(rows_of(&board), ["#...", "X...", "....", "X..#"]);
+        assert_eq!(board.row_score(0), Some(2));
+        assert_eq!(board.col_score_all(), &vec![4, 0, 0, 1]);
+
+        // Sticky gravity settles the same way here
+        let mut sticky = masked_board(&rows);
+        sticky.clear_rows_sticky(&[0, 1]);
+        assert_eq!(rows_of(&sticky), rows_of(&board));
+
+        // Clearing the whole board keeps the mask
+        board.clear();
+        assert_eq!(rows_of(&board), ["....", "X...", "....", "X..."]);
+        assert_eq!(board.row_score(2), Some(1));
+    }
+
+    #[test]
+    fn test_cell_ages() {
+        let mut board = board_from_rows(&["....", "#...", "####"]);
+        let old = BoardPosition { x: 0, y: 1 };
+        assert_eq!(board.cell_age(old), Some(0));
+        assert_eq!(board.cell_age(BoardPosition { x: 1, y: 1 }), None);
+
+        for _ in 0..3 {
+            board.tick();
+        }
+        assert_eq!(board.cell_age(old), Some(3));
+
+        // A new cell starts at 0; older ones keep their age as they slide down
+        board.fill_cell(BoardPosition { x: 1, y: 1 });
+        board.tick();
+        board.clear_rows(&[0]);
+        assert_eq!(board.cell_age(BoardPosition { x: 0, y: 0 }), Some(4));
+        assert_eq!(board.cell_age(BoardPosition { x: 1, y: 0 }), Some(1));
+        assert_eq!(board.cell_age(old), None);
+    }
+
+    #[test]
+    fn test_cascade_chain() {
+        // The left pair drops into the bottom row's gap and completes it
+        let mut board = board_from_rows(&["##..", "####", "..##"]);
+        let chain = board.clear_rows_sticky(&[1]);
+
+        assert_eq!(chain.steps, vec![vec![1], vec![0]]);
+        assert_eq!(chain.depth(), 2);
+        assert_eq!(chain.rows_cleared(), 2);
+        assert_eq!(rows_of(&board), ["....", "....", "...."]);
+
+        // The second step scores double
+        assert_eq!(
+            board.score_clear_chain(&chain, &[100, 300, 500, 800]),
+            100 + 2 * 100
+        );
+    }
+
+    #[test]
+    fn test_simulate_commit_matches_commit() {
+        // Two nearly full rows with a 2-wide gap on the right, so some drops clear lines
+        let mut board = Board::new(10, 20).unwrap();
+        let mut piece = PieceShape::new(PieceType::O, BoardPosition { x: 0, y: 0 });
+        for x in [0, 2, 4, 6] {
+            piece.position = BoardPosition { x, y: 0 };
+            board.commit_piece(&piece);
+        }
+
+        for typ in (0..7).map(PieceType::from_idx) {
+            for rot_idx in 0..board.pieces().rotation_count(typ) {
+                let (min_dx, max_dx) = board.pieces().minmax_x(typ, rot_idx);
+                for x in -min_dx..(board.width - max_dx) {
+                    let mut piece = piece;
+                    piece.typ = typ;
+                    piece.rot_idx = rot_idx;
+                    piece.position = BoardPosition {
+                        x,
+                        y: board.height - board.pieces().max_y(typ, rot_idx) - 1,
+                    };
+
+                    let mut committed = board.clone();
+                    piece.position = committed.calculate_drop(&piece).0;
+                    let sim = board.simulate_commit(&piece);
+                    let rows = committed.commit_piece(&piece).unwrap_or_default();
+
+                    assert_eq!(sim.filled_rows, rows, "{:?} rot {} x {}", typ, rot_idx, x);
+                    assert_eq!(&sim.col_heights, committed.col_score_all());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_surface_profile() {
+        // Heights read straight off the drawn rows
+        let scanned = |board: &Board| -> Vec<isize> {
+            let rows = rows_of(board);
+            (0..board.width as usize)
+                .map(|x| {
+                    rows.iter()
+                        .position(|row| row.as_bytes()[x] != b'.')
+                        .map_or(0, |i| (rows.len() - i) as isize)
+                })
+                .collect()
+        };
+
+        let mut board = board_from_rows(&["#...#", "##.##", "#####", "##.#.", "#####"]);
+        assert_eq!(board.surface_profile(), vec![5, 4, 3, 4, 5]);
+
+        // Naive and sticky clears, then the rows that fall into place
+        board.clear_rows(&[0]);
+        assert_eq!(board.surface_profile(), scanned(&board));
+        board.clear_rows_sticky(&[1]);
+        assert_eq!(board.surface_profile(), scanned(&board));
+        board.clear_rows(&[0]);
+        assert_eq!(board.surface_profile(), scanned(&board));
+        assert_eq!(&board.surface_profile(), board.col_score_all());
+
+        board.clear();
+        assert_eq!(board.surface_profile(), vec![0; 5]);
+    }
+
+    #[test]
+    fn test_mirrored_kicks() {
+        // Everything filled but a flat T at (4, 5) and room for it rotated
+        // clockwise 3 cells to its left
+        let mut board = Board::new(10, 12).unwrap();
+        for y in 0..board.height {
+            for x in 0..board.width {
+                board.set_cell(BoardPosition { x, y }, true);
+            }
+        }
+        let pieces = PieceSet::default();
+        let piece = PieceShape::new(PieceType::T, BoardPosition { x: 4, y: 5 });
+        let mut rotated = piece;
+        rotated.rotate(&RotationDirection::Cw, &pieces);
+        for (cells, x) in [(piece.cells(&pieces), 4), (rotated.cells(&pieces), 1)] {
+            for &(dx, dy) in cells {
+                let pos = BoardPosition {
+                    x: x + dx,
+                    y: 5 + dy,
+                };
+                board.set_cell(pos, false);
+            }
+        }
+
+        // The table only kicks 0>>1 to the right
+        let mut jlszt = vec![vec![[0, 0]]; 8];
+        jlszt[0].push([3, 0]);
+        let i = vec![vec![[0, 0]]; 8];
+        board.set_wall_kicks(WallKicks::custom(&jlszt, &i).unwrap());
+        assert_eq!(board.try_rotation(&piece, &RotationDirection::Cw), None);
+
+        // Mirrored, the same kick reaches the room on the left
+        board.set_mirror_kicks(true);
+        assert_eq!(
+            board.try_rotation(&piece, &RotationDirection::Cw),
+            Some(BoardPosition { x: 1, y: 5 })
+        );
+    }
+
+    #[test]
+    fn test_diff() {
+        let board = test_board();
+        assert!(board.diff(&board).is_empty());
+        assert!(board.diff(&board.clone()).is_empty());
+
+        // A cell filled, and one emptied
+        let mut other = board.clone();
+        let pos = BoardPosition { x: 9, y: 19 };
+        other.set_cell(pos, true);
+        assert_eq!(other.diff(&board), vec![(pos, true)]);
+        assert_eq!(board.diff(&other), vec![(pos, false)]);
+
+        let mut emptied = board.clone();
+        emptied.set_cell(BoardPosition { x: 2, y: 0 }, false);
+        assert_eq!(
+            emptied.diff(&board),
+            vec![(BoardPosition { x: 2, y: 0 }, false)]
+        );
+    }
+
+    #[test]
+    fn test_out_of_bounds_queries() {
+        let board = test_board();
+
+        // The simple queries give None
+        assert_eq!(board.row_score(-1), None);
+        assert_eq!(board.row_score(20), None);
+        assert_eq!(board.col_score(10), None);
+        assert_eq!(board.col_score(0), Some(2));
+
+        // The fallible ones say what was missing
+        assert_eq!(board.try_row_score(20), Err(BoardError::NoSuchRow(20)));
+        assert_eq!(board.try_col_score(-3), Err(BoardError::NoSuchColumn(-3)));
+        assert_eq!(board.try_row_score(0), Ok(2));
+
+        let off = BoardPosition { x: 10, y: 0 };
+        assert_eq!(board.cell(off), Err(BoardError::OffBoard(off)));
+        assert!(!board.is_cell_filled(off));
+        assert_eq!(board.cell(BoardPosition { x: 2, y: 0 }), Ok(true));
+        assert_eq!(board.cell(BoardPosition { x: 3, y: 0 }), Ok(false));
+        assert_eq!(
+            BoardError::OffBoard(off).to_string(),
+            "(10, 0) is off the board"
+        );
+    }
+
+    #[test]
+    fn test_settle() {
+        // A block floating over an empty floor comes down to it
+        let mut board = board_from_rows(&["....", ".##.", "....", "...."]);
+        board.settle();
+        assert_eq!(rows_of(&board), ["....", "....", "....", ".##."]);
+        assert_eq!(board.col_score_all(), &[0, 1, 1, 0]);
+
+        // Naive gravity keeps rows whole; sticky drops the block past the stub
+        let floating = ["....", ".##.", "....", "#..."];
+        let mut naive = board_from_rows(&floating);
+        naive.settle();
+        assert_eq!(rows_of(&naive), ["....", "....", ".##.", "#..."]);
+
+        let mut sticky = board_from_rows(&floating);
+        sticky.settle_sticky();
+        assert_eq!(rows_of(&sticky), ["....", "....", "....", "###."]);
+        assert_eq!(sticky.col_score_all(), &[1, 1, 1, 0]);
+    }
+
+    #[test]
+    fn test_commit_off_board() {
+        // Hanging off the side wall or the floor drops the cells without topping out
+        let mut board = Board::new(4, 4).unwrap();
+        let mut piece = PieceShape::new(PieceType::O, BoardPosition { x: -1, y: 0 });
+        board.commit_piece(&piece);
+        piece.position = BoardPosition { x: 2, y: -1 };
+        board.commit_piece(&piece);
+        assert!(!board.topped_out());
+        assert_eq!(board.col_score_all(), &[2, 0, 1, 1]);
+
+        // Only cells above the top row do
+        piece.position = BoardPosition { x: 1, y: 3 };
+        board.commit_piece(&piece);
+        assert!(board.topped_out());
+    }
+}
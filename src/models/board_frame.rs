@@ -0,0 +1,289 @@
+// src/models/board_frame.rs
+//
+// A lightweight snapshot of what a board looks like on a single tick.
+// Frames record the engine's output (not the player's input), so a viewer
+// can play a game back without running the engine itself.
+
+use crate::{
+    models::{Board, PieceSet, PieceShape, PieceType},
+    views::BoardPosition,
+};
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+const FILE_MAGIC: &[u8; 4] = b"GOF1";
+
+// most cells a frame read back may have: far more than any real board, so a
+// bigger header is a corrupt file and isn't allocated for
+const MAX_FRAME_CELLS: usize = 1 << 24;
+
+// The active piece as it appears in a frame
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct FramePiece {
+    pub typ: PieceType,
+    pub rot_idx: usize,
+    pub position: BoardPosition,
+}
+
+impl FramePiece {
+    // Board positions of the piece's cells, in the shapes of `pieces`
+    pub fn cells<'a>(&'a self, pieces: &'a PieceSet) -> impl Iterator<Item = BoardPosition> + 'a {
+        pieces
+            .get_rotation(self.typ, self.rot_idx)
+            .iter()
+            .map(|&(dx, dy)| BoardPosition {
+                x: self.position.x + dx,
+                y: self.position.y + dy,
+            })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoardFrame {
+    pub width: isize,
+    pub height: isize,
+    cells: Vec<u8>, // filled cells packed 8 per byte, row-ordered
+    pub active_piece: Option<FramePiece>,
+    pub score: usize,
+}
+
+impl BoardFrame {
+    pub fn capture(board: &Board, active_piece: Option<&PieceShape>) -> Self {
+        let cell_count = (board.width * board.height) as usize;
+        let mut cells = vec![0u8; cell_count.div_ceil(8)];
+
+        for y in 0..board.height {
+            for x in 0..board.width {
+                if board.is_cell_filled(BoardPosition { x, y }) {
+                    let idx = (y * board.width + x) as usize;
+                    cells[idx / 8] |= 1 << (idx % 8);
+                }
+            }
+        }
+
+        Self {
+            width: board.width,
+            height: board.height,
+            cells,
+            active_piece: active_piece.map(|piece| FramePiece {
+                typ: piece.typ,
+                rot_idx: piece.rot_idx,
+                position: piece.position,
+            }),
+            score: board.score(),
+        }
+    }
+
+    pub fn is_cell_filled(&self, pos: BoardPosition) -> bool {
+        if pos.x < 0 || pos.y < 0 || pos.x >= self.width || pos.y >= self.height {
+            return false;
+        }
+        let idx = (pos.y * self.width + pos.x) as usize;
+        self.cells[idx / 8] & (1 << (idx % 8)) != 0
+    }
+
+    /************************ Serialization *******************************/
+
+    // Layout: width u16, height u16, score u64, piece flag u8
+    // [piece type u8, rotation u8, x i16, y i16], packed cells
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&(self.width as u16).to_le_bytes())?;
+        writer.write_all(&(self.height as u16).to_le_bytes())?;
+        writer.write_all(&(self.score as u64).to_le_bytes())?;
+
+        match &self.active_piece {
+            Some(piece) => {
+                writer.write_all(&[1, piece.typ.idx() as u8, piece.rot_idx as u8])?;
+                writer.write_all(&(piece.position.x as i16).to_le_bytes())?;
+                writer.write_all(&(piece.position.y as i16).to_le_bytes())?;
+            }
+            None => writer.write_all(&[0])?,
+        }
+
+        writer.write_all(&self.cells)
+    }
+
+    pub fn read_from<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let width = u16::from_le_bytes(read_array(reader)?) as isize;
+        let height = u16::from_le_bytes(read_array(reader)?) as isize;
+        let score = u64::from_le_bytes(read_array(reader)?) as usize;
+
+        let [has_piece] = read_array(reader)?;
+        let active_piece = if has_piece == 1 {
+            let [typ, rot_idx] = read_array(reader)?;
+            let x = i16::from_le_bytes(read_array(reader)?) as isize;
+            let y = i16::from_le_bytes(read_array(reader)?) as isize;
+            Some(FramePiece {
+                typ: PieceType::from_idx(typ as usize),
+                rot_idx: rot_idx as usize,
+                position: BoardPosition { x, y },
+            })
+        } else {
+            None
+        };
+
+        let cell_count = (width as usize)
+            .checked_mul(height as usize)
+            .filter(|&count| count <= MAX_FRAME_CELLS)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("a {}x{} frame is too big", width, height),
+                )
+            })?;
+        let mut cells = vec![0u8; cell_count.div_ceil(8)];
+        reader.read_exact(&mut cells)?;
+
+        Ok(Self {
+            width,
+            height,
+            cells,
+            active_piece,
+            score,
+        })
+    }
+}
+
+fn read_array<R: Read, const N: usize>(reader: &mut R) -> io::Result<[u8; N]> {
+    let mut buf = [0u8; N];
+    reader.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+/************************ Frame buffer *******************************/
+
+// Ring buffer holding the most recent frames of a board
+#[derive(Debug)]
+pub struct FrameBuffer {
+    frames: VecDeque<BoardFrame>,
+    capacity: usize,
+}
+
+impl FrameBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            frames: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    // Oldest frame is dropped once the buffer is full
+    pub fn push(&mut self, frame: BoardFrame) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.frames.len() == self.capacity {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(frame);
+    }
+
+    pub fn frames(&self) -> impl Iterator<Item = &BoardFrame> {
+        self.frames.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.frames.clear();
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(FILE_MAGIC)?;
+        writer.write_all(&(self.frames.len() as u32).to_le_bytes())?;
+        for frame in &self.frames {
+            frame.write_to(&mut writer)?;
+        }
+        writer.flush()
+    }
+}
+
+// Load every frame from a file written by FrameBuffer::save
+pub fn load_frames(path: &Path) -> io::Result<Vec<BoardFrame>> {
+    let mut reader = BufReader::new(File::open(path)?);
+
+    let magic: [u8; 4] = read_array(&mut reader)?;
+    if &magic != FILE_MAGIC {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not a board frame file",
+        ));
+    }
+
+    let count = u32::from_le_bytes(read_array(&mut reader)?) as usize;
+    (0..count)
+        .map(|_| BoardFrame::read_from(&mut reader))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_frames() -> Vec<BoardFrame> {
+        let mut board = Board::new(10, 20).unwrap();
+        let mut frames = vec![BoardFrame::capture(&board, None)];
+
+        let mut piece = PieceShape::new(PieceType::T, BoardPosition { x: 3, y: 18 });
+        frames.push(BoardFrame::capture(&board, Some(&piece)));
+
+        piece.rot_idx = 1;
+        piece.position = BoardPosition { x: 0, y: 0 };
+        board.commit_piece(&piece);
+        board.add_score(42);
+        frames.push(BoardFrame::capture(&board, None));
+
+        frames
+    }
+
+    #[test]
+    fn test_frame_round_trip() {
+        let frames = test_frames();
+
+        let mut buffer = FrameBuffer::new(2);
+        for frame in &frames {
+            buffer.push(frame.clone());
+        }
+        // Ring buffer only keeps the newest frames
+        assert_eq!(buffer.len(), 2);
+        assert_eq!(buffer.frames().next(), Some(&frames[1]));
+
+        let path = std::env::temp_dir().join("gameover_board_frame_test.gof");
+        buffer.save(&path).unwrap();
+        let loaded = load_frames(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(loaded, frames[1..]);
+
+        let last = &loaded[1];
+        assert_eq!(last.score, 42);
+        assert!(last.is_cell_filled(BoardPosition { x: 1, y: 0 }));
+        assert!(last.is_cell_filled(BoardPosition { x: 2, y: 1 }));
+        assert!(!last.is_cell_filled(BoardPosition { x: 0, y: 0 }));
+        assert_eq!(loaded[0].active_piece.map(|p| p.position.x), Some(3),);
+    }
+
+    #[test]
+    fn test_oversized_frame() {
+        // A header claiming the biggest board there could be, and no cells
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&u16::MAX.to_le_bytes());
+        bytes.extend_from_slice(&u16::MAX.to_le_bytes());
+        bytes.extend_from_slice(&0u64.to_le_bytes());
+        bytes.push(0);
+
+        let err = BoardFrame::read_from(&mut bytes.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
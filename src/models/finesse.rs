@@ -0,0 +1,94 @@
+// src/models/finesse.rs
+//
+// Finesse: the fewest moves and rotations that could have placed a piece.
+// Searches from the spawn position, then hard drops. Drops aren't counted.
+
+use crate::{
+    models::{Board, PieceShape, PlaceResult},
+    views::RotationDirection,
+};
+use std::collections::{HashSet, VecDeque};
+
+// Fewest L/R/rotate inputs that take `spawn` to where `placed` ended up.
+// None if no such inputs reach it, as for a piece tucked in after a soft drop.
+pub fn min_inputs(board: &Board, spawn: &PieceShape, placed: &PieceShape) -> Option<usize> {
+    let mut board = board.clone();
+    let target = landed_cells(&mut board, placed, false);
+
+    let mut seen = HashSet::new();
+    let mut queue = VecDeque::new();
+    seen.insert((spawn.position.x, spawn.position.y, spawn.rot_idx));
+    queue.push_back((*spawn, 0));
+
+    while let Some((piece, inputs)) = queue.pop_front() {
+        if landed_cells(&mut board, &piece, true) == target {
+            return Some(inputs);
+        }
+
+        for next in neighbours(&mut board, &piece) {
+            if seen.insert((next.position.x, next.position.y, next.rot_idx)) {
+                queue.push_back((next, inputs + 1));
+            }
+        }
+    }
+    None
+}
+
+// Pieces one input away: a step left, a step right, or a rotation
+fn neighbours(board: &mut Board, piece: &PieceShape) -> Vec<PieceShape> {
+    let mut next = Vec::new();
+
+    for dx in [-1, 1] {
+        let mut moved = *piece;
+        moved.position.x += dx;
+        if board.try_place(&moved, moved.position) == PlaceResult::PlaceOk {
+            next.push(moved);
+        }
+    }
+
+    if let Some(pos) = board.try_rotation(piece, &RotationDirection::Cw) {
+        let mut rotated = *piece;
+        rotated.rotate(&RotationDirection::Cw, board.pieces());
+        rotated.position = pos;
+        next.push(rotated);
+    }
+    next
+}
+
+// Sorted cells of a piece, hard dropped first if `drop` is set
+fn landed_cells(board: &mut Board, piece: &PieceShape, drop: bool) -> Vec<(isize, isize)> {
+    let position = match drop {
+        true => board.calculate_drop(piece).0,
+        false => piece.position,
+    };
+    let mut cells: Vec<(isize, isize)> = piece
+        .cells(board.pieces())
+        .iter()
+        .map(|&(dx, dy)| (position.x + dx, position.y + dy))
+        .collect();
+    cells.sort();
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{models::PieceType, views::BoardPosition};
+
+    #[test]
+    fn test_min_inputs() {
+        let board = Board::new(10, 20).unwrap();
+        let spawn = PieceShape::new(PieceType::T, BoardPosition { x: 4, y: 18 });
+
+        // Flat against the left wall, upside down
+        let mut placed = spawn;
+        placed.rot_idx = 2;
+        placed.position = BoardPosition { x: 0, y: -1 };
+        assert_eq!(min_inputs(&board, &spawn, &placed), Some(6));
+
+        // Dropped straight down takes nothing
+        let mut placed = spawn;
+        placed.position.y = 0;
+        assert_eq!(min_inputs(&board, &spawn, &placed), Some(0));
+    }
+}
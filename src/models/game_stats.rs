@@ -0,0 +1,91 @@
+// src/models/game_stats.rs
+//
+// Running tallies for one game, for the post-game summary
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GameStats {
+    pub pieces_placed: usize,
+    pub singles: usize,
+    pub doubles: usize,
+    pub triples: usize,
+    pub tetrises: usize,
+    pub lines: usize,
+    pub tspins: usize,
+    pub max_combo: usize, // most pieces in a row that cleared lines
+    pub perfect_clears: usize,
+    pub finesse_faults: usize, // pieces placed with more inputs than needed
+    combo: usize,              // current run of clearing pieces
+    back_to_back: bool,        // the last clear was a tetris or t-spin
+    level_ups: usize,          // levels gained since level 1
+}
+
+const LINES_PER_LEVEL: usize = 10;
+
+impl GameStats {
+    // A piece was committed to the board
+    pub fn record_lock(&mut self, cleared: bool, tspin: bool) {
+        self.pieces_placed += 1;
+        if tspin {
+            self.tspins += 1;
+        }
+
+        if cleared {
+            self.combo += 1;
+            self.max_combo = self.max_combo.max(self.combo);
+        } else {
+            self.combo = 0;
+        }
+    }
+
+    // The rows a piece filled were cleared
+    pub fn record_clear(&mut self, rows: usize, tspin: bool, perfect_clear: bool) {
+        self.lines += rows;
+        self.back_to_back = tspin || rows >= 4;
+        match rows {
+            1 => self.singles += 1,
+            2 => self.doubles += 1,
+            3 => self.triples += 1,
+            4 => self.tetrises += 1,
+            _ => {}
+        }
+        if perfect_clear {
+            self.perfect_clears += 1;
+        }
+    }
+
+    // A piece took more moves and rotations than it needed
+    pub fn record_finesse_fault(&mut self) {
+        self.finesse_faults += 1;
+    }
+
+    pub fn combo(&self) -> usize {
+        self.combo
+    }
+
+    pub fn back_to_back(&self) -> bool {
+        self.back_to_back
+    }
+
+    // Level up for each threshold the lines have passed. `level_lines` lists
+    // the lines each level takes in turn, the last repeating; empty is 10.
+    pub fn update_level(&mut self, level_lines: &[usize]) {
+        while self.lines >= lines_to_reach(level_lines, self.level() + 1) {
+            self.level_ups += 1;
+        }
+    }
+
+    // Starts at 1 and goes up as update_level finds the lines for it
+    pub fn level(&self) -> usize {
+        self.level_ups + 1
+    }
+}
+
+// Lines cleared in all to reach `level`
+fn lines_to_reach(level_lines: &[usize], level: usize) -> usize {
+    (0..level.saturating_sub(1))
+        .map(|i| {
+            let lines = level_lines.get(i).or(level_lines.last());
+            lines.copied().unwrap_or(LINES_PER_LEVEL).max(1)
+        })
+        .sum()
+}
@@ -1,8 +1,20 @@
 // src/models/mod.rs
 
 pub mod board;
+pub mod board_frame;
+pub mod finesse;
+pub mod game_stats;
 pub mod piece;
+pub mod piece_bag;
+pub mod piece_shape;
 pub mod wall_kick;
 
-pub use board::{Board, PlaceResult};
-pub use piece::PieceType;
+pub use board::{
+    Board, BoardError, ClearChain, DecodeError, MaskError, PlaceResult, SimResult, SizeError,
+};
+pub use board_frame::{BoardFrame, FrameBuffer, FramePiece};
+pub use game_stats::GameStats;
+pub use piece::{PieceDef, PieceSet, PieceSetError, PieceType};
+pub use piece_bag::PieceBag;
+pub use piece_shape::PieceShape;
+pub use wall_kick::{KickTableError, WallKicks};
@@ -8,10 +8,14 @@
 use crate::models::wall_kick::{
     get_wall_kick_index, WallKickOffset, I_WALL_KICKS, JLSZT_WALL_KICKS, O_WALL_KICKS,
 };
+use std::fmt;
 
 // Type alias for a Tetromino block
 type Block = (isize, isize);
 
+// cells in every piece; the board and the AI assume tetrominoes
+const PIECE_CELLS: usize = 4;
+
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum PieceType {
     I,
@@ -24,6 +28,7 @@ pub enum PieceType {
 }
 
 impl PieceType {
+    // The standard shapes. Boards play with the shapes of their PieceSet.
     pub const fn rotations(&self) -> &'static [[Block; 4]; 4] {
         match self {
             PieceType::I => &I_ROTATIONS,
@@ -36,13 +41,165 @@ impl PieceType {
         }
     }
 
+    pub fn wall_kick_offsets(&self, from_rot: usize, to_rot: usize) -> &'static [WallKickOffset] {
+        let kick_idx = get_wall_kick_index(from_rot, to_rot);
+
+        match self {
+            PieceType::I => I_WALL_KICKS[kick_idx],
+            PieceType::O => O_WALL_KICKS[0],
+            _ => JLSZT_WALL_KICKS[kick_idx],
+        }
+    }
+
+    /******************* Utility Methods ******************/
+    const ALL: [PieceType; 7] = [
+        PieceType::I,
+        PieceType::J,
+        PieceType::L,
+        PieceType::S,
+        PieceType::Z,
+        PieceType::T,
+        PieceType::O,
+    ];
+
+    pub fn from_idx(idx: usize) -> Self {
+        let safe_idx = idx % Self::ALL.len();
+        Self::ALL[safe_idx]
+    }
+
+    pub fn idx(&self) -> usize {
+        Self::ALL.iter().position(|typ| typ == self).unwrap()
+    }
+
+    // Case-insensitive: "t" or "T"
+    pub fn named(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|typ| format!("{:?}", typ).eq_ignore_ascii_case(name))
+    }
+}
+
+/******************* Piece Sets ******************/
+
+// One piece's shape and color as a data file gives it
+#[derive(Debug, Clone, PartialEq)]
+pub struct PieceDef {
+    pub typ: PieceType,
+    pub rotations: Vec<Vec<[isize; 2]>>, // cells of 1, 2 or 4 rotations, repeated to make 4
+    pub color: Option<[f32; 3]>,
+}
+
+// The shapes and colors a board plays with: the standard pieces unless a
+// data file replaces some of them
+#[derive(Debug, Clone, PartialEq)]
+pub struct PieceSet {
+    rotations: [[[Block; 4]; 4]; 7], // by PieceType::idx
+    colors: [Option<[f32; 3]>; 7],
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PieceSetError {
+    UnknownPiece(String),
+    Rotations {
+        typ: PieceType,
+        count: usize,
+    },
+    Cells {
+        typ: PieceType,
+        rotation: usize,
+        count: usize,
+    },
+}
+
+impl fmt::Display for PieceSetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PieceSetError::UnknownPiece(name) => write!(f, "no piece is called {:?}", name),
+            PieceSetError::Rotations { typ, count } => {
+                write!(f, "{:?} has {} rotations, not 1, 2 or 4", typ, count)
+            }
+            PieceSetError::Cells {
+                typ,
+                rotation,
+                count,
+            } => write!(
+                f,
+                "{:?} rotation {} has {} different cells instead of {}",
+                typ, rotation, count, PIECE_CELLS
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PieceSetError {}
+
+impl Default for PieceSet {
+    fn default() -> Self {
+        Self {
+            rotations: PieceType::ALL.map(|typ| *typ.rotations()),
+            colors: [None; 7],
+        }
+    }
+}
+
+impl PieceSet {
+    // The standard pieces with `defs` swapped in. Every rotation of a piece
+    // must have the same four cells' worth, so sizes can't change mid-game.
+    pub fn custom(defs: &[PieceDef]) -> Result<Self, PieceSetError> {
+        let mut set = Self::default();
+
+        for def in defs {
+            let typ = def.typ;
+            let count = def.rotations.len();
+            if !matches!(count, 1 | 2 | 4) {
+                return Err(PieceSetError::Rotations { typ, count });
+            }
+
+            let mut rotations = [[(0, 0); 4]; 4];
+            for (rotation, cells) in def.rotations.iter().enumerate() {
+                let mut distinct: Vec<Block> = cells.iter().map(|&[x, y]| (x, y)).collect();
+                distinct.sort_unstable();
+                distinct.dedup();
+                if distinct.len() != PIECE_CELLS || cells.len() != PIECE_CELLS {
+                    return Err(PieceSetError::Cells {
+                        typ,
+                        rotation,
+                        count: distinct.len(),
+                    });
+                }
+                for (block, &[x, y]) in rotations[rotation].iter_mut().zip(cells) {
+                    *block = (x, y);
+                }
+            }
+            for rotation in count..4 {
+                rotations[rotation] = rotations[rotation % count];
+            }
+
+            set.rotations[typ.idx()] = rotations;
+            set.colors[typ.idx()] = def.color;
+        }
+        Ok(set)
+    }
+
+    pub fn rotations(&self, typ: PieceType) -> &[[Block; 4]; 4] {
+        &self.rotations[typ.idx()]
+    }
+
+    pub fn get_rotation(&self, typ: PieceType, rot_idx: usize) -> &[Block; 4] {
+        &self.rotations(typ)[rot_idx % self.rotation_count(typ)]
+    }
+
+    pub fn rotation_count(&self, typ: PieceType) -> usize {
+        self.rotations(typ).len()
+    }
+
     // returns a vec where each index is a dx relative to min_x
     // and value is the lowest y-value for that x-coordinate
-    pub fn skirt(&self, rot_idx: usize) -> Vec<isize> {
-        let piece = self.get_rotation(rot_idx);
+    pub fn skirt(&self, typ: PieceType, rot_idx: usize) -> Vec<isize> {
+        let piece = self.get_rotation(typ, rot_idx);
 
         // Find min/max x to determine skirt width
-        let (min_x, max_x) = self.minmax_x(rot_idx);
+        let (min_x, max_x) = self.minmax_x(typ, rot_idx);
 
         // Initialize skirt with maximum possible y-values
         let width = (max_x - min_x + 1) as usize;
@@ -59,19 +216,9 @@ impl PieceType {
         skirt
     }
 
-    pub fn wall_kick_offsets(&self, from_rot: usize, to_rot: usize) -> &'static [WallKickOffset] {
-        let kick_idx = get_wall_kick_index(from_rot, to_rot);
-
-        match self {
-            PieceType::I => I_WALL_KICKS[kick_idx],
-            PieceType::O => O_WALL_KICKS[0],
-            _ => JLSZT_WALL_KICKS[kick_idx],
-        }
-    }
-
     // returns the minimum and maximum x offsets
-    pub fn minmax_x(&self, rot_idx: usize) -> (isize, isize) {
-        let piece = self.get_rotation(rot_idx);
+    pub fn minmax_x(&self, typ: PieceType, rot_idx: usize) -> (isize, isize) {
+        let piece = self.get_rotation(typ, rot_idx);
         (
             piece.iter().map(|&(x, _)| x).min().unwrap(),
             piece.iter().map(|&(x, _)| x).max().unwrap(),
@@ -79,39 +226,36 @@ impl PieceType {
     }
 
     // returns the maximum x offset
-    pub fn max_x(&self, rot_idx: usize) -> isize {
-        let piece = self.get_rotation(rot_idx);
+    pub fn max_x(&self, typ: PieceType, rot_idx: usize) -> isize {
+        let piece = self.get_rotation(typ, rot_idx);
         piece.iter().map(|&(x, _)| x).max().unwrap()
     }
 
     // returns the highest y offset
-    pub fn max_y(&self, rot_idx: usize) -> isize {
-        let piece = self.get_rotation(rot_idx);
+    pub fn max_y(&self, typ: PieceType, rot_idx: usize) -> isize {
+        let piece = self.get_rotation(typ, rot_idx);
         piece.iter().map(|&(_, y)| y).max().unwrap()
     }
 
-    /******************* Utility Methods ******************/
-    const ALL: [PieceType; 7] = [
-        PieceType::I,
-        PieceType::J,
-        PieceType::L,
-        PieceType::S,
-        PieceType::Z,
-        PieceType::T,
-        PieceType::O,
-    ];
-
-    pub fn from_idx(idx: usize) -> Self {
-        let safe_idx = idx % Self::ALL.len();
-        Self::ALL[safe_idx]
-    }
-
-    pub const fn get_rotation(&self, rot_idx: usize) -> &'static [Block; 4] {
-        &self.rotations()[rot_idx % self.rotation_count()]
+    // The point the shape turns about, in the cell coordinates of its
+    // rotations: the middle of the box they fill between them. For the
+    // standard set that's O about the corner its cells share, I about the
+    // middle of its 4 by 4 box, and the rest about their middle cell.
+    pub fn pivot(&self, typ: PieceType) -> (f32, f32) {
+        let blocks = self.rotations(typ).iter().flatten();
+        let middle = |coords: Vec<isize>| {
+            let (min, max) = (coords.iter().min(), coords.iter().max());
+            (min.unwrap() + max.unwrap()) as f32 / 2.0
+        };
+        (
+            middle(blocks.clone().map(|&(x, _)| x).collect()),
+            middle(blocks.map(|&(_, y)| y).collect()),
+        )
     }
 
-    pub const fn rotation_count(&self) -> usize {
-        self.rotations().len()
+    // Color as rgb; None draws in the board's color
+    pub fn color(&self, typ: PieceType) -> Option<[f32; 3]> {
+        self.colors[typ.idx()]
     }
 }
 
@@ -120,7 +264,7 @@ impl PieceType {
 // bottom-left origin
 
 const I_ROTATIONS: [[Block; 4]; 4] = [
-    [(0, 0), (1, 0), (2, 0), (3, 0)], // 0° - center is between blocks at (1.5, 0.5)
+    [(0, 0), (1, 0), (2, 0), (3, 0)], // 0° - center is between blocks at (1.5, 1.5)
     [(2, 0), (2, 1), (2, 2), (2, 3)], // 90° - center is between blocks
     [(0, 1), (1, 1), (2, 1), (3, 1)], // 180° - center is between blocks
     [(1, 0), (1, 1), (1, 2), (1, 3)], // 270° - center is between blocks
@@ -185,10 +329,11 @@ mod tests {
             PieceType::O,
         ];
 
-        for piece_type in pieces.iter() {
-            for rot_idx in 0..piece_type.rotation_count() {
-                let rotation = piece_type.get_rotation(rot_idx);
-                let skirt = piece_type.skirt(rot_idx);
+        let set = PieceSet::default();
+        for &piece_type in pieces.iter() {
+            for rot_idx in 0..set.rotation_count(piece_type) {
+                let rotation = set.get_rotation(piece_type, rot_idx);
+                let skirt = set.skirt(piece_type, rot_idx);
 
                 // Find the min/max x manually to verify
                 let min_x = rotation.iter().map(|&(x, _)| x).min().unwrap();
@@ -243,4 +388,72 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_pivots() {
+        // A quarter turn about the pivot gives the next rotation's shape, and
+        // keeps to the box the rotations are drawn in
+        let turn = |cells: &[Block; 4], (px, py): (f32, f32)| -> Vec<Block> {
+            cells
+                .iter()
+                .map(|&(x, y)| {
+                    let (dx, dy) = (x as f32 - px, y as f32 - py);
+                    ((px - dy).round() as isize, (py + dx).round() as isize)
+                })
+                .collect()
+        };
+        let shape = |cells: &[Block]| -> Vec<Block> {
+            let min_x = cells.iter().map(|&(x, _)| x).min().unwrap_or(0);
+            let min_y = cells.iter().map(|&(_, y)| y).min().unwrap_or(0);
+            let mut shape: Vec<Block> =
+                cells.iter().map(|&(x, y)| (x - min_x, y - min_y)).collect();
+            shape.sort();
+            shape
+        };
+        let set = PieceSet::default();
+        for typ in PieceType::ALL {
+            let side = (0..set.rotation_count(typ))
+                .flat_map(|rot_idx| set.get_rotation(typ, rot_idx).iter())
+                .map(|&(x, y)| x.max(y))
+                .max()
+                .unwrap_or(0);
+            for rot_idx in 0..set.rotation_count(typ) {
+                let turned = turn(set.get_rotation(typ, rot_idx), set.pivot(typ));
+                assert!(
+                    turned
+                        .iter()
+                        .all(|&(x, y)| (0..=side).contains(&x) && (0..=side).contains(&y)),
+                    "{:?} rotation {} leaves its box",
+                    typ,
+                    rot_idx
+                );
+                assert_eq!(
+                    shape(&turned),
+                    shape(set.get_rotation(typ, rot_idx + 1)),
+                    "{:?} rotation {}",
+                    typ,
+                    rot_idx
+                );
+            }
+        }
+
+        // JLSZT keep their middle cell; the O and I turn about grid points
+        assert_eq!(set.pivot(PieceType::T), (1.0, 1.0));
+        assert_eq!(set.pivot(PieceType::O), (0.5, 0.5));
+        assert_eq!(set.pivot(PieceType::I), (1.5, 1.5));
+
+        // A custom piece turns about the middle of its own box
+        let custom = PieceSet::custom(&[PieceDef {
+            typ: PieceType::T,
+            rotations: vec![
+                vec![[1, 2], [2, 2], [3, 2], [2, 1]],
+                vec![[2, 1], [2, 2], [2, 3], [3, 2]],
+                vec![[1, 2], [2, 2], [3, 2], [2, 3]],
+                vec![[1, 2], [2, 1], [2, 2], [2, 3]],
+            ],
+            color: None,
+        }])
+        .unwrap();
+        assert_eq!(custom.pivot(PieceType::T), (2.0, 2.0));
+    }
 }
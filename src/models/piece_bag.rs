@@ -0,0 +1,42 @@
+// src/models/piece_bag.rs
+//
+// The 7-bag randomizer: each bag deals every piece type once, in a
+// random order, and is refilled when it runs out.
+
+use crate::models::PieceType;
+use nannou::rand::Rng;
+
+const BAG_SIZE: usize = 7;
+
+#[derive(Debug, Clone, Default)]
+pub struct PieceBag {
+    remaining: Vec<PieceType>, // types not yet dealt from this bag, in no order
+}
+
+impl PieceBag {
+    pub fn deal(&mut self, rng: &mut impl Rng) -> PieceType {
+        if self.remaining.is_empty() {
+            self.remaining
+                .extend((0..BAG_SIZE).map(PieceType::from_idx));
+        }
+        let idx = rng.gen_range(0..self.remaining.len());
+        self.remaining.swap_remove(idx)
+    }
+
+    pub fn remaining(&self) -> &[PieceType] {
+        &self.remaining
+    }
+
+    // A bag part way through, as `remaining` left it. The order matters:
+    // it's what the next deal picks from.
+    pub fn with_remaining(remaining: &[PieceType]) -> Self {
+        Self {
+            remaining: remaining.to_vec(),
+        }
+    }
+
+    // Throw the bag away; the next deal starts a new one
+    pub fn reset(&mut self) {
+        self.remaining.clear();
+    }
+}
@@ -0,0 +1,80 @@
+// src/models/piece_shape.rs
+//
+// A piece as the rules see it: its type, rotation and place on the board.
+// No color or anything else for drawing, so the board and the AI can try
+// placements by the thousand without touching the renderer's types.
+
+use crate::{
+    models::{PieceSet, PieceType},
+    views::{BoardPosition, RotationDirection},
+};
+
+type Cells = [(isize, isize); 4];
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PieceShape {
+    pub typ: PieceType,
+    pub rot_idx: usize, // rotation index
+    pub position: BoardPosition,
+}
+
+impl PieceShape {
+    pub fn new(typ: PieceType, position: BoardPosition) -> Self {
+        Self {
+            typ,
+            rot_idx: 0,
+            position,
+        }
+    }
+
+    // Cells in the shapes of `pieces`, the set of the board the piece is on
+    pub fn cells<'a>(&self, pieces: &'a PieceSet) -> &'a Cells {
+        pieces.get_rotation(self.typ, self.rot_idx)
+    }
+
+    // The board cells the piece covers at its current position
+    pub fn board_cells(&self, pieces: &PieceSet) -> [BoardPosition; 4] {
+        self.cells(pieces).map(|(dx, dy)| BoardPosition {
+            x: self.position.x + dx,
+            y: self.position.y + dy,
+        })
+    }
+
+    // Look up the rotation from the piece type table
+    pub fn rotate<'a>(&mut self, direction: &RotationDirection, pieces: &'a PieceSet) -> &'a Cells {
+        let count = pieces.rotation_count(self.typ);
+
+        let inx = match direction {
+            RotationDirection::Cw => (self.rot_idx + 1) % count,
+            RotationDirection::Ccw => (self.rot_idx + count - 1) % count,
+        };
+
+        self.rot_idx = inx;
+        pieces.get_rotation(self.typ, inx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Board, PlaceResult};
+
+    #[test]
+    fn test_color_free_placement() {
+        let mut board = Board::new(6, 10).unwrap();
+        let mut row = vec![true; 6];
+        row[4] = false;
+        row[5] = false;
+        board.add_garbage(&[row]);
+
+        // An O dropped into the gap finishes the row, no color needed
+        let piece = PieceShape::new(PieceType::O, BoardPosition { x: 4, y: 8 });
+        let (landing, result) = board.calculate_drop(&piece);
+        assert_eq!(landing, BoardPosition { x: 4, y: 0 });
+        assert!(result == PlaceResult::RowFilled);
+
+        let mut placed = piece;
+        placed.position = landing;
+        assert_eq!(board.commit_piece(&placed), Some(vec![0]));
+    }
+}
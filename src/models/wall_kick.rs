@@ -2,8 +2,14 @@
 //
 // Table describing possible wall kick positions for piece rotations
 
+use crate::models::PieceType;
+use std::{fmt, rc::Rc};
+
 pub type WallKickOffset = (isize, isize); // (dx, dy)
 
+// rotation transitions in a kick table, in get_wall_kick_index order
+const TRANSITIONS: usize = 8;
+
 // For J, L, S, Z, T pieces (standard SRS kicks)
 pub const JLSZT_WALL_KICKS: [&[WallKickOffset]; 8] = [
     // 0>>1 (0° to 90° clockwise)
@@ -47,6 +53,83 @@ pub const I_WALL_KICKS: [&[WallKickOffset]; 8] = [
 // O piece never needs wall kicks
 pub const O_WALL_KICKS: [&[WallKickOffset]; 1] = [&[(0, 0)]];
 
+// ARS style: one step right, then one left, for every piece but the I.
+// Never up, so there are no floor kicks.
+pub const ARS_WALL_KICKS: &[WallKickOffset] = &[(0, 0), (1, 0), (-1, 0)];
+
+// The kick table a board rotates pieces with
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum WallKicks {
+    #[default]
+    Srs,
+    Ars,                     // with the center column rule, see Board::try_rotation
+    Custom(Rc<CustomKicks>), // shared, so boards clone cheaply in AI searches
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CustomKicks {
+    jlszt: Vec<Vec<WallKickOffset>>, // per transition, for J, L, S, Z, T
+    i: Vec<Vec<WallKickOffset>>,     // per transition, for I
+}
+
+// A custom table without a kick list for each of the 8 transitions
+#[derive(Debug, PartialEq)]
+pub struct KickTableError {
+    pub pieces: &'static str,
+    pub transitions: usize,
+}
+
+impl fmt::Display for KickTableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} kicks list {} rotation transitions instead of {}",
+            self.pieces, self.transitions, TRANSITIONS
+        )
+    }
+}
+
+impl std::error::Error for KickTableError {}
+
+impl WallKicks {
+    // A table of [dx, dy] kicks for each transition, in get_wall_kick_index order
+    pub fn custom(
+        jlszt: &[Vec<[isize; 2]>],
+        i: &[Vec<[isize; 2]>],
+    ) -> Result<Self, KickTableError> {
+        let table = |pieces, lists: &[Vec<[isize; 2]>]| {
+            if lists.len() != TRANSITIONS {
+                return Err(KickTableError {
+                    pieces,
+                    transitions: lists.len(),
+                });
+            }
+            Ok(lists
+                .iter()
+                .map(|list| list.iter().map(|&[dx, dy]| (dx, dy)).collect())
+                .collect())
+        };
+
+        Ok(WallKicks::Custom(Rc::new(CustomKicks {
+            jlszt: table("JLSZT", jlszt)?,
+            i: table("I", i)?,
+        })))
+    }
+
+    // Kicks to try, in order, for a rotation from `from_rot` to `to_rot`
+    pub fn offsets(&self, typ: PieceType, from_rot: usize, to_rot: usize) -> &[WallKickOffset] {
+        let kick_idx = get_wall_kick_index(from_rot, to_rot);
+        match (self, typ) {
+            (_, PieceType::O) => O_WALL_KICKS[0],
+            (WallKicks::Srs, _) => typ.wall_kick_offsets(from_rot, to_rot),
+            (WallKicks::Ars, PieceType::I) => O_WALL_KICKS[0],
+            (WallKicks::Ars, _) => ARS_WALL_KICKS,
+            (WallKicks::Custom(kicks), PieceType::I) => &kicks.i[kick_idx],
+            (WallKicks::Custom(kicks), _) => &kicks.jlszt[kick_idx],
+        }
+    }
+}
+
 // Helper to get the correct index for wall kick data
 pub fn get_wall_kick_index(from_rot: usize, to_rot: usize) -> usize {
     match (from_rot % 4, to_rot % 4) {
@@ -0,0 +1,9 @@
+// src/net/mod.rs
+
+pub mod osc_sender;
+#[cfg(feature = "ws")]
+pub mod ws_server;
+
+pub use osc_sender::OscSender;
+#[cfg(feature = "ws")]
+pub use ws_server::WsServer;
@@ -0,0 +1,147 @@
+// src/net/osc_sender.rs
+//
+// OSC feed of every board's column heights, for sound and light rigs.
+// A send carries only the columns that changed since the last one, and
+// every so often all of them, so a receiver that dropped a packet or
+// started late catches up.
+//
+// Each board's heights go to /gameover/<board id>/cols as int pairs:
+// column, height, column, height, ...
+
+use crate::{utils::Timer, views::GameManager};
+use nannou_osc as osc;
+use std::{collections::HashMap, io};
+
+pub struct OscSender {
+    sender: osc::Sender<osc::Connected>,
+    throttle: Timer,      // limits how often heights are sent
+    resync: Timer,        // until every column is sent again
+    columns: ColumnDelta, // heights as last sent
+}
+
+impl OscSender {
+    // Send to `addr` ("host:port") at most once every `interval` seconds
+    pub fn connect(addr: &str, interval: f32, resync_interval: f32) -> io::Result<Self> {
+        let sender = osc::sender()?.connect(addr)?;
+        println!("\n<------ OSC sending to {} ----->", addr);
+
+        Ok(Self {
+            sender,
+            throttle: Timer::new(interval),
+            resync: Timer::new(resync_interval),
+            columns: ColumnDelta::default(),
+        })
+    }
+
+    // Called every frame. Sends each board's changed columns when the throttle allows.
+    pub fn update(&mut self, dt: f32, game: &GameManager) {
+        if self.resync.tick(dt) {
+            self.columns.clear();
+        }
+        // A removed board's heights go, so one added again under its id starts over
+        self.columns.retain(|id| game.boards().contains_key(id));
+        if !self.throttle.tick(dt) {
+            return;
+        }
+
+        for (id, board) in game.boards() {
+            let changes = self.columns.changes(id, board.board().col_score_all());
+            if changes.is_empty() {
+                continue;
+            }
+            // Unsent columns stay changed, and go out with the next send
+            match self.sender.send(cols_message(id, &changes)) {
+                Ok(_) => self.columns.mark_sent(id, board.board().col_score_all()),
+                Err(e) => println!("OSC: failed to send board {}: {}", id, e),
+            }
+        }
+    }
+}
+
+// Column heights last sent for each board, to send only what's changed
+#[derive(Debug, Clone, Default)]
+pub struct ColumnDelta {
+    sent: HashMap<String, Vec<isize>>,
+}
+
+impl ColumnDelta {
+    // Columns of board `id` whose heights changed since they were last sent,
+    // with their new heights; every column until a board's first send
+    pub fn changes(&self, id: &str, heights: &[isize]) -> Vec<(usize, isize)> {
+        let sent = self.sent.get(id).map(Vec::as_slice).unwrap_or_default();
+        heights
+            .iter()
+            .enumerate()
+            .filter(|&(col, height)| sent.get(col) != Some(height))
+            .map(|(col, &height)| (col, height))
+            .collect()
+    }
+
+    // Board `id`'s heights have gone out
+    pub fn mark_sent(&mut self, id: &str, heights: &[isize]) {
+        let sent = self.sent.entry(id.to_owned()).or_default();
+        sent.clear();
+        sent.extend_from_slice(heights);
+    }
+
+    // Forget what was sent, so every column goes out again
+    pub fn clear(&mut self) {
+        self.sent.clear();
+    }
+
+    // Keep only the boards `keep` says are still there
+    pub fn retain(&mut self, mut keep: impl FnMut(&str) -> bool) {
+        self.sent.retain(|id, _| keep(id));
+    }
+}
+
+// The message for one board's changed columns
+pub fn cols_message(id: &str, changes: &[(usize, isize)]) -> (String, Vec<osc::Type>) {
+    let args = changes
+        .iter()
+        .flat_map(|&(col, height)| [osc::Type::Int(col as i32), osc::Type::Int(height as i32)])
+        .collect();
+    (format!("/gameover/{}/cols", id), args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_changed_columns() {
+        let mut columns = ColumnDelta::default();
+        let mut heights = vec![0, 2, 2, 3, 0, 0];
+
+        // Everything the first time, then nothing until something changes
+        assert_eq!(columns.changes("board1", &heights).len(), 6);
+        columns.mark_sent("board1", &heights);
+        assert!(columns.changes("board1", &heights).is_empty());
+
+        // A piece locked across columns 3 and 4
+        heights[3] = 5;
+        heights[4] = 4;
+        let changes = columns.changes("board1", &heights);
+        assert_eq!(changes, vec![(3, 5), (4, 4)]);
+        let (addr, args) = cols_message("board1", &changes);
+        assert_eq!(addr, "/gameover/board1/cols");
+        assert_eq!(args, [3, 5, 4, 4].map(osc::Type::Int).to_vec());
+
+        // A send that failed leaves them to go out next time
+        assert_eq!(columns.changes("board1", &heights), changes);
+        columns.mark_sent("board1", &heights);
+        assert!(columns.changes("board1", &heights).is_empty());
+
+        // Other boards are tracked apart, and a resync sends it all again
+        assert_eq!(columns.changes("board2", &heights).len(), 6);
+        columns.clear();
+        assert_eq!(columns.changes("board1", &heights).len(), 6);
+
+        // A board that's gone is forgotten, and sends everything if it's back
+        columns.mark_sent("board1", &heights);
+        columns.mark_sent("board2", &heights);
+        columns.retain(|id| id != "board2");
+        assert!(columns.changes("board1", &heights).is_empty());
+        assert_eq!(columns.changes("board2", &heights).len(), 6);
+    }
+}
@@ -0,0 +1,166 @@
+// src/net/ws_server.rs
+//
+// WebSocket server broadcasting JSON game snapshots to browser dashboards.
+// Clients connect at ws://<host>:<port>. They only ever receive messages.
+
+use crate::{utils::Timer, views::GameSnapshot};
+use std::{
+    io,
+    net::{TcpListener, TcpStream},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+use tungstenite::{Message, WebSocket};
+
+// A slow client must not stall the broadcast for everyone else
+const WRITE_TIMEOUT: Duration = Duration::from_millis(200);
+// nor one that connects and never finishes the handshake
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Connected clients, each with an id that's never given out again
+#[derive(Default)]
+struct ClientList {
+    next_id: u64,
+    sockets: Vec<(u64, WebSocket<TcpStream>)>,
+}
+
+type Clients = Arc<Mutex<ClientList>>;
+
+pub struct WsServer {
+    sender: Sender<String>, // hands serialized snapshots to the broadcast thread
+    throttle: Timer,        // limits how often snapshots are sent
+    last_sent: Option<String>, // skip sending when nothing has changed
+    clients: Clients,
+    seen_id: u64, // clients from this id up connected since the last update
+}
+
+impl WsServer {
+    // Start listening on `port` and broadcast at most once every `interval` seconds
+    pub fn start(port: u16, interval: f32) -> io::Result<Self> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        let clients: Clients = Arc::new(Mutex::new(ClientList::default()));
+        let (sender, receiver) = mpsc::channel();
+
+        let accept_clients = Arc::clone(&clients);
+        thread::spawn(move || accept_loop(listener, accept_clients));
+
+        let broadcast_clients = Arc::clone(&clients);
+        thread::spawn(move || broadcast_loop(receiver, broadcast_clients));
+
+        println!(
+            "\n<------ WebSocket server listening on port {} ----->",
+            port
+        );
+
+        Ok(Self {
+            sender,
+            throttle: Timer::new(interval),
+            last_sent: None,
+            clients,
+            seen_id: 0,
+        })
+    }
+
+    // Called every frame. Sends the snapshot when the throttle allows and it has changed.
+    pub fn update(&mut self, dt: f32, snapshot: impl FnOnce() -> GameSnapshot) {
+        if !self.throttle.tick(dt) {
+            return;
+        }
+
+        // Newly connected clients need the current snapshot even if nothing changed
+        let Ok((client_count, new_clients, next_id)) = self.clients.lock().map(|clients| {
+            let new_clients = clients.sockets.iter().any(|&(id, _)| id >= self.seen_id);
+            (clients.sockets.len(), new_clients, clients.next_id)
+        }) else {
+            return;
+        };
+        self.seen_id = next_id;
+        if client_count == 0 {
+            return;
+        }
+
+        let json = match serde_json::to_string(&snapshot()) {
+            Ok(json) => json,
+            Err(e) => {
+                println!("WebSocket: failed to serialize snapshot: {}", e);
+                return;
+            }
+        };
+
+        if !new_clients && self.last_sent.as_ref() == Some(&json) {
+            return;
+        }
+
+        if self.sender.send(json.clone()).is_ok() {
+            self.last_sent = Some(json);
+        }
+    }
+
+    pub fn client_count(&self) -> usize {
+        self.clients
+            .lock()
+            .map(|clients| clients.sockets.len())
+            .unwrap_or(0)
+    }
+}
+
+fn accept_loop(listener: TcpListener, clients: Clients) {
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let peer = stream
+            .peer_addr()
+            .map(|addr| addr.to_string())
+            .unwrap_or_default();
+
+        if stream.set_write_timeout(Some(WRITE_TIMEOUT)).is_err()
+            || stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT)).is_err()
+        {
+            continue;
+        }
+
+        // Each handshake on its own thread, so a stalled one doesn't hold up the rest
+        let clients = Arc::clone(&clients);
+        thread::spawn(move || match tungstenite::accept(stream) {
+            Ok(socket) => {
+                println!("WebSocket: client connected {}", peer);
+                if let Ok(mut clients) = clients.lock() {
+                    let id = clients.next_id;
+                    clients.next_id += 1;
+                    clients.sockets.push((id, socket));
+                }
+            }
+            Err(e) => println!("WebSocket: handshake with {} failed: {}", peer, e),
+        });
+    }
+}
+
+fn broadcast_loop(receiver: Receiver<String>, clients: Clients) {
+    // Ends when the WsServer is dropped
+    for json in receiver {
+        let Ok(mut clients) = clients.lock() else {
+            return;
+        };
+
+        // Any client we can't write to has gone away
+        clients.sockets.retain_mut(
+            |(_, socket)| match socket.send(Message::text(json.clone())) {
+                Ok(()) => true,
+                Err(_) => {
+                    let peer = socket
+                        .get_ref()
+                        .peer_addr()
+                        .map(|addr| addr.to_string())
+                        .unwrap_or_default();
+                    println!("WebSocket: client disconnected {}", peer);
+                    false
+                }
+            },
+        );
+    }
+}
@@ -0,0 +1,279 @@
+// src/recording/frame_recorder.rs
+//
+// Records the rendered scene to a numbered PNG sequence, or an animated GIF.
+// Frames are captured from a fixed-size RenderTarget at the configured fps,
+// cropped to the configured region, and written to disk off the render thread.
+// With session folders on, each recording gets its own folder and file prefix.
+
+#[cfg(feature = "gif")]
+use crate::recording::GifWriter;
+use crate::{
+    config::{FrameRecorderConfig, RecordFormat, RecordRegion},
+    recording::{crop_rect, RecordingSession, RenderTarget},
+    utils::{Clock, SystemClock, Timer},
+    views::BoardInstance,
+};
+use nannou::{
+    image::{imageops, RgbaImage},
+    prelude::*,
+};
+#[cfg(feature = "gif")]
+use std::sync::mpsc::Sender;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+pub struct FrameRecorder {
+    target: RenderTarget,
+    capturer: wgpu::TextureCapturer,
+    output_dir: PathBuf,
+    session_folders: bool,
+    session: Option<RecordingSession>, // folder of the current recording
+    clock: Box<dyn Clock>,             // names session folders
+
+    region: RecordRegion,
+    scene_size: [u32; 2], // size of the texture the region is measured in
+    region_missing: bool, // so a missing board is only reported once
+
+    frame_timer: Timer, // time between captured frames
+    frame_limit: u32,   // recording stops by itself after this many frames
+    frame_count: u32,
+    recording: bool,
+
+    #[cfg(feature = "gif")]
+    format: RecordFormat,
+    #[cfg(feature = "gif")]
+    fps: u32,
+    #[cfg(feature = "gif")]
+    gif: Option<GifWriter>, // opened on the first frame, once its size is known
+}
+
+// Where a single captured frame goes
+enum FrameOutput {
+    Png(PathBuf),
+    #[cfg(feature = "gif")]
+    Gif(Sender<RgbaImage>),
+}
+
+impl FrameRecorder {
+    pub fn new(
+        device: &wgpu::Device,
+        scene: &wgpu::Texture,
+        config: &FrameRecorderConfig,
+        output_dir: &Path,
+    ) -> Self {
+        #[cfg(not(feature = "gif"))]
+        if config.format == RecordFormat::Gif {
+            println!("Frame recorder: built without the \"gif\" feature, recording PNGs instead");
+        }
+
+        Self {
+            target: RenderTarget::new(device, scene, [config.width, config.height]),
+            capturer: wgpu::TextureCapturer::default(),
+            output_dir: output_dir.to_path_buf(),
+            session_folders: config.session_folders,
+            session: None,
+            clock: Box::new(SystemClock),
+
+            region: config.region.clone(),
+            scene_size: scene.size(),
+            region_missing: false,
+
+            frame_timer: Timer::new(1.0 / config.fps as f32),
+            frame_limit: config.frame_limit,
+            frame_count: 0,
+            recording: false,
+
+            #[cfg(feature = "gif")]
+            format: config.format,
+            #[cfg(feature = "gif")]
+            fps: config.fps,
+            #[cfg(feature = "gif")]
+            gif: None,
+        }
+    }
+
+    pub fn set_clock(&mut self, clock: impl Clock + 'static) {
+        self.clock = Box::new(clock);
+    }
+
+    pub fn start(&mut self) {
+        let created = if self.session_folders {
+            RecordingSession::start(&self.output_dir, self.clock.as_ref()).map(Some)
+        } else {
+            std::fs::create_dir_all(&self.output_dir).map(|_| None)
+        };
+        match created {
+            Ok(session) => self.session = session,
+            Err(e) => {
+                println!(
+                    "Frame recorder: can't create a folder in {}: {}",
+                    self.output_dir.display(),
+                    e
+                );
+                return;
+            }
+        }
+
+        let dir = self.session.as_ref().map_or(&self.output_dir, |s| &s.dir);
+        println!("\n<------ Recording frames to {} ----->", dir.display());
+        self.frame_count = 0;
+        self.frame_timer.reset();
+        self.recording = true;
+    }
+
+    // Waits for frames still being written
+    pub fn stop(&mut self, device: &wgpu::Device) {
+        if !self.recording {
+            return;
+        }
+
+        self.recording = false;
+        if self.capturer.await_active_snapshots(device).is_err() {
+            println!("Frame recorder: some frames failed to save");
+        }
+
+        #[cfg(feature = "gif")]
+        if let Some(gif) = self.gif.take() {
+            match gif.finish() {
+                Ok(()) => println!("\n<------ Saved {} ----->", self.gif_path().display()),
+                Err(e) => println!("Frame recorder: failed to write GIF: {}", e),
+            }
+        }
+
+        println!("\n<------ Recorded {} frames ----->", self.frame_count);
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording
+    }
+
+    // Call once per frame after the scene has been rendered
+    pub fn capture(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        dt: f32,
+        boards: &HashMap<String, BoardInstance>,
+    ) {
+        if !self.recording || !self.frame_timer.tick(dt) {
+            return;
+        }
+
+        // The region is measured in the scene; frames come from the scaled target
+        let Some(crop) = crop_rect(&self.region, boards, self.scene_size) else {
+            if !self.region_missing {
+                println!(
+                    "Frame recorder: {:?} not found, skipping frames",
+                    self.region
+                );
+                self.region_missing = true;
+            }
+            return;
+        };
+        self.region_missing = false;
+        let target_size = self.target.size();
+        let crop = crop.scaled(self.scene_size, target_size);
+        let full_frame = crop.x == 0 && crop.y == 0 && [crop.width, crop.height] == target_size;
+
+        #[cfg(feature = "gif")]
+        if self.format == RecordFormat::Gif && !self.open_gif([crop.width, crop.height]) {
+            self.stop(device);
+            return;
+        }
+
+        let ce_desc = wgpu::CommandEncoderDescriptor {
+            label: Some("Frame recorder"),
+        };
+        let mut encoder = device.create_command_encoder(&ce_desc);
+        self.target.encode(&mut encoder);
+        let snapshot = self
+            .capturer
+            .capture(device, &mut encoder, self.target.texture());
+        queue.submit(Some(encoder.finish()));
+
+        let output = self.frame_output();
+        let read = snapshot.read(move |result| match result {
+            Ok(image) => {
+                let mut image = image.to_owned();
+                if !full_frame {
+                    image = imageops::crop_imm(&image, crop.x, crop.y, crop.width, crop.height)
+                        .to_image();
+                }
+                output.write(image);
+            }
+            Err(e) => println!("Frame recorder: failed to read frame: {:?}", e),
+        });
+        if read.is_err() {
+            println!("Frame recorder: failed to read frame {}", self.frame_count);
+        }
+
+        self.frame_count += 1;
+        if self.frame_count >= self.frame_limit {
+            self.stop(device);
+        }
+    }
+
+    fn frame_output(&self) -> FrameOutput {
+        #[cfg(feature = "gif")]
+        if let Some(gif) = &self.gif {
+            return FrameOutput::Gif(gif.sender());
+        }
+
+        FrameOutput::Png(self.frame_path(self.frame_count))
+    }
+
+    fn frame_path(&self, frame: u32) -> PathBuf {
+        self.file_path(&format!("frame_{:06}.png", frame))
+    }
+
+    fn file_path(&self, name: &str) -> PathBuf {
+        match &self.session {
+            Some(session) => session.file_path(name),
+            None => self.output_dir.join(name),
+        }
+    }
+
+    // Returns false if the GIF can't be written
+    #[cfg(feature = "gif")]
+    fn open_gif(&mut self, size: [u32; 2]) -> bool {
+        if self.gif.is_some() {
+            return true;
+        }
+
+        let path = self.gif_path();
+        match GifWriter::create(&path, size, self.fps) {
+            Ok(gif) => {
+                self.gif = Some(gif);
+                true
+            }
+            Err(e) => {
+                println!("Frame recorder: can't create {}: {}", path.display(), e);
+                false
+            }
+        }
+    }
+
+    #[cfg(feature = "gif")]
+    fn gif_path(&self) -> PathBuf {
+        self.file_path("recording.gif")
+    }
+}
+
+impl FrameOutput {
+    fn write(self, image: RgbaImage) {
+        match self {
+            FrameOutput::Png(path) => {
+                if let Err(e) = image.save(&path) {
+                    println!("Frame recorder: failed to save {}: {}", path.display(), e);
+                }
+            }
+            // The writer reports its own errors when it finishes
+            #[cfg(feature = "gif")]
+            FrameOutput::Gif(sender) => {
+                let _ = sender.send(image);
+            }
+        }
+    }
+}
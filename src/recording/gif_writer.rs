@@ -0,0 +1,103 @@
+// src/recording/gif_writer.rs
+//
+// Encodes recorded frames into an animated GIF on a background thread.
+// Palettes are quantized per frame with NeuQuant, so each frame gets its own
+// 256 colors. That suits the mostly-amber scene well.
+
+use nannou::image::RgbaImage;
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    sync::mpsc::{self, Sender},
+    thread::{self, JoinHandle},
+};
+
+// 1 is best quality and slowest, 30 is fastest. 10 is the gif crate's suggestion.
+const QUANTIZE_SPEED: i32 = 10;
+
+pub struct GifWriter {
+    sender: Sender<RgbaImage>,
+    handle: JoinHandle<Result<(), gif::EncodingError>>,
+}
+
+impl GifWriter {
+    // Every frame must be `size` pixels
+    pub fn create(path: &Path, size: [u32; 2], fps: u32) -> io::Result<Self> {
+        let file = BufWriter::new(File::create(path)?);
+        let (sender, receiver) = mpsc::channel();
+        let handle = thread::spawn(move || encode_frames(file, size, fps, receiver));
+
+        Ok(Self { sender, handle })
+    }
+
+    // Hands frames to the encoder thread
+    pub fn sender(&self) -> Sender<RgbaImage> {
+        self.sender.clone()
+    }
+
+    // Waits for all queued frames to be encoded. Senders handed out must be dropped first.
+    pub fn finish(self) -> Result<(), gif::EncodingError> {
+        drop(self.sender);
+        self.handle
+            .join()
+            .unwrap_or_else(|_| Err(io::Error::other("GIF encoder thread panicked").into()))
+    }
+}
+
+pub fn encode_frames<W: Write>(
+    writer: W,
+    size: [u32; 2],
+    fps: u32,
+    frames: impl IntoIterator<Item = RgbaImage>,
+) -> Result<(), gif::EncodingError> {
+    let [width, height] = size.map(|side| side.min(u16::MAX as u32) as u16);
+    let mut encoder = gif::Encoder::new(writer, width, height, &[])?;
+    encoder.set_repeat(gif::Repeat::Infinite)?;
+
+    // GIF delays are in hundredths of a second
+    let delay = (100.0 / fps.max(1) as f32).round().max(1.0) as u16;
+
+    for image in frames {
+        if [image.width(), image.height()] != [width as u32, height as u32] {
+            println!(
+                "GIF writer: skipping {}x{} frame in a {}x{} GIF",
+                image.width(),
+                image.height(),
+                width,
+                height
+            );
+            continue;
+        }
+
+        let mut pixels = image.into_raw();
+        let mut frame = gif::Frame::from_rgba_speed(width, height, &mut pixels, QUANTIZE_SPEED);
+        frame.delay = delay;
+        encoder.write_frame(&frame)?;
+    }
+
+    // Writes the trailer
+    encoder.into_inner()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nannou::image::Rgba;
+
+    #[test]
+    fn test_encoded_frames_have_gif_header() {
+        let frames = (0..3).map(|i| {
+            let mut image = RgbaImage::new(8, 4);
+            image.put_pixel(i, 0, Rgba([255, 170, 0, 255]));
+            image
+        });
+
+        let mut bytes = Vec::new();
+        encode_frames(&mut bytes, [8, 4], 30, frames).unwrap();
+
+        assert_eq!(&bytes[..6], b"GIF89a");
+        assert_eq!(bytes.last(), Some(&0x3B));
+    }
+}
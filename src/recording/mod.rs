@@ -0,0 +1,15 @@
+// src/recording/mod.rs
+
+pub mod frame_recorder;
+#[cfg(feature = "gif")]
+pub mod gif_writer;
+pub mod region;
+pub mod render_target;
+pub mod session;
+
+pub use frame_recorder::FrameRecorder;
+#[cfg(feature = "gif")]
+pub use gif_writer::GifWriter;
+pub use region::crop_rect;
+pub use render_target::RenderTarget;
+pub use session::RecordingSession;
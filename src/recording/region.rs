@@ -0,0 +1,77 @@
+// src/recording/region.rs
+//
+// Resolves the configured RecordRegion to a crop rect in scene texture pixels.
+
+use crate::{config::RecordRegion, utils::PixelRect, views::BoardInstance};
+use std::collections::HashMap;
+
+// None when there's nothing to record yet, e.g. the board hasn't been made
+pub fn crop_rect(
+    region: &RecordRegion,
+    boards: &HashMap<String, BoardInstance>,
+    scene_size: [u32; 2],
+) -> Option<PixelRect> {
+    let rect = match region {
+        RecordRegion::Window => PixelRect::full(scene_size),
+        RecordRegion::Board(id) => boards.get(id)?.pixel_bounds(scene_size),
+        &RecordRegion::Rect {
+            x,
+            y,
+            width,
+            height,
+        } => {
+            // Keep explicit rects inside the scene
+            let x = x.min(scene_size[0]);
+            let y = y.min(scene_size[1]);
+            PixelRect {
+                x,
+                y,
+                width: width.min(scene_size[0] - x),
+                height: height.min(scene_size[1] - y),
+            }
+        }
+    };
+
+    (!rect.is_empty()).then_some(rect)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::BoardConfig;
+    use nannou::prelude::*;
+
+    #[test]
+    fn test_board_crop_matches_pixel_bounds() {
+        let config = BoardConfig {
+            width: 16,
+            height: 24,
+            ..BoardConfig::default()
+        };
+        let scene_size = [3800, 1080];
+
+        let mut boards = HashMap::new();
+        for (id, x) in [("board1", -340.0), ("board2", 340.0)] {
+            let board = BoardInstance::new(id, vec2(x, 0.0), &config, 1).unwrap();
+            boards.insert(id.to_string(), board);
+        }
+
+        let region = RecordRegion::Board("board2".to_string());
+        let rect = crop_rect(&region, &boards, scene_size);
+        assert_eq!(rect, Some(boards["board2"].pixel_bounds(scene_size)));
+
+        // 480x720 board centered 340 right of the texture center
+        assert_eq!(
+            rect,
+            Some(PixelRect {
+                x: 1900 + 340 - 240,
+                y: 540 - 360,
+                width: 480,
+                height: 720,
+            })
+        );
+
+        let missing = RecordRegion::Board("board3".to_string());
+        assert_eq!(crop_rect(&missing, &boards, scene_size), None);
+    }
+}
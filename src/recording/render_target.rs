@@ -0,0 +1,74 @@
+// src/recording/render_target.rs
+//
+// Fixed-size offscreen texture for recording.
+// The finished scene texture is scaled into it, so recordings keep the
+// configured resolution no matter how big the window is.
+
+use nannou::prelude::*;
+
+// PNG-ready, so the capturer doesn't need to convert
+const TARGET_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+pub struct RenderTarget {
+    texture: wgpu::Texture,
+    reshaper: wgpu::TextureReshaper, // scales the scene texture into ours
+}
+
+impl RenderTarget {
+    pub fn new(device: &wgpu::Device, scene: &wgpu::Texture, size: [u32; 2]) -> Self {
+        let texture = texture_builder(size).build(device);
+
+        let scene_view = scene.view().build();
+        let reshaper = wgpu::TextureReshaper::new(
+            device,
+            &scene_view,
+            scene.sample_count(),
+            scene.sample_type(),
+            1,
+            TARGET_FORMAT,
+        );
+
+        Self { texture, reshaper }
+    }
+
+    // Copy the current scene into the target
+    pub fn encode(&self, encoder: &mut wgpu::CommandEncoder) {
+        let view = self.texture.view().build();
+        self.reshaper.encode_render_pass(&view, encoder);
+    }
+
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+
+    pub fn size(&self) -> [u32; 2] {
+        self.texture.size()
+    }
+}
+
+fn texture_builder(size: [u32; 2]) -> wgpu::TextureBuilder {
+    wgpu::TextureBuilder::new()
+        .size(size)
+        // Written by the reshaper, then read back by the TextureCapturer
+        .usage(
+            wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
+        )
+        .sample_count(1)
+        .format(TARGET_FORMAT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target_has_configured_size() {
+        let descriptor = texture_builder([1900, 540]).into_descriptor();
+        assert_eq!(descriptor.size.width, 1900);
+        assert_eq!(descriptor.size.height, 540);
+        assert_eq!(descriptor.size.depth_or_array_layers, 1);
+        assert_eq!(descriptor.format, TARGET_FORMAT);
+    }
+}
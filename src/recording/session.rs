@@ -0,0 +1,108 @@
+// src/recording/session.rs
+//
+// A recording session: its own timestamped folder under the output directory,
+// with the folder name doubling as the prefix of every file inside it.
+// Repeated recordings never overwrite each other.
+
+use crate::utils::Clock;
+use chrono::{DateTime, Local};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordingSession {
+    pub id: String,   // e.g. 20250407-130509, or 20250407-130509-2 on collision
+    pub dir: PathBuf, // <output directory>/<id>
+}
+
+impl RecordingSession {
+    // Create the session folder for a recording starting now by `clock`
+    pub fn start(output_dir: &Path, clock: &dyn Clock) -> io::Result<Self> {
+        Self::create(output_dir, clock.now())
+    }
+
+    // Create the session folder for a recording started at `started`
+    pub fn create(output_dir: &Path, started: DateTime<Local>) -> io::Result<Self> {
+        fs::create_dir_all(output_dir)?;
+        if !output_dir.is_dir() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} is not a directory", output_dir.display()),
+            ));
+        }
+
+        // Two sessions started within the same second get a numbered suffix
+        let base = session_id(started);
+        for n in 1.. {
+            let id = if n == 1 {
+                base.clone()
+            } else {
+                format!("{}-{}", base, n)
+            };
+            let dir = output_dir.join(&id);
+
+            match fs::create_dir(&dir) {
+                Ok(()) => return Ok(Self { id, dir }),
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        unreachable!()
+    }
+
+    // Path of a file in the session folder, prefixed with the session id
+    pub fn file_path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}_{}", self.id, name))
+    }
+}
+
+pub fn session_id(started: DateTime<Local>) -> String {
+    started.format("%Y%m%d-%H%M%S").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::MockClock;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_session_folders() {
+        let started = Local.with_ymd_and_hms(2025, 4, 7, 13, 5, 9).unwrap();
+        assert_eq!(session_id(started), "20250407-130509");
+
+        let root = std::env::temp_dir().join("gameover_session_test");
+        let _ = fs::remove_dir_all(&root);
+
+        let first = RecordingSession::create(&root, started).unwrap();
+        let second = RecordingSession::create(&root, started).unwrap();
+        assert_eq!(first.id, "20250407-130509");
+        assert_eq!(second.id, "20250407-130509-2");
+        assert!(first.dir.is_dir() && second.dir.is_dir());
+        assert_eq!(
+            second.file_path("frame_000001.png"),
+            root.join("20250407-130509-2")
+                .join("20250407-130509-2_frame_000001.png")
+        );
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_session_from_clock() {
+        let clock = MockClock {
+            now: Local.with_ymd_and_hms(2025, 12, 31, 23, 59, 58).unwrap(),
+        };
+        let root = std::env::temp_dir().join("gameover_session_clock_test");
+        let _ = fs::remove_dir_all(&root);
+
+        // Named for the clock's time, however long the test takes
+        let session = RecordingSession::start(&root, &clock).unwrap();
+        assert_eq!(session.id, "20251231-235958");
+        assert_eq!(session.dir, root.join("20251231-235958"));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
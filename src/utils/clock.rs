@@ -0,0 +1,35 @@
+// src/utils/clock.rs
+//
+// Where the time of day comes from. The game runs on the dt each update
+// is given; only things named after the time, like recording folders,
+// read a clock, and tests hand them one that doesn't move.
+
+use chrono::{DateTime, Local};
+
+pub trait Clock {
+    fn now(&self) -> DateTime<Local>;
+}
+
+// The computer's own clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Local> {
+        Local::now()
+    }
+}
+
+// Reads whatever time it was set to
+#[cfg(test)]
+#[derive(Debug, Clone, Copy)]
+pub struct MockClock {
+    pub now: DateTime<Local>,
+}
+
+#[cfg(test)]
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<Local> {
+        self.now
+    }
+}
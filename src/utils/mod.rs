@@ -1,3 +1,11 @@
+pub mod clock;
+pub mod pixel_rect;
+pub mod seeded_rng;
 pub mod timer;
 
+#[cfg(test)]
+pub use clock::MockClock;
+pub use clock::{Clock, SystemClock};
+pub use pixel_rect::PixelRect;
+pub use seeded_rng::{RngState, SeededRng};
 pub use timer::Timer;
@@ -0,0 +1,68 @@
+// src/utils/pixel_rect.rs
+//
+// Rectangle in texture pixels: origin top-left, y pointing down.
+
+use nannou::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PixelRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl PixelRect {
+    pub fn full(size: [u32; 2]) -> Self {
+        Self {
+            x: 0,
+            y: 0,
+            width: size[0],
+            height: size[1],
+        }
+    }
+
+    // From a rect in screen points (origin at center, y up), for a texture
+    // of `size` pixels drawn at one pixel per point. Clamped to the texture.
+    pub fn from_screen(center: Vec2, dims: Vec2, size: [u32; 2]) -> Self {
+        let [tex_w, tex_h] = [size[0] as f32, size[1] as f32];
+        let left = (center.x - dims.x / 2.0 + tex_w / 2.0)
+            .round()
+            .clamp(0.0, tex_w);
+        let right = (center.x + dims.x / 2.0 + tex_w / 2.0)
+            .round()
+            .clamp(0.0, tex_w);
+        let top = (tex_h / 2.0 - center.y - dims.y / 2.0)
+            .round()
+            .clamp(0.0, tex_h);
+        let bottom = (tex_h / 2.0 - center.y + dims.y / 2.0)
+            .round()
+            .clamp(0.0, tex_h);
+
+        Self {
+            x: left as u32,
+            y: top as u32,
+            width: (right - left) as u32,
+            height: (bottom - top) as u32,
+        }
+    }
+
+    // Same area in a texture of another size, e.g. a downscaled copy
+    pub fn scaled(&self, from: [u32; 2], to: [u32; 2]) -> Self {
+        let sx = to[0] as f32 / from[0] as f32;
+        let sy = to[1] as f32 / from[1] as f32;
+        let x = (self.x as f32 * sx).round() as u32;
+        let y = (self.y as f32 * sy).round() as u32;
+
+        Self {
+            x: x.min(to[0]),
+            y: y.min(to[1]),
+            width: ((self.width as f32 * sx).round() as u32).min(to[0] - x.min(to[0])),
+            height: ((self.height as f32 * sy).round() as u32).min(to[1] - y.min(to[1])),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+}
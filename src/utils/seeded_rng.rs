@@ -0,0 +1,91 @@
+// src/utils/seeded_rng.rs
+//
+// A seeded StdRng that counts what it's drawn, so its state can be saved
+// as the seed and the count and rebuilt exactly by drawing that much again.
+
+use nannou::rand::{rngs::StdRng, RngCore, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+// Everything needed to rebuild the generator where it was
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct RngState {
+    pub seed: u64,
+    pub words: u64, // 32-bit words drawn since seeding
+}
+
+#[derive(Debug, Clone)]
+pub struct SeededRng {
+    state: RngState,
+    rng: StdRng,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            state: RngState { seed, words: 0 },
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    // Reseed and draw the same words again, to land on the same state
+    pub fn from_state(state: RngState) -> Self {
+        let mut rng = Self::new(state.seed);
+        for _ in 0..state.words {
+            rng.next_u32();
+        }
+        rng
+    }
+
+    pub fn state(&self) -> RngState {
+        self.state
+    }
+}
+
+// Everything is drawn a 32-bit word at a time, as StdRng hands them out:
+// a u64 is two words, low first, and bytes take whole words. Then the
+// count of words alone says where in the stream it is.
+impl RngCore for SeededRng {
+    fn next_u32(&mut self) -> u32 {
+        self.state.words += 1;
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let low = self.next_u32() as u64;
+        let high = self.next_u32() as u64;
+        (high << 32) | low
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(4) {
+            let word = self.next_u32().to_le_bytes();
+            chunk.copy_from_slice(&word[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), nannou::rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nannou::rand::Rng;
+
+    #[test]
+    fn test_rebuilt_from_state() {
+        let mut rng = SeededRng::new(42);
+        for _ in 0..10 {
+            rng.gen_range(0..7);
+            rng.gen_bool(0.5);
+        }
+
+        // Rebuilt partway through, it draws the same from there on
+        let mut rebuilt = SeededRng::from_state(rng.state());
+        assert_eq!(rebuilt.state(), rng.state());
+        let draws = |rng: &mut SeededRng| (0..20).map(|_| rng.gen_range(0..7)).collect::<Vec<_>>();
+        assert_eq!(draws(&mut rebuilt), draws(&mut rng));
+    }
+}
@@ -2,7 +2,7 @@
 //
 // The timer utility
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Timer {
     duration: f32,
     elapsed: f32,
@@ -45,6 +45,15 @@ impl Timer {
         self.paused = false;
     }
 
+    // Change how long the timer runs, keeping time already elapsed
+    pub fn set_duration(&mut self, duration: f32) {
+        self.duration = duration;
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.duration
+    }
+
     pub fn progress(&self) -> f32 {
         self.elapsed / self.duration
     }
@@ -0,0 +1,238 @@
+// src/views/attack_router.rs
+//
+// Versus play: turns each board's line clears into garbage for the others.
+// Garbage waits in a queue of batches on the receiving board, and lands
+// after that board's next lock that doesn't clear. A clear first cancels
+// queued garbage, oldest batch first, and what's left goes to an opponent.
+
+use crate::{config::AttackRouting, views::LockOutcome};
+use nannou::rand::{rngs::StdRng, Rng, SeedableRng};
+use std::collections::{HashMap, VecDeque};
+
+// garbage sent by a clear of 0 to 4 rows, and by a t-spin clearing 0 to 3.
+// A mini sends nothing for 0 or 1 rows; any more and it's a full t-spin.
+const LINE_ATTACK: [usize; 5] = [0, 0, 1, 2, 4];
+const TSPIN_ATTACK: [usize; 4] = [0, 2, 4, 6];
+const TSPIN_MINI_ATTACK: [usize; 4] = [0, 0, 4, 6];
+
+// extra garbage for each piece in a run of clears, from the second on
+const COMBO_BONUS: [usize; 12] = [0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 4, 5];
+
+// extra for a tetris or t-spin clear right after another one
+const BACK_TO_BACK_BONUS: usize = 1;
+const PERFECT_CLEAR_ATTACK: usize = 10;
+
+pub struct AttackRouter {
+    routing: AttackRouting,
+    rng: StdRng, // seeded, so random targets can be replayed
+    pending: HashMap<String, VecDeque<usize>>, // batches of garbage rows waiting for each board
+    back_to_back: HashMap<String, bool>, // each board's last clear was a tetris or t-spin
+    next_target: HashMap<String, usize>, // each board's round robin position
+}
+
+impl AttackRouter {
+    pub fn new(routing: AttackRouting, seed: u64) -> Self {
+        Self {
+            routing,
+            rng: StdRng::seed_from_u64(seed),
+            pending: HashMap::new(),
+            back_to_back: HashMap::new(),
+            next_target: HashMap::new(),
+        }
+    }
+
+    // Garbage a clear is worth, before cancellation. `combo` counts this clear.
+    pub fn attack(&mut self, id: &str, lock: &LockOutcome, combo: usize) -> usize {
+        let rows = lock.rows_cleared;
+        if rows == 0 {
+            return 0;
+        }
+
+        let difficult = lock.tspin || rows >= 4;
+        let mut attack = match (lock.tspin, lock.tspin_mini) {
+            (true, true) => TSPIN_MINI_ATTACK[rows.min(3)],
+            (true, false) => TSPIN_ATTACK[rows.min(3)],
+            (false, _) => LINE_ATTACK[rows.min(4)],
+        };
+
+        let was_difficult = self.back_to_back.insert(id.to_owned(), difficult);
+        if difficult && was_difficult == Some(true) {
+            attack += BACK_TO_BACK_BONUS;
+        }
+        attack += COMBO_BONUS[combo.saturating_sub(1).min(COMBO_BONUS.len() - 1)];
+        if lock.perfect_clear {
+            attack += PERFECT_CLEAR_ATTACK;
+        }
+        attack
+    }
+
+    // Route a board's clear. `opponents` are the other boards' ids and scores,
+    // sorted by id. Returns the opponent garbage was queued for, and how much.
+    pub fn send(
+        &mut self,
+        id: &str,
+        lock: &LockOutcome,
+        combo: usize,
+        opponents: &[(String, usize)],
+    ) -> Option<(String, usize)> {
+        let attack = self.attack(id, lock, combo);
+        let sent = self.cancel(id, attack);
+        if sent == 0 {
+            return None;
+        }
+
+        let target = self.pick_target(id, opponents)?;
+        self.pending
+            .entry(target.clone())
+            .or_default()
+            .push_back(sent);
+        Some((target, sent))
+    }
+
+    // Counter garbage queued for a board with its attack, oldest batch first.
+    // Returns what's left of the attack.
+    fn cancel(&mut self, id: &str, mut attack: usize) -> usize {
+        let Some(queue) = self.pending.get_mut(id) else {
+            return attack;
+        };
+
+        while let Some(batch) = queue.front_mut() {
+            if attack == 0 {
+                break;
+            }
+            let cancelled = attack.min(*batch);
+            *batch -= cancelled;
+            attack -= cancelled;
+            if *batch == 0 {
+                queue.pop_front();
+            }
+        }
+        attack
+    }
+
+    fn pick_target(&mut self, id: &str, opponents: &[(String, usize)]) -> Option<String> {
+        if opponents.is_empty() {
+            return None;
+        }
+
+        let idx = match self.routing {
+            AttackRouting::RoundRobin => {
+                let next = self.next_target.entry(id.to_owned()).or_default();
+                let idx = *next % opponents.len();
+                *next = idx + 1;
+                idx
+            }
+            AttackRouting::Random => self.rng.gen_range(0..opponents.len()),
+            // The leader takes it; ties go to the first by id
+            AttackRouting::Targeted => {
+                let best = opponents.iter().map(|(_, score)| *score).max();
+                opponents
+                    .iter()
+                    .position(|(_, score)| Some(*score) == best)
+                    .unwrap_or_default()
+            }
+        };
+        Some(opponents[idx].0.clone())
+    }
+
+    // Batches of garbage due on a board, which are now on their way in
+    pub fn take_pending(&mut self, id: &str) -> Vec<usize> {
+        self.pending.remove(id).map(Vec::from).unwrap_or_default()
+    }
+
+    // Rows of garbage queued for a board
+    pub fn pending(&self, id: &str) -> usize {
+        self.pending
+            .get(id)
+            .map(|queue| queue.iter().sum())
+            .unwrap_or_default()
+    }
+
+    // Column left open in a batch of garbage
+    pub fn garbage_hole(&mut self, width: usize) -> usize {
+        self.rng.gen_range(0..width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clear(rows_cleared: usize) -> LockOutcome {
+        LockOutcome {
+            rows_cleared,
+            chain: 1,
+            ..LockOutcome::default()
+        }
+    }
+
+    #[test]
+    fn test_tetris_sends_to_opponent() {
+        let mut router = AttackRouter::new(AttackRouting::RoundRobin, 1);
+        let opponents = |id: &str| -> Vec<(String, usize)> {
+            ["a", "b", "c"]
+                .iter()
+                .filter(|&&other| other != id)
+                .map(|&other| (other.to_owned(), 0))
+                .collect()
+        };
+
+        // A tetris from a lands 4 rows on b
+        let sent = router.send("a", &clear(4), 1, &opponents("a"));
+        assert_eq!(sent, Some(("b".to_owned(), 4)));
+        assert_eq!(router.pending("b"), 4);
+
+        // b's double cancels one of them; back to back tetrises from a go on to c
+        assert_eq!(router.send("b", &clear(2), 1, &opponents("b")), None);
+        assert_eq!(router.pending("b"), 3);
+        let sent = router.send("a", &clear(4), 2, &opponents("a"));
+        assert_eq!(sent, Some(("c".to_owned(), 4 + BACK_TO_BACK_BONUS)));
+
+        assert_eq!(router.take_pending("b"), vec![3]);
+        assert_eq!(router.pending("b"), 0);
+    }
+
+    #[test]
+    fn test_cancellation() {
+        let mut router = AttackRouter::new(AttackRouting::RoundRobin, 1);
+        let opponents = vec![("b".to_owned(), 0)];
+        router
+            .pending
+            .insert("a".to_owned(), VecDeque::from([2, 3]));
+
+        // Partial: a double's single row of garbage only dents the oldest batch
+        assert_eq!(router.send("a", &clear(2), 1, &opponents), None);
+        assert_eq!(router.pending("a"), 4);
+
+        // Full: a tetris's four rows cancel everything queued, with none to spare
+        assert_eq!(router.send("a", &clear(4), 1, &opponents), None);
+        assert_eq!(router.take_pending("a"), Vec::<usize>::new());
+
+        // Overflow: a triple cancels the one row queued, and sends the other on
+        router.pending.insert("a".to_owned(), VecDeque::from([1]));
+        let sent = router.send("a", &clear(3), 1, &opponents);
+        assert_eq!(sent, Some(("b".to_owned(), 1)));
+        assert_eq!(router.pending("a"), 0);
+        assert_eq!(router.pending("b"), 1);
+    }
+
+    #[test]
+    fn test_tspin_mini_attack() {
+        let mut router = AttackRouter::new(AttackRouting::RoundRobin, 1);
+        let spin = |rows_cleared, tspin_mini| LockOutcome {
+            tspin: true,
+            tspin_mini,
+            ..clear(rows_cleared)
+        };
+
+        // A mini single sends nothing where a full t-spin single sends two
+        assert_eq!(router.attack("a", &spin(1, true), 1), 0);
+        assert_eq!(router.attack("b", &spin(1, false), 1), TSPIN_ATTACK[1]);
+
+        // A mini clearing two is a full t-spin double, back to back after the single
+        assert_eq!(
+            router.attack("a", &spin(2, true), 1),
+            TSPIN_ATTACK[2] + BACK_TO_BACK_BONUS
+        );
+    }
+}
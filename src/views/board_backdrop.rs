@@ -0,0 +1,103 @@
+// src/views/board_backdrop.rs
+//
+// The background a board draws behind its cells: a solid color,
+// a vertical gradient or an image, as set by the rendering config.
+
+use crate::config::BoardBackground;
+use nannou::prelude::*;
+use std::rc::Rc;
+
+#[derive(Clone, Default)]
+pub enum BoardBackdrop {
+    #[default]
+    None,
+    Solid(Rgba),
+    Gradient {
+        top: Rgba,
+        bottom: Rgba,
+    },
+    Image(Rc<wgpu::Texture>), // shared by every board
+}
+
+impl BoardBackdrop {
+    // Load the configured background. An image that fails to load falls back to its solid color.
+    pub fn load(app: &App, config: &BoardBackground) -> Self {
+        match config {
+            BoardBackground::None => BoardBackdrop::None,
+            BoardBackground::Solid(color) => BoardBackdrop::Solid(to_rgba(color)),
+            BoardBackground::Gradient { top, bottom } => BoardBackdrop::Gradient {
+                top: to_rgba(top),
+                bottom: to_rgba(bottom),
+            },
+            BoardBackground::Image { path, fallback } => {
+                match wgpu::Texture::from_path(app, path) {
+                    Ok(texture) => BoardBackdrop::Image(Rc::new(texture)),
+                    Err(e) => {
+                        println!("GameOver: failed to load board background {}: {}", path, e);
+                        BoardBackdrop::Solid(to_rgba(fallback))
+                    }
+                }
+            }
+        }
+    }
+
+    // Fill the board's area, centered at `location`
+    pub fn draw(&self, draw: &Draw, location: Vec2, size: Vec2) {
+        match self {
+            BoardBackdrop::None => {}
+            BoardBackdrop::Solid(color) => {
+                draw.rect().xy(location).wh(size).color(*color);
+            }
+            BoardBackdrop::Gradient { top, bottom } => {
+                let points = gradient_points(location, size, *top, *bottom);
+                draw.mesh().indexed_colored(points, [0, 1, 2, 0, 2, 3]);
+            }
+            BoardBackdrop::Image(texture) => {
+                draw.texture(texture).xy(location).wh(size);
+            }
+        }
+    }
+}
+
+fn to_rgba(color: &[f32; 3]) -> Rgba {
+    rgba(color[0], color[1], color[2], 1.0)
+}
+
+// Corners of the board's area, bottom left first and counter-clockwise,
+// colored so the gradient runs from the bottom edge to the top edge
+fn gradient_points(location: Vec2, size: Vec2, top: Rgba, bottom: Rgba) -> [(Vec3, Rgba); 4] {
+    let half = size / 2.0;
+    [
+        (vec3(location.x - half.x, location.y - half.y, 0.0), bottom),
+        (vec3(location.x + half.x, location.y - half.y, 0.0), bottom),
+        (vec3(location.x + half.x, location.y + half.y, 0.0), top),
+        (vec3(location.x - half.x, location.y + half.y, 0.0), top),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gradient_endpoints() {
+        let top = rgba(0.1, 0.2, 0.3, 1.0);
+        let bottom = rgba(0.6, 0.5, 0.4, 1.0);
+        let location = vec2(100.0, -50.0);
+        let size = vec2(300.0, 600.0);
+
+        // The top edge sits at the top of the board in the top color
+        for (point, color) in gradient_points(location, size, top, bottom) {
+            if point.y > location.y {
+                assert_eq!(point.y, 250.0);
+                assert_eq!(color, top);
+            } else {
+                assert_eq!(point.y, -350.0);
+                assert_eq!(color, bottom);
+            }
+        }
+
+        // Colors from the config are opaque rgb
+        assert_eq!(to_rgba(&[0.1, 0.2, 0.3]), top);
+    }
+}
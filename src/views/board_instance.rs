@@ -4,14 +4,26 @@
 // handles game state, player input
 
 use crate::{
-    models::{Board, PieceType, PlaceResult},
-    utils::Timer,
-    views::{BoardPosition, PieceInstance, RotationDirection},
+    ai::Reachable,
+    config::{
+        Anchor, BoardConfig, ClearBurst, ClearGravity, DasConfig, DigConfig, GravityPoint,
+        KickTable, LandingGuide, LockMode, Randomizer, ScoringConfig,
+    },
+    models::{
+        finesse, Board, BoardFrame, ClearChain, GameStats, KickTableError, PieceBag, PieceSet,
+        PieceType, PlaceResult, SizeError, WallKicks,
+    },
+    utils::{PixelRect, RngState, SeededRng, Timer},
+    views::{
+        gravity, BoardPosition, BoardSnapshot, CellMesh, GravityStrategy, PieceInstance,
+        RenderOptions, RotationDirection, Telemetry,
+    },
 };
 use nannou::{
     prelude::*,
-    rand::{rngs::ThreadRng, Rng},
+    rand::{rngs::StdRng, Rng, SeedableRng},
 };
+use std::{collections::VecDeque, rc::Rc};
 
 // helps visualize grid for debugging
 const DEBUG: bool = false;
@@ -20,6 +32,49 @@ const DEBUG: bool = false;
 const CLEAR_DURATION: f32 = 1.0;
 const SLIDE_DURATION: f32 = 0.15;
 const GAME_OVER_DURATION: f32 = 3.0;
+const TURN_DURATION: f32 = 0.08; // a rotated piece turning into place
+
+// held soft drop falls this many times faster than gravity
+const SOFT_DROP_FACTOR: f32 = 20.0;
+
+// lock delay limits for the lock modes that reset on movement
+const MAX_LOCK_RESETS: u32 = 15; // MoveReset: resets per piece
+const INFINITE_LOCK_CAP: f32 = 5.0; // Infinite: seconds a piece can spend locking
+
+// a 1-wide well this deep is worth telling the app about, as a tetris setup
+const DEEP_WELL: isize = 4;
+
+// a full board counts again once it drains this far below the full ratio
+const FULL_HYSTERESIS: f32 = 0.1;
+
+// columns the drop assist may move a piece
+const ASSIST_REACH: isize = 1;
+
+// queued inputs: how many wait at most, and how many a tick applies
+const INPUT_QUEUE_CAPACITY: usize = 32;
+const QUEUED_INPUTS_PER_TICK: usize = 4;
+
+// mixed into a board's seed for its spawn jitter, so the pieces dealt don't change
+const JITTER_SEED: u64 = 0x9e37_79b9_7f4a_7c15;
+
+// mixed into a board's seed for the hue its color drift starts from
+const DRIFT_SEED: u64 = 0xc2b2_ae3d_27d4_eb4f;
+
+// corners of a T's 3x3 box, around the center every rotation shares
+const T_CORNERS: [(isize, isize); 4] = [(0, 0), (2, 0), (0, 2), (2, 2)];
+// the two corners on the side each T rotation points to
+const T_FRONT_CORNERS: [[(isize, isize); 2]; 4] = [
+    [(0, 0), (2, 0)],
+    [(2, 0), (2, 2)],
+    [(0, 2), (2, 2)],
+    [(0, 0), (0, 2)],
+];
+
+// how much the landing row guide lightens its row
+const LANDING_ROW_ALPHA: f32 = 0.12;
+
+// black outline around each filled cell
+const CELL_STROKE_WEIGHT: f32 = 1.5;
 
 #[derive(Debug, Copy, Clone)]
 pub enum GameState {
@@ -33,10 +88,22 @@ pub enum GameState {
     Paused,
 }
 
-#[derive(PartialEq)]
+// Key edges, not key states: a single press when a key goes down and a single
+// release when it comes up. Don't forward OS auto-repeat; KeySampler filters it.
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum PlayerInput {
-    L,
+    L, // a single step, as the AI sends
     R,
+    LHold, // pressed: one step, then auto-shifts after the DAS delay while held
+    RHold,
+    LRelease, // released
+    RRelease,
+    RotateHold,      // pressed: rotates, and a piece spawned while held starts rotated
+    RotateRelease,   // released
+    Hold,            // pressed: swaps the active piece with the held one
+    HoldRelease,     // released
+    SoftDrop,        // pressed: drops one row, then keeps falling fast while held
+    SoftDropRelease, // released
     HardDrop,
     Rotate,
     Pause,
@@ -44,6 +111,80 @@ pub enum PlayerInput {
     ResumeState,
 }
 
+impl PlayerInput {
+    // Inputs arriving in the same frame apply holds first, then moves,
+    // then rotation, then drops, so a shift-and-spin lands where it's aimed
+    fn order(self) -> u8 {
+        match self {
+            PlayerInput::Hold | PlayerInput::HoldRelease => 0,
+            PlayerInput::L
+            | PlayerInput::R
+            | PlayerInput::LHold
+            | PlayerInput::RHold
+            | PlayerInput::LRelease
+            | PlayerInput::RRelease => 1,
+            PlayerInput::Rotate | PlayerInput::RotateHold | PlayerInput::RotateRelease => 2,
+            PlayerInput::SoftDrop | PlayerInput::SoftDropRelease | PlayerInput::HardDrop => 3,
+            PlayerInput::Pause | PlayerInput::SaveState | PlayerInput::ResumeState => 4,
+        }
+    }
+}
+
+// What locking the last piece did
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LockOutcome {
+    pub rows_cleared: usize,     // rows removed over the whole chain
+    pub chain: usize,            // clear steps: 0 for no clear, 2+ for a cascade
+    pub perfect_clear: bool,     // the clear left the board empty
+    pub tspin: bool,             // the piece spun into place
+    pub tspin_mini: bool,        // ...but only as a mini
+    pub full_rows: Vec<FullRow>, // rows the piece itself filled, bottom up
+}
+
+// A row the locking piece filled, so a clear animation can start from the piece
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FullRow {
+    pub row: isize,
+    pub piece_columns: Vec<isize>, // filled by the piece, left to right; the rest were there
+}
+
+// A fading cell on a hard drop's path
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct TrailCell {
+    pos: BoardPosition,
+    age: f32, // seconds since the drop
+}
+
+// A piece of a cleared cell, flying off the board
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Fragment {
+    pos: Vec2, // screen position
+    vel: Vec2, // pixels per second
+    color: Rgba,
+    age: f32, // seconds since the clear
+}
+
+// One-cell steps for the active piece
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum MoveDirection {
+    Left,
+    Right,
+    Down,
+}
+
+impl MoveDirection {
+    fn offset(self) -> (isize, isize) {
+        match self {
+            MoveDirection::Left => (-1, 0),
+            MoveDirection::Right => (1, 0),
+            MoveDirection::Down => (0, -1),
+        }
+    }
+}
+
+// Clones are independent copies, rng included, for lookahead and undo.
+// Only a backdrop image is shared between them.
+#[derive(Clone)]
 pub struct BoardInstance {
     pub id: String,
     pub board: Board,   // the internal board logic
@@ -53,73 +194,358 @@ pub struct BoardInstance {
     screen_height: f32,
     screen_width: f32,
 
-    color: Rgba,          // color of cells
-    boundary_color: Rgba, // color of outer boundary
+    color: Rgba,           // color of cells
+    boundary_color: Rgba,  // color of outer boundary
+    render: RenderOptions, // how the board is drawn
+    trail: Vec<TrailCell>, // cells of the trails still fading
+    fragments: Vec<Fragment>,
+    rise_rows: f32,     // rows the stack was still short of when the last garbage came
+    rise_age: f32,      // seconds the stack has been rising
+    preview_angle: f32, // radians the spawn preview has spun, drawing only
+    piece_turn: f32,    // radians the active piece is drawn short of its rotation
+    hue_drift: f32,     // degrees the color has drifted, drawing only
+    landing_row: Option<isize>, // row the active piece's lowest cells will land on
 
     game_state: GameState,              // state of the game loops
     prev_game_state: Option<GameState>, // used to come back from pause, for example
+    unpaused: bool,                     // the next frame's dt may include paused time
+    tick: u64,                          // updates since the board was made, restarts and all
     timers: GameTimers,                 // timers used in the game
+    gravity_interval: f32,              // gravity without a curve
+    gravity_curve: Vec<GravityPoint>,   // gravity by level, sorted by level
+    level_lines: Vec<usize>,            // lines each level takes, the last repeating
+    gravity: Box<dyn GravityStrategy>,  // how the active piece falls
+    lock_delay: f32,                    // lock delay at the top of the board
+    lock_delay_floor: Option<f32>,      // lock delay on the floor, if it scales by row
 
     rows_to_clear: Option<Vec<isize>>, // rows idxs for the Clearing state to clear
     active_piece: Option<PieceInstance>, // the currently active piece
+    next_piece: PieceType,             // spawns after the active piece
+    practice: VecDeque<PieceType>,     // asked-for pieces that spawn before next_piece
+    randomizer: Randomizer,            // how next pieces are picked
+    bag: PieceBag,                     // the 7-bag next pieces are dealt from
+    soft_drop_held: bool,              // soft drop key is down
+    shift_held: Option<MoveDirection>, // left or right key is down
+    das_charge: f32,                   // seconds the shift key has been held
+    das: DasConfig,                    // auto-shift timing
+    rotate_held: bool,                 // rotate key is down
+    irs: bool,                         // spawn pieces rotated while rotate is held
+    held_piece: Option<PieceType>,     // set aside by the hold input
+    hold_used: bool,                   // the active piece has already been held
+    hold_down: bool,                   // hold key is down
+    ihs: bool,                         // hold pieces as they spawn while hold is down
+    assist: bool,                      // nudge hard drops away from making holes
+
+    lock_mode: LockMode,               // when the lock delay starts over
+    clear_gravity: ClearGravity,       // how cells fall after a line clear
+    dig: DigConfig,                    // garbage to start each game with
+    scoring: ScoringConfig,            // points for clears
+    lock_resets: u32,                  // lock delay resets used by the active piece
+    lock_time: f32,                    // seconds the active piece has spent locking
+    last_lock: LockOutcome,            // result of the most recent lock
+    perfect_clear: bool,               // a perfect clear the app hasn't celebrated yet
+    deep_well: Option<isize>,          // column of the deep well on the board, if any
+    new_well: Option<(isize, isize)>,  // a deep well the app hasn't seen form yet
+    untaken_lock: Option<LockOutcome>, // a finished lock the manager hasn't seen yet
+    rotated_last: bool,                // the active piece's last successful move was a rotation
+    last_kick: (isize, isize),         // offset the last rotation was kicked by
+    piece_inputs: usize,               // moves and rotations pressed for the active piece
+    stats: GameStats,                  // tallies for the current game
+
+    queued_inputs: VecDeque<PlayerInput>, // inputs that arrived between ticks, oldest first
+
+    spawn_delay: f32,        // seconds to wait before every spawn
+    spawn_jitter: f32,       // most seconds of random wait added to that
+    spawn_wait: Option<f32>, // seconds left before the next spawn, once drawn
+    jitter_rng: StdRng,      // draws spawn waits, apart from the pieces
+
+    full_ratio: Option<f32>, // share of open cells filled that counts as full
+    full: bool,              // filled past full_ratio, not yet drained back
+    board_full: bool,        // the board just filled up and the app hasn't heard
+
+    rng: SeededRng, // seeded per board so a game can be reproduced
 }
 
 impl BoardInstance {
+    // Fails for a board without at least one cell each way
     pub fn new(
         id: &str,
         location: Vec2,
-        width: usize,
-        height: usize,
-        cell_size: f32,
-        gravity_interval: f32,
-        lock_delay: f32,
-    ) -> Self {
+        config: &BoardConfig,
+        seed: u64,
+    ) -> Result<Self, SizeError> {
         //let boundary_color = rgba(0.22, 0.902, 0.082, 1.0);
         //let piece_color = rgba(0.235, 0.851, 0.11, 1.0);
 
         let boundary_color: Rgba = hsva(40.0 / 360.0, 1.0, 0.75, 1.0).into();
         let piece_color: Rgba = hsva(40.0 / 360.0, 1.0, 0.7, 1.0).into();
 
-        let screen_height = height as f32 * cell_size;
-        let screen_width = width as f32 * cell_size;
+        let mut rng = SeededRng::new(seed);
+        let mut bag = PieceBag::default();
+        let next_piece = deal_piece(config.randomizer, &mut bag, &mut rng);
 
-        Self {
+        let screen_height = config.height as f32 * config.cell_size;
+        let screen_width = config.width as f32 * config.cell_size;
+
+        let mut gravity_curve = config.gravity_curve.clone();
+        gravity_curve.sort_by_key(|point| point.level);
+
+        // Instant lock has no delay to scale
+        let lock_delay = match config.lock_mode {
+            LockMode::Instant => 0.0,
+            _ => config.lock_delay,
+        };
+
+        let mut instance = Self {
             id: id.to_owned(),
-            board: Board::new(width, height),
+            board: Board::new(config.width, config.height)?,
             location,
-            cell_size,
+            cell_size: config.cell_size,
 
             screen_height,
             screen_width,
 
             color: piece_color,
             boundary_color,
+            render: RenderOptions::default(),
+            trail: Vec::new(),
+            preview_angle: 0.0,
+            piece_turn: 0.0,
+            hue_drift: drift_start(seed),
+            fragments: Vec::new(),
+            rise_rows: 0.0,
+            rise_age: 0.0,
+            landing_row: None,
 
             game_state: GameState::Ready,
             prev_game_state: None,
+            unpaused: false,
+            tick: 0,
             timers: GameTimers::new(
-                gravity_interval,
+                config.gravity_interval,
                 lock_delay,
                 CLEAR_DURATION,
                 SLIDE_DURATION,
                 GAME_OVER_DURATION,
             ),
 
+            gravity_interval: config.gravity_interval,
+            gravity_curve,
+            level_lines: config.level_lines.clone(),
+            gravity: gravity::strategy(config.gravity),
+            lock_delay,
+            lock_delay_floor: config.lock_delay_floor,
+
             rows_to_clear: None,
             active_piece: None,
+            next_piece,
+            practice: VecDeque::new(),
+            randomizer: config.randomizer,
+            bag,
+            soft_drop_held: false,
+            shift_held: None,
+            das_charge: 0.0,
+            das: config.das,
+            rotate_held: false,
+            irs: config.irs,
+            held_piece: None,
+            hold_used: false,
+            hold_down: false,
+            ihs: config.ihs,
+            assist: config.assist,
+
+            lock_mode: config.lock_mode,
+            clear_gravity: config.clear_gravity,
+            dig: config.dig,
+            scoring: config.scoring,
+            lock_resets: 0,
+            lock_time: 0.0,
+            last_lock: LockOutcome::default(),
+            perfect_clear: false,
+            deep_well: None,
+            new_well: None,
+            untaken_lock: None,
+            rotated_last: false,
+            last_kick: (0, 0),
+            piece_inputs: 0,
+            queued_inputs: VecDeque::with_capacity(INPUT_QUEUE_CAPACITY),
+            stats: GameStats::default(),
+
+            spawn_delay: config.spawn_delay.max(0.0),
+            spawn_jitter: config.spawn_jitter.max(0.0),
+            spawn_wait: None,
+            jitter_rng: StdRng::seed_from_u64(seed ^ JITTER_SEED),
+
+            full_ratio: config.full_ratio,
+            full: false,
+            board_full: false,
+
+            rng,
+        };
+        if let Some(mask) = &config.mask {
+            if let Err(e) = instance.board.set_mask_ascii(mask) {
+                println!("GameOver: ignoring board mask: {}", e);
+            }
         }
+        match wall_kicks(&config.kicks) {
+            Ok(kicks) => instance.board.set_wall_kicks(kicks),
+            Err(e) => println!("GameOver: ignoring kick table, using SRS: {}", e),
+        }
+        instance.board.set_mirror_kicks(config.mirror_kicks);
+        instance.board.set_pieces(Rc::clone(&config.pieces));
+        instance.apply_level_gravity();
+        instance.fill_garbage();
+        Ok(instance)
     }
 
     /************************ Update orchestrator *******************************/
 
+    pub fn update(&mut self, dt: f32, inputs: &[PlayerInput]) {
+        self.tick += 1;
+
+        // The board tracks changed cells, but the active piece lives here
+        let prev_piece = self.active_piece.clone();
+
+        // Time spent paused mustn't reach the timers, so drop the first dt after a pause
+        let dt = if std::mem::take(&mut self.unpaused) {
+            0.0
+        } else {
+            dt
+        };
+        if self.game_state != GameState::Paused {
+            // Cells age by the tick, but not on a board that's over
+            if self.game_state != GameState::Frozen {
+                self.board.tick();
+            }
+            self.fade_trail(dt);
+            self.move_fragments(dt);
+            self.spin_preview(dt);
+            self.finish_turn(dt);
+            self.drift_hue(dt);
+            self.rise_age += dt;
+        }
+        // Queued taps keep the order they came in; only this frame's are sorted
+        let mut frame_inputs = inputs.to_vec();
+        frame_inputs.sort_by_key(|input| input.order());
+        let drain = self.queued_inputs.len().min(QUEUED_INPUTS_PER_TICK);
+        let inputs: Vec<PlayerInput> = self
+            .queued_inputs
+            .drain(..drain)
+            .chain(frame_inputs)
+            .collect();
+        let was_locking = matches!(self.game_state, GameState::Locking { .. });
+        self.update_state(dt, &inputs);
+        self.watch_fill();
+        if !was_locking && matches!(self.game_state, GameState::Locking { .. }) {
+            self.scale_lock_delay();
+        }
+        self.board
+            .mark_piece_moved(prev_piece.as_deref(), self.active_piece.as_deref());
+        self.landing_row = match self.render.landing_guide {
+            LandingGuide::Off => None,
+            LandingGuide::Row => self.find_landing_row(),
+        };
+    }
+
+    // Keep an input for the coming ticks. Bursts faster than the frame rate
+    // are applied a few per tick, in the order they came.
+    pub fn queue_input(&mut self, input: PlayerInput) {
+        if self.queued_inputs.len() >= INPUT_QUEUE_CAPACITY {
+            println!(
+                "GameOver: input queue full on board {}, dropping {:?}",
+                self.id, input
+            );
+            return;
+        }
+        self.queued_inputs.push_back(input);
+    }
+
+    // Queue a frame's worth of inputs, in the order a frame applies them
+    pub fn queue_inputs(&mut self, inputs: &[PlayerInput]) {
+        let mut inputs = inputs.to_vec();
+        inputs.sort_by_key(|input| input.order());
+        for input in inputs {
+            self.queue_input(input);
+        }
+    }
+
+    pub fn queued_inputs(&self) -> usize {
+        self.queued_inputs.len()
+    }
+
+    // A piece that has just landed gets the lock delay for its row
+    fn scale_lock_delay(&mut self) {
+        let Some(piece) = self.active_piece.as_ref() else {
+            return;
+        };
+        let Some(bottom) = piece
+            .cells(self.board.pieces())
+            .iter()
+            .map(|&(_, dy)| dy)
+            .min()
+        else {
+            return;
+        };
+        let delay = self.lock_delay_at(piece.position.y + bottom);
+        self.timers.lock.set_duration(delay);
+    }
+
+    // Lock delay for a piece whose lowest cells rest on `row`
+    pub fn lock_delay_at(&self, row: isize) -> f32 {
+        let Some(floor) = self.lock_delay_floor else {
+            return self.lock_delay;
+        };
+        let t = (row as f32 / (self.board.height - 1).max(1) as f32).clamp(0.0, 1.0);
+        floor + (self.lock_delay - floor) * t
+    }
+
+    // Row the active piece's lowest cells land on if it's dropped now
+    fn find_landing_row(&mut self) -> Option<isize> {
+        if !matches!(
+            self.game_state,
+            GameState::Falling | GameState::Locking { .. }
+        ) {
+            return None;
+        }
+        let (drop_pos, _) = self.get_drop_position()?;
+        let piece = self.active_piece.as_ref()?;
+        let bottom = piece
+            .cells(self.board.pieces())
+            .iter()
+            .map(|&(_, dy)| dy)
+            .min()?;
+        Some(drop_pos.y + bottom)
+    }
+
     // Game State Machine
-    pub fn update(&mut self, dt: f32, input: &Option<PlayerInput>, rng: &mut ThreadRng) {
+    fn update_state(&mut self, dt: f32, inputs: &[PlayerInput]) {
         match self.game_state {
             GameState::Ready => {
+                // The entry delay, and a random wait on top to keep boards
+                // side by side out of step
+                if self.spawn_delay > 0.0 || self.spawn_jitter > 0.0 {
+                    let wait = self.spawn_wait.get_or_insert_with(|| {
+                        let jitter = match self.spawn_jitter > 0.0 {
+                            true => self.jitter_rng.gen_range(0.0..self.spawn_jitter),
+                            false => 0.0,
+                        };
+                        self.spawn_delay + jitter
+                    });
+                    *wait -= dt;
+                    if *wait > 0.0 {
+                        return;
+                    }
+                    self.spawn_wait = None;
+                }
+
                 // Spawn a new piece
-                if self.spawn_new_piece(rng) {
+                if self.spawn_new_piece() {
                     self.timers.reset_all();
+                    self.gravity.reset();
+                    self.lock_resets = 0;
+                    self.lock_time = 0.0;
                     self.game_state = GameState::Falling;
+                    self.carry_das_charge();
                 } else {
                     self.timers.reset_all();
                     self.game_state = GameState::GameOver;
@@ -128,55 +554,35 @@ impl BoardInstance {
 
             GameState::Falling => {
                 // Handle an active piece
-                if let Some(input) = input {
+                for input in inputs {
                     self.handle_input(input);
                 }
+                self.auto_shift(dt);
 
-                if self.timers.gravity.tick(dt) {
-                    // Apply gravity and check the result
-                    if let Some(piece) = self.active_piece.as_mut() {
-                        if Self::is_piece_at_bottom(piece) {
-                            // Don't attempt to move below the bottom of the board
-                            if DEBUG {
-                                println!("Piece fell to bottom. Transition to Locking");
-                            }
-                            self.game_state = GameState::Locking {
-                                now: false,
-                                hard_drop: false,
-                            };
-                        } else {
-                            let next_pos = BoardPosition {
-                                x: piece.position.x,
-                                y: piece.position.y - 1,
-                            };
-
-                            let result = self.board.try_place(piece, next_pos);
-                            match result {
-                                PlaceResult::PlaceOk => {
-                                    // Piece moved down successfully, continue in Falling state
-                                    piece.position = next_pos;
-                                    self.timers.gravity.reset();
-                                    self.game_state = GameState::Falling;
-                                }
-                                PlaceResult::RowFilled => {
-                                    // Row was filled by gravity, immediately commit and clear
-                                    piece.position = next_pos;
-                                    self.game_state = GameState::Locking {
-                                        now: true,
-                                        hard_drop: false,
-                                    };
-                                }
-                                _ => {
-                                    if DEBUG {
-                                        println!("No valid falling position, now locking.");
-                                    }
-                                    self.game_state = GameState::Locking {
-                                        now: false,
-                                        hard_drop: false,
-                                    };
-                                }
-                            }
-                        }
+                self.apply_gravity(dt);
+
+                // Held soft drop falls at the accelerated rate, from the interval after the press
+                let pressed = inputs.contains(&PlayerInput::SoftDrop);
+                if self.soft_drop_held
+                    && !pressed
+                    && self.game_state == GameState::Falling
+                    && self.timers.soft_drop.tick(dt)
+                {
+                    self.soft_drop_step();
+                }
+
+                // Instant lock skips the locking window: the piece commits as it lands
+                if self.lock_mode == LockMode::Instant {
+                    if let GameState::Locking {
+                        now: false,
+                        hard_drop,
+                    } = self.game_state
+                    {
+                        self.game_state = GameState::Locking {
+                            now: true,
+                            hard_drop,
+                        };
+                        self.update_state(0.0, &[]);
                     }
                 }
             }
@@ -189,23 +595,31 @@ impl BoardInstance {
                     }
 
                     self.score_piece(hard_drop);
-                    self.rows_to_clear = self.commit_piece();
+                    self.rows_to_clear = self.lock_active_piece();
                     if self.rows_to_clear.is_some() {
                         self.game_state = GameState::Clearing;
                     } else {
                         self.game_state = GameState::Ready;
                     }
+                    self.check_top_out();
                     return;
                 }
 
                 // Last-minute adjustment period for piece
-                if let Some(input) = input {
+                self.lock_time += dt;
+                let before = self.piece_placement();
+                for input in inputs {
                     self.handle_input(input);
                 }
+                self.auto_shift(dt);
+                let still_locking = matches!(self.game_state, GameState::Locking { .. });
+                if still_locking && self.piece_placement() != before {
+                    self.reset_lock_on_move();
+                }
 
                 // Check if the piece can now fall because of some input during the Locking period
                 if let Some(piece) = self.active_piece.as_mut() {
-                    if Self::is_piece_at_bottom(piece) {
+                    if Self::is_piece_at_bottom(piece, self.board.pieces()) {
                         // Don't attempt to move below the bottom of the board
                         if DEBUG {
                             println!("Piece at bottom. Lock timer at {:?}", self.timers.lock);
@@ -234,7 +648,7 @@ impl BoardInstance {
                 // Commit the piece, check for filled rows, return to Ready state.
                 if self.timers.lock.tick(dt) {
                     self.score_piece(hard_drop);
-                    self.rows_to_clear = self.commit_piece();
+                    self.rows_to_clear = self.lock_active_piece();
 
                     if self.rows_to_clear.is_some() {
                         self.game_state = GameState::Clearing;
@@ -255,14 +669,17 @@ impl BoardInstance {
                     if DEBUG {
                         print_col_score(self.board.col_score_all());
                     }
+                    self.check_top_out();
                 }
             }
 
             GameState::Clearing => {
                 // Give the game a chance to pause
-                if let Some(input) = input {
+                for input in inputs {
                     self.handle_input(input);
                 }
+                // A held shift keeps charging for the next piece
+                self.auto_shift(dt);
 
                 // Let the animation run
                 if self.timers.clear_animation.tick(dt) {
@@ -274,8 +691,7 @@ impl BoardInstance {
                     }
 
                     if let Some(rows) = self.rows_to_clear.take() {
-                        self.score_row_clear(rows.len());
-                        self.clear_rows(&rows)
+                        self.last_lock = self.clear_rows(&rows);
                     }
 
                     // Reset timer and return to Ready state
@@ -287,7 +703,7 @@ impl BoardInstance {
             GameState::GameOver => {
                 // Grid has been filled to the top
                 self.commit_piece();
-                if let Some(input) = input {
+                for input in inputs {
                     self.handle_input(input);
                 }
                 if self.timers.game_over_animation.tick(dt) {
@@ -297,36 +713,115 @@ impl BoardInstance {
 
             GameState::Frozen => {
                 // Game Over, freeze the game.
-                if let Some(input) = input {
+                for input in inputs {
                     self.handle_input(input);
                 }
             }
 
             GameState::Paused => {
                 // Pause the game
-                if let Some(input) = input {
+                for input in inputs {
                     self.handle_pause_input(input);
                 }
             }
         }
     }
 
+    // Lock the active piece where it is right now, skipping the lock delay.
+    // Filled rows are cleared at once. Returns None if no piece was locked.
+    pub fn force_lock(&mut self) -> Option<LockOutcome> {
+        if !matches!(
+            self.game_state,
+            GameState::Falling | GameState::Locking { .. }
+        ) {
+            return None;
+        }
+        let piece = self.active_piece.as_ref()?;
+
+        match self.board.try_place(piece, piece.position) {
+            PlaceResult::PlaceOk | PlaceResult::RowFilled => {}
+            // A piece above the board would lock into cells that aren't there
+            PlaceResult::OutOfBounds => {
+                self.timers.reset_all();
+                self.game_state = GameState::GameOver;
+                return None;
+            }
+            // A piece overlapping the stack can't lock: the board has topped out
+            PlaceResult::PlaceBad => {
+                self.timers.reset_all();
+                self.game_state = GameState::GameOver;
+                return None;
+            }
+        }
+
+        self.score_piece(false);
+        let rows = self.lock_active_piece();
+        self.game_state = GameState::Ready;
+        if self.check_top_out() {
+            return None;
+        }
+        if let Some(rows) = rows {
+            self.last_lock = self.clear_rows(&rows);
+        }
+        Some(self.last_lock.clone())
+    }
+
+    // A piece that locked partly off the board ends the game, rows and all.
+    // Returns true if it did.
+    fn check_top_out(&mut self) -> bool {
+        if !self.board.topped_out() {
+            return false;
+        }
+        self.rows_to_clear = None;
+        self.timers.reset_all();
+        self.game_state = GameState::GameOver;
+        true
+    }
+
     /************************ Update loop methods ***************************/
-    fn spawn_new_piece(&mut self, rng: &mut ThreadRng) -> bool {
-        // Randomize new piece properties and create
-        let piece_type = self.get_random_piece_type(rng);
-        let color = self.get_piece_color();
+    fn spawn_new_piece(&mut self) -> bool {
+        let mut piece_type = self.take_next_piece();
+        self.hold_used = false;
 
-        let spawn_pos = BoardPosition {
-            x: self.board.midpoint_x() - piece_type.max_x(0) / 2,
-            y: self.board.height - piece_type.max_y(0) - 1,
-        };
+        // Initial hold: the new piece goes straight to hold
+        if self.ihs && self.hold_down {
+            self.hold_used = true;
+            piece_type = match self.held_piece.replace(piece_type) {
+                Some(held) => held,
+                None => self.take_next_piece(),
+            };
+        }
+        self.spawn_piece(piece_type)
+    }
+
+    // The next piece, with a new random one lined up behind it
+    fn take_next_piece(&mut self) -> PieceType {
+        // Asked-for pieces jump the queue without using up the randomizer
+        if let Some(piece_type) = self.practice.pop_front() {
+            return piece_type;
+        }
+        let next_piece = self.get_random_piece_type();
+        std::mem::replace(&mut self.next_piece, next_piece)
+    }
+
+    // Bring a piece into play at the top. Returns false if it doesn't fit.
+    fn spawn_piece(&mut self, piece_type: PieceType) -> bool {
+        let color = self.get_piece_color(piece_type);
 
-        let new_piece = PieceInstance::new(piece_type, color, spawn_pos);
+        let spawn_pos = self.spawn_position(piece_type);
+        let mut new_piece = PieceInstance::new(piece_type, color, spawn_pos);
+
+        // Initial rotation, if it fits
+        if self.irs && self.rotate_held {
+            if let Some(pos) = self.board.try_rotation(&new_piece, &RotationDirection::Cw) {
+                new_piece.rotate(&RotationDirection::Cw, self.board.pieces());
+                new_piece.position = pos;
+            }
+        }
 
         // Verify that piece can be placed
         let can_place = matches!(
-            self.board.try_place(&new_piece, spawn_pos),
+            self.board.try_place(&new_piece, new_piece.position),
             PlaceResult::PlaceOk | PlaceResult::RowFilled
         );
 
@@ -335,9 +830,168 @@ impl BoardInstance {
         }
 
         self.active_piece = Some(new_piece);
+        self.piece_turn = 0.0;
+        self.rotated_last = false;
+        self.piece_inputs = 0;
         can_place
     }
 
+    // Swap the active piece for the held one, or set it aside and take the
+    // next if nothing is held. Once per piece.
+    fn hold_piece(&mut self) {
+        if self.hold_used {
+            return;
+        }
+        let Some(piece) = self.active_piece.take() else {
+            return;
+        };
+
+        let piece_type = match self.held_piece.replace(piece.typ) {
+            Some(held) => held,
+            None => self.take_next_piece(),
+        };
+        self.hold_used = true;
+        self.timers.gravity.reset();
+        self.timers.lock.reset();
+        self.lock_resets = 0;
+        self.lock_time = 0.0;
+        self.game_state = if self.spawn_piece(piece_type) {
+            GameState::Falling
+        } else {
+            GameState::GameOver
+        };
+    }
+
+    // Where a piece of this type spawns, centered at the top
+    fn spawn_position(&self, typ: PieceType) -> BoardPosition {
+        BoardPosition {
+            x: self.board.midpoint_x() - self.board.pieces().max_x(typ, 0) / 2,
+            y: self.board.height - self.board.pieces().max_y(typ, 0) - 1,
+        }
+    }
+
+    // Set gravity for the current level
+    fn apply_level_gravity(&mut self) {
+        let interval = curve_interval(&self.gravity_curve, self.stats.level())
+            .unwrap_or(self.gravity_interval);
+        self.timers.set_gravity_interval(interval);
+    }
+
+    // Commit the active piece and tally it. Returns the rows it filled.
+    fn lock_active_piece(&mut self) -> Option<Vec<isize>> {
+        let tspin = self.is_tspin();
+        let tspin_mini = self.is_tspin_mini();
+        if self.is_finesse_fault() {
+            self.stats.record_finesse_fault();
+        }
+        let piece_cells = self
+            .active_piece
+            .as_ref()
+            .map(|piece| piece.board_cells(self.board.pieces()));
+        let rows = self.commit_piece();
+        self.stats.record_lock(rows.is_some(), tspin);
+        if tspin && rows.is_none() {
+            let points = match tspin_mini {
+                true => self.scoring.tspin_mini,
+                false => self.scoring.tspin,
+            };
+            self.board.add_score(points);
+        }
+        self.last_lock = LockOutcome {
+            tspin,
+            tspin_mini,
+            full_rows: full_rows(rows.as_deref(), piece_cells.as_ref()),
+            ..LockOutcome::default()
+        };
+
+        // A clearing lock is finished once its rows are cleared
+        if rows.is_none() {
+            self.untaken_lock = Some(self.last_lock.clone());
+            self.watch_well();
+        }
+        rows
+    }
+
+    // Note a deep well as it forms, once, and forget it when it's gone
+    fn watch_well(&mut self) {
+        let well = self
+            .board
+            .deepest_well()
+            .filter(|&(_, depth)| depth >= DEEP_WELL);
+        let column = well.map(|(x, _)| x);
+        if column.is_some() && column != self.deep_well {
+            self.new_well = well;
+        }
+        self.deep_well = column;
+    }
+
+    // Note the board filling past its full ratio, once until it drains again
+    fn watch_fill(&mut self) {
+        let Some(full_ratio) = self.full_ratio else {
+            return;
+        };
+        let ratio = self.board.fill_ratio();
+        if !self.full && ratio >= full_ratio {
+            self.full = true;
+            self.board_full = true;
+        } else if self.full && ratio < full_ratio - FULL_HYSTERESIS {
+            self.full = false;
+        }
+    }
+
+    // The active piece took more moves and rotations than it needed
+    fn is_finesse_fault(&self) -> bool {
+        let Some(piece) = self.active_piece.as_ref() else {
+            return false;
+        };
+        let spawn = PieceInstance::new(piece.typ, piece.color, self.spawn_position(piece.typ));
+
+        finesse::min_inputs(&self.board, &spawn, piece)
+            .is_some_and(|min_inputs| self.piece_inputs > min_inputs)
+    }
+
+    // A T that rotated into place with at least three of the four
+    // corners around its center blocked by cells or walls
+    fn is_tspin(&self) -> bool {
+        let Some(piece) = self.active_piece.as_ref() else {
+            return false;
+        };
+        if piece.typ != PieceType::T || !self.rotated_last {
+            return false;
+        }
+
+        // Every T rotation is centered on (1, 1)
+        let blocked = T_CORNERS
+            .iter()
+            .filter(|&&corner| self.is_corner_blocked(piece, corner))
+            .count();
+        blocked >= 3
+    }
+
+    // A t-spin with one of the two corners the T points to left open. The
+    // kick that moves a T two rows into its slot makes it a full one anyway.
+    fn is_tspin_mini(&self) -> bool {
+        let Some(piece) = self.active_piece.as_ref() else {
+            return false;
+        };
+        let (dx, dy) = self.last_kick;
+        if !self.is_tspin() || (dx.abs() == 1 && dy.abs() == 2) {
+            return false;
+        }
+        !T_FRONT_CORNERS[piece.rot_idx % 4]
+            .iter()
+            .all(|&corner| self.is_corner_blocked(piece, corner))
+    }
+
+    // A corner of the piece's box is filled, or off the board
+    fn is_corner_blocked(&self, piece: &PieceInstance, (dx, dy): (isize, isize)) -> bool {
+        let pos = BoardPosition {
+            x: piece.position.x + dx,
+            y: piece.position.y + dy,
+        };
+        pos.x < 0 || pos.x >= self.board.width || pos.y < 0 || self.board.is_cell_filled(pos)
+    }
+
     // Freeze a piece in place
     fn commit_piece(&mut self) -> Option<Vec<isize>> {
         self.active_piece
@@ -345,17 +999,216 @@ impl BoardInstance {
             .and_then(|piece| self.board.commit_piece(&piece))
     }
 
-    fn clear_rows(&mut self, rows: &[isize]) {
-        self.board.clear_rows(rows);
+    // Clear and score rows. Sticky gravity can set off a cascade chain.
+    fn clear_rows(&mut self, rows: &[isize]) -> LockOutcome {
+        self.burst_rows(rows);
+        let chain = match self.clear_gravity {
+            ClearGravity::Naive => {
+                self.board.clear_rows(rows);
+                ClearChain {
+                    steps: vec![rows.to_vec()],
+                }
+            }
+            ClearGravity::Sticky => self.board.clear_rows_sticky(rows),
+        };
+        self.score_clear(&chain, rows.len());
+
+        // Nothing left on the board
+        let perfect_clear = self.board.filled_count() == 0;
+        if perfect_clear {
+            self.board.score_perfect_clear();
+            self.perfect_clear = true;
+        }
+        self.stats
+            .record_clear(rows.len(), self.last_lock.tspin, perfect_clear);
+        self.stats.update_level(&self.level_lines);
+        self.apply_level_gravity();
+
         if DEBUG {
             print_col_score(self.board.col_score_all());
         }
+
+        let outcome = LockOutcome {
+            rows_cleared: chain.rows_cleared(),
+            chain: chain.depth(),
+            perfect_clear,
+            tspin: self.last_lock.tspin,
+            tspin_mini: self.last_lock.tspin_mini,
+            full_rows: std::mem::take(&mut self.last_lock.full_rows),
+        };
+        self.untaken_lock = Some(outcome.clone());
+        self.watch_well();
+        outcome
+    }
+
+    // Score a clear from the scoring table, before it's recorded in the stats
+    fn score_clear(&mut self, chain: &ClearChain, rows: usize) {
+        let tspin = self.last_lock.tspin;
+        let points = self.scoring.line_points(tspin, self.last_lock.tspin_mini);
+        let delta = self.board.score_clear_chain(chain, &points);
+
+        // A tetris or t-spin straight after another
+        if self.stats.back_to_back() && (tspin || rows >= 4) {
+            let bonus = delta as f32 * (self.scoring.back_to_back - 1.0).max(0.0);
+            self.board.add_score(bonus as usize);
+        }
+        let combo = self.stats.combo().saturating_sub(1);
+        self.board.add_score(self.scoring.combo * combo);
+    }
+
+    // Drop the active piece as far as the gravity strategy says
+    fn apply_gravity(&mut self, dt: f32) {
+        let Some((drop_pos, _)) = self.get_drop_position() else {
+            return;
+        };
+        let Some(piece) = self.active_piece.as_ref() else {
+            return;
+        };
+        let room = piece.position.y - drop_pos.y;
+
+        let step = self.gravity.step(&mut self.timers.gravity, dt, room);
+        for _ in 0..step.rows {
+            if !self.fall_one_row() || self.game_state != GameState::Falling {
+                return;
+            }
+        }
+        // Finds the piece can't fall, and starts Locking
+        if step.lock {
+            self.fall_one_row();
+        }
+    }
+
+    // Move the active piece down one row, or start Locking if it can't fall.
+    // Returns true if the piece moved.
+    fn fall_one_row(&mut self) -> bool {
+        let Some(piece) = self.active_piece.as_mut() else {
+            return false;
+        };
+
+        if Self::is_piece_at_bottom(piece, self.board.pieces()) {
+            // Don't attempt to move below the bottom of the board
+            if DEBUG {
+                println!("Piece fell to bottom. Transition to Locking");
+            }
+            self.game_state = GameState::Locking {
+                now: false,
+                hard_drop: false,
+            };
+            return false;
+        }
+
+        let next_pos = BoardPosition {
+            x: piece.position.x,
+            y: piece.position.y - 1,
+        };
+
+        match self.board.try_place(piece, next_pos) {
+            PlaceResult::PlaceOk => {
+                // Piece moved down successfully, continue in Falling state
+                piece.position = next_pos;
+                self.timers.gravity.reset();
+                self.rotated_last = false;
+                true
+            }
+            PlaceResult::RowFilled => {
+                // Row was filled by gravity, immediately commit and clear
+                piece.position = next_pos;
+                self.rotated_last = false;
+                self.game_state = GameState::Locking {
+                    now: true,
+                    hard_drop: false,
+                };
+                true
+            }
+            _ => {
+                if DEBUG {
+                    println!("No valid falling position, now locking.");
+                }
+                self.game_state = GameState::Locking {
+                    now: false,
+                    hard_drop: false,
+                };
+                false
+            }
+        }
     }
 
     /**************** Player input methods that affect GameState ******************/
 
+    // A tap drops one row; holding keeps dropping at the soft drop rate.
+    // Either way each row dropped scores a point.
+    fn soft_drop(&mut self) {
+        self.soft_drop_held = true;
+        if self.game_state == GameState::Falling {
+            self.soft_drop_step();
+            self.timers.soft_drop.reset();
+        }
+    }
+
+    // Left or right pressed: step once and start charging DAS
+    fn hold_shift(&mut self, dir: MoveDirection) {
+        // Key repeat from the OS doesn't restart the charge
+        if self.shift_held == Some(dir) {
+            return;
+        }
+        self.piece_inputs += 1;
+        self.shift_held = Some(dir);
+        self.das_charge = 0.0;
+        self.step_active_piece(dir);
+    }
+
+    fn release_shift(&mut self, dir: MoveDirection) {
+        if self.shift_held == Some(dir) {
+            self.shift_held = None;
+            self.das_charge = 0.0;
+        }
+    }
+
+    // Charge DAS while a direction is held, shifting once it's charged
+    fn auto_shift(&mut self, dt: f32) {
+        let Some(dir) = self.shift_held else {
+            return;
+        };
+        let before = shifts_due(&self.das, self.das_charge);
+        self.das_charge += dt;
+        let steps = shifts_due(&self.das, self.das_charge) - before;
+        self.shift_steps(dir, steps);
+    }
+
+    // A new piece spawned with a direction held keeps the charge and shifts
+    // at once if preserve_charge is set. Otherwise DAS charges again.
+    fn carry_das_charge(&mut self) {
+        match self.shift_held {
+            Some(dir) if self.das.preserve_charge && self.das_charge >= self.das.delay => {
+                self.das_charge = self.das.delay;
+                self.shift_steps(dir, shifts_due(&self.das, self.das.delay));
+            }
+            _ => self.das_charge = 0.0,
+        }
+    }
+
+    fn shift_steps(&mut self, dir: MoveDirection, steps: usize) {
+        for _ in 0..steps {
+            if matches!(self.game_state, GameState::Locking { now: true, .. })
+                || !self.step_active_piece(dir)
+            {
+                break;
+            }
+        }
+    }
+
+    fn soft_drop_step(&mut self) {
+        if self.fall_one_row() {
+            self.board.score_soft_drop(1);
+        }
+    }
+
     // Player-induced drop down to lowest legal position
     fn hard_drop(&mut self) {
+        if self.assist {
+            self.assist_drop();
+        }
+
         //Calculate a valid drop position
         if let Some((drop_pos, result)) = self.get_drop_position() {
             if DEBUG {
@@ -365,10 +1218,15 @@ impl BoardInstance {
             let Some(piece) = self.active_piece.as_mut() else {
                 return;
             };
+            if drop_pos != piece.position {
+                self.rotated_last = false;
+            }
 
+            let from = piece.position;
             match result {
                 PlaceResult::PlaceOk => {
                     piece.position = drop_pos;
+                    self.leave_trail(from);
                     self.timers.lock.reset();
                     self.game_state = GameState::Locking {
                         now: false,
@@ -380,6 +1238,7 @@ impl BoardInstance {
                 }
                 PlaceResult::RowFilled => {
                     piece.position = drop_pos;
+                    self.leave_trail(from);
                     self.game_state = GameState::Locking {
                         now: true,
                         hard_drop: true,
@@ -397,29 +1256,166 @@ impl BoardInstance {
         }
     }
 
-    // Generalized function to handle moving a piece to any position
-    fn move_active_piece(&mut self, new_pos: BoardPosition) {
-        let Some(result) = self.try_piece_movement(new_pos) else {
+    // Casual assist: if dropping straight down would leave a hole, slide the piece
+    // up to ASSIST_REACH columns to where it leaves fewer, landing as low as it can
+    fn assist_drop(&mut self) {
+        let Some(piece) = self.active_piece.clone() else {
             return;
         };
-
-        let Some(piece) = self.active_piece.as_mut() else {
+        let here = holes_after_drop(&mut self.board, &piece);
+        if here == 0 {
             return;
-        };
+        }
 
-        match result {
-            PlaceResult::PlaceOk => {
+        let reachable = Reachable::search(&self.board, &piece);
+        let mut best: Option<(usize, isize, isize, isize)> = None; // holes, landing y, reach, x
+        for dir in [-1, 1] {
+            for reach in 1..=ASSIST_REACH {
+                // Only as far as the piece can slide from where it is
+                let mut moved = piece.clone();
+                moved.position.x += dir * reach;
+                if !reachable.reaches(&moved)
+                    || self.board.try_place(&moved, moved.position) != PlaceResult::PlaceOk
+                {
+                    break;
+                }
+                let holes = holes_after_drop(&mut self.board, &moved);
+                let landing = self.board.calculate_drop(&moved).0.y;
+                let candidate = (holes, landing, reach, moved.position.x);
+                if holes < here && best.map_or(true, |best| candidate < best) {
+                    best = Some(candidate);
+                }
+            }
+        }
+
+        if let (Some((_, _, _, x)), Some(piece)) = (best, self.active_piece.as_mut()) {
+            piece.position.x = x;
+        }
+    }
+
+    // Trail the cells the active piece fell through from `from`, if trails are on
+    fn leave_trail(&mut self, from: BoardPosition) {
+        let Some(piece) = self
+            .active_piece
+            .as_ref()
+            .filter(|_| self.render.drop_trail.enabled)
+        else {
+            return;
+        };
+
+        for &(dx, dy) in piece.cells(self.board.pieces()) {
+            let x = piece.position.x + dx;
+            for y in (piece.position.y + dy + 1)..=(from.y + dy).min(self.board.height - 1) {
+                let pos = BoardPosition { x, y };
+                if !self.trail.iter().any(|cell| cell.pos == pos) {
+                    self.trail.push(TrailCell { pos, age: 0.0 });
+                }
+            }
+        }
+    }
+
+    // Age the trails, dropping cells that have faded out
+    fn fade_trail(&mut self, dt: f32) {
+        let lifetime = self.render.drop_trail.lifetime;
+        self.trail.retain_mut(|cell| {
+            cell.age += dt;
+            cell.age < lifetime
+        });
+    }
+
+    // Break the cells of rows about to clear into fragments, if bursts are on.
+    // Purely for show: the rows clear the same either way.
+    fn burst_rows(&mut self, rows: &[isize]) {
+        if !self.render.clear_burst.enabled {
+            return;
+        }
+
+        // Cells fly away from the board's middle column, faster the further out
+        let middle = (self.board.width - 1) as f32 / 2.0;
+        for &y in rows {
+            for x in 0..self.board.width {
+                let pos = BoardPosition { x, y };
+                if !self.board.is_cell_filled(pos) {
+                    continue;
+                }
+                let spread = (x as f32 - middle) / middle.max(1.0);
+                self.fragments.push(Fragment {
+                    pos: pos.to_screen(self),
+                    vel: vec2(spread, 0.5) * self.render.clear_burst.speed,
+                    color: self.aged_color(pos),
+                    age: 0.0,
+                });
+            }
+        }
+    }
+
+    // Fly the fragments, dropping those that have faded out
+    fn move_fragments(&mut self, dt: f32) {
+        let ClearBurst {
+            lifetime, gravity, ..
+        } = self.render.clear_burst;
+        self.fragments.retain_mut(|fragment| {
+            fragment.vel.y -= gravity * dt;
+            fragment.pos += fragment.vel * dt;
+            fragment.age += dt;
+            fragment.age < lifetime
+        });
+    }
+
+    // Generalized function to handle moving a piece to any position
+    // Returns true if the piece moved
+    fn move_active_piece(&mut self, new_pos: BoardPosition) -> bool {
+        let Some(result) = self.try_piece_movement(new_pos) else {
+            return false;
+        };
+
+        let Some(piece) = self.active_piece.as_mut() else {
+            return false;
+        };
+
+        match result {
+            PlaceResult::PlaceOk => {
                 piece.position = new_pos;
+                self.rotated_last = false;
+                true
             }
             PlaceResult::RowFilled => {
                 piece.position = new_pos;
+                self.rotated_last = false;
                 self.game_state = GameState::Locking {
                     now: true,
                     hard_drop: false,
                 };
+                true
             }
-            PlaceResult::OutOfBounds | PlaceResult::PlaceBad => {}
+            PlaceResult::OutOfBounds | PlaceResult::PlaceBad => false,
+        }
+    }
+
+    // Move the active piece one cell. Returns true if it moved.
+    fn step_active_piece(&mut self, dir: MoveDirection) -> bool {
+        let Some(piece) = self.active_piece.as_ref() else {
+            return false;
+        };
+        let (dx, dy) = dir.offset();
+        let new_pos = BoardPosition {
+            x: piece.position.x + dx,
+            y: piece.position.y + dy,
+        };
+
+        self.move_active_piece(new_pos)
+    }
+
+    // Move the active piece until it's blocked, or until it fills a row and locks.
+    // Returns how many cells it moved.
+    pub fn move_while_possible(&mut self, dir: MoveDirection) -> usize {
+        let mut moved = 0;
+        while !matches!(self.game_state, GameState::Locking { now: true, .. })
+            && self.step_active_piece(dir)
+        {
+            moved += 1;
         }
+        moved
     }
 
     fn rotate_active_piece(&mut self) {
@@ -430,14 +1426,44 @@ impl BoardInstance {
             // Try to find a valid position with wall kicks
             if let Some(new_pos) = self.board.try_rotation(piece, &rotation_direction) {
                 // Apply rotation and position
-                piece.rotate(&rotation_direction);
+                self.last_kick = (new_pos.x - piece.position.x, new_pos.y - piece.position.y);
+                piece.rotate(&rotation_direction, self.board.pieces());
                 piece.position = new_pos;
+                self.rotated_last = true;
+
+                // Drawn from where it was, a quarter turn back, turning into place
+                self.piece_turn = -PI / 2.0;
+            }
+        }
+    }
+
+    // Moves and rotations while locking may buy the piece more time
+    fn reset_lock_on_move(&mut self) {
+        match self.lock_mode {
+            LockMode::StepReset | LockMode::Instant => {}
+            LockMode::MoveReset => {
+                if self.lock_resets < MAX_LOCK_RESETS {
+                    self.lock_resets += 1;
+                    self.timers.lock.reset();
+                }
+            }
+            LockMode::Infinite => {
+                if self.lock_time < INFINITE_LOCK_CAP {
+                    self.timers.lock.reset();
+                }
             }
         }
     }
 
     /**************** Piece movement helper methods ******************/
 
+    // Position and rotation of the active piece, to tell if it moved
+    fn piece_placement(&self) -> Option<(BoardPosition, usize)> {
+        self.active_piece
+            .as_ref()
+            .map(|piece| (piece.position, piece.rot_idx))
+    }
+
     // Test movement validity
     fn try_piece_movement(&mut self, new_pos: BoardPosition) -> Option<PlaceResult> {
         self.active_piece
@@ -453,9 +1479,9 @@ impl BoardInstance {
     }
 
     // Checks that a piece is at the bottom of the grid
-    fn is_piece_at_bottom(piece: &PieceInstance) -> bool {
+    fn is_piece_at_bottom(piece: &PieceInstance, pieces: &PieceSet) -> bool {
         // Check if any cell is at y=0
-        piece.cells().iter().any(|&(_dx, dy)| {
+        piece.cells(pieces).iter().any(|&(_dx, dy)| {
             let cell_y = piece.position.y + dy;
             cell_y == 0
         })
@@ -463,15 +1489,63 @@ impl BoardInstance {
 
     /************************ Piece creation methods ************************/
     // Obtain a random PieceType
-    fn get_random_piece_type(&self, rng: &mut ThreadRng) -> PieceType {
-        let idx = rng.gen_range(0.0..7.0).trunc() as usize;
-        PieceType::from_idx(idx)
+    fn get_random_piece_type(&mut self) -> PieceType {
+        deal_piece(self.randomizer, &mut self.bag, &mut self.rng)
     }
 
-    // Get the piece's color; currently all pieces are the same color so just returns
-    // the board's filled cell color.
-    fn get_piece_color(&self) -> Rgba {
-        self.color
+    // The piece set's color for the type, or the board's filled cell color.
+    // Locked cells always take the board's color.
+    fn get_piece_color(&self, piece_type: PieceType) -> Rgba {
+        match self.board.pieces().color(piece_type) {
+            Some([red, green, blue]) => rgba(red, green, blue, self.color.alpha),
+            None => self.color,
+        }
+    }
+
+    /************************ Garbage methods **************************************/
+    // Start the game on top of the configured rows of garbage
+    fn fill_garbage(&mut self) {
+        let rows: Vec<Vec<bool>> = (0..self.dig.garbage_rows)
+            .map(|_| self.garbage_row())
+            .collect();
+        self.board.add_garbage(&rows);
+    }
+
+    // Garbage from an opponent: full rows with a hole in the same column.
+    // Tops out the board if it pushes the stack off the top.
+    pub fn receive_garbage(&mut self, count: usize, hole: usize) {
+        let width = self.board.width as usize;
+        let rows: Vec<Vec<bool>> = (0..count)
+            .map(|_| (0..width).map(|x| x != hole).collect())
+            .collect();
+
+        if !self.board.add_garbage(&rows) {
+            self.timers.reset_all();
+            self.game_state = GameState::GameOver;
+        }
+
+        // The rows are there already; only the drawing catches up
+        if self.render.garbage_rise.enabled {
+            self.rise_rows = self.rise_offset() + count as f32;
+            self.rise_age = 0.0;
+        }
+    }
+
+    // Rows below its place the stack is drawn while it rises under garbage
+    pub fn rise_offset(&self) -> f32 {
+        let duration = self.render.garbage_rise.duration.max(f32::EPSILON);
+        self.rise_rows * (1.0 - self.rise_age / duration).max(0.0)
+    }
+
+    // A row of garbage with random holes, and always at least one
+    fn garbage_row(&mut self) -> Vec<bool> {
+        let width = self.board.width as usize;
+        let density = self.dig.hole_density.clamp(0.0, 1.0) as f64;
+        let mut row: Vec<bool> = (0..width).map(|_| !self.rng.gen_bool(density)).collect();
+        if row.iter().all(|&filled| filled) {
+            row[self.rng.gen_range(0..width)] = false;
+        }
+        row
     }
 
     /************************ Scoring methods **************************************/
@@ -481,10 +1555,6 @@ impl BoardInstance {
         }
     }
 
-    fn score_row_clear(&mut self, number_of_rows: usize) {
-        self.board.score_cleared_rows(number_of_rows);
-    }
-
     pub fn score(&self) -> usize {
         self.board.score()
     }
@@ -492,30 +1562,55 @@ impl BoardInstance {
     /************************ Input handling methods *******************************/
 
     fn handle_input(&mut self, input: &PlayerInput) {
+        if matches!(
+            input,
+            PlayerInput::L | PlayerInput::R | PlayerInput::Rotate | PlayerInput::RotateHold
+        ) {
+            self.piece_inputs += 1;
+        }
+
         match input {
             PlayerInput::L => {
-                if let Some(piece) = self.active_piece.as_mut() {
-                    let new_pos = BoardPosition {
-                        x: piece.position.x - 1,
-                        y: piece.position.y,
-                    };
-
-                    self.move_active_piece(new_pos);
-                }
+                self.step_active_piece(MoveDirection::Left);
             }
             PlayerInput::R => {
-                if let Some(piece) = self.active_piece.as_mut() {
-                    let new_pos = BoardPosition {
-                        x: piece.position.x + 1,
-                        y: piece.position.y,
-                    };
-
-                    self.move_active_piece(new_pos);
-                }
+                self.step_active_piece(MoveDirection::Right);
+            }
+            PlayerInput::LHold => {
+                self.hold_shift(MoveDirection::Left);
+            }
+            PlayerInput::RHold => {
+                self.hold_shift(MoveDirection::Right);
+            }
+            PlayerInput::LRelease => {
+                self.release_shift(MoveDirection::Left);
+            }
+            PlayerInput::RRelease => {
+                self.release_shift(MoveDirection::Right);
+            }
+            PlayerInput::SoftDrop => {
+                self.soft_drop();
+            }
+            PlayerInput::SoftDropRelease => {
+                self.soft_drop_held = false;
             }
             PlayerInput::Rotate => {
                 self.rotate_active_piece();
             }
+            PlayerInput::RotateHold => {
+                self.rotate_held = true;
+                self.rotate_active_piece();
+            }
+            PlayerInput::RotateRelease => {
+                self.rotate_held = false;
+            }
+            PlayerInput::Hold => {
+                self.hold_down = true;
+                self.hold_piece();
+            }
+            PlayerInput::HoldRelease => {
+                self.hold_down = false;
+            }
             PlayerInput::HardDrop => {
                 self.hard_drop();
             }
@@ -527,11 +1622,23 @@ impl BoardInstance {
     }
 
     fn handle_pause_input(&mut self, input: &PlayerInput) {
-        // ignore everything except Pause
+        // ignore everything except Pause, and releases so no key is left held
         match input {
             PlayerInput::Pause => {
                 self.handle_pause();
             }
+            PlayerInput::LRelease => {
+                self.release_shift(MoveDirection::Left);
+            }
+            PlayerInput::RRelease => {
+                self.release_shift(MoveDirection::Right);
+            }
+            PlayerInput::RotateRelease => {
+                self.rotate_held = false;
+            }
+            PlayerInput::HoldRelease => {
+                self.hold_down = false;
+            }
             PlayerInput::SaveState => {
                 self.board.save_state();
                 self.active_piece = None;
@@ -552,6 +1659,7 @@ impl BoardInstance {
             // Exiting pause state
             self.game_state = self.prev_game_state.take().unwrap_or(GameState::Ready);
             self.timers.resume_all();
+            self.unpaused = true;
             // Restore timers if pause state exists
         } else {
             // Entering pause state
@@ -565,6 +1673,8 @@ impl BoardInstance {
 
     // Draw orchestrator
     pub fn draw(&self, draw: &Draw) {
+        self.draw_background(draw);
+
         // Allow for pausing during clearing animation
         let effective_state = if self.game_state == GameState::Paused {
             self.prev_game_state.unwrap_or(self.game_state)
@@ -577,8 +1687,8 @@ impl BoardInstance {
         if effective_state == GameState::GameOver {
             game_over_line_pos = {
                 let progress = self.timers.game_over_animation.progress();
-                let top_bound = self.screen_height / 2.0 + self.location.y;
-                let bottom_bound = self.location.y - self.screen_height / 2.0;
+                let top_bound = self.screen_height / 2.0 + self.center().y;
+                let bottom_bound = self.center().y - self.screen_height / 2.0;
                 let max_distance = top_bound - bottom_bound;
                 let separation = max_distance * progress;
                 top_bound - separation
@@ -591,70 +1701,317 @@ impl BoardInstance {
             altered_color = rgba(avg, avg, avg, self.color.alpha);
         }
 
+        let cells = self.cell_mesh(effective_state, game_over_line_pos, altered_color);
+        if DEBUG {
+            for y in 0..self.board.height {
+                for x in 0..self.board.width {
+                    let pos = BoardPosition { x, y };
+                    if !self.board.is_cell_filled(pos) && !self.board.is_cell_blocked(pos) {
+                        self.draw_unfilled_cell(draw, pos);
+                    }
+                }
+            }
+        }
+        self.draw_trail(draw);
+        self.draw_spawn_preview(draw);
+        self.draw_piece_glow(draw);
+        cells.draw(draw);
+        self.draw_turning_piece(draw);
+        self.draw_landing_row(draw);
+        self.draw_fragments(draw);
+
+        // Draw the clearing animation if effective state is Clearing state
+        if effective_state == GameState::Clearing {
+            self.draw_clear_animation(draw);
+        }
+
+        // Draw the game over animation if effective state is GameOver state
+        if effective_state == GameState::GameOver {
+            self.draw_game_over(draw, game_over_line_pos);
+        }
+
+        if self.render.show_col_heights {
+            self.draw_col_heights(draw);
+        }
+
+        // Draw boundary around the board
+        if effective_state == GameState::Frozen {
+            self.draw_boundary(draw, altered_color);
+        } else {
+            self.draw_boundary(draw, self.boundary_color);
+        }
+    }
+
+    // Draw with the board's center at (0, 0), leaving where it goes, and any
+    // rotation or scale, to the transform the caller gives `draw`
+    pub fn draw_local(&self, draw: &Draw) {
+        self.draw(&draw.translate(self.local_offset().extend(0.0)));
+    }
+
+    // What takes the board's screen points to its local ones
+    fn local_offset(&self) -> Vec2 {
+        -self.center()
+    }
+
+    // Filled cells and the active piece, batched into a single mesh
+    fn cell_mesh(
+        &self,
+        effective_state: GameState,
+        game_over_line_pos: f32,
+        altered_color: Rgba,
+    ) -> CellMesh {
+        let mut cells = CellMesh::with_capacity(
+            self.cell_size,
+            CELL_STROKE_WEIGHT,
+            (self.board.width * self.board.height) as usize,
+        );
+
+        // Garbage still rising draws the stack low
+        let up = if self.render.flip_y { -1.0 } else { 1.0 };
+        let rise = vec2(0.0, self.rise_offset() * self.cell_size * up);
+
         // Draw the board
         for y in 0..self.board.height {
             for x in 0..self.board.width {
                 let pos = BoardPosition { x, y };
-                if self.board.is_cell_filled(pos) {
-                    let screen_pos = pos.to_screen(self);
+                if self.board.is_cell_blocked(pos) {
+                    // The mask is drawn like the boundary
+                    cells.push(pos.to_screen(self), self.boundary_color);
+                } else if self.board.is_cell_filled(pos) {
+                    let screen_pos = pos.to_screen(self) - rise;
 
                     // Handle GameOver modified cell color
                     if matches!(effective_state, GameState::GameOver | GameState::Frozen)
                         && screen_pos.y > game_over_line_pos
                     {
-                        self.draw_cell(draw, pos, altered_color);
+                        cells.push(screen_pos, altered_color);
                     } else {
                         // Draw the cell normally
-                        self.draw_cell(draw, pos, self.color);
+                        let color = self.aged_color(pos);
+                        if self.render.color_drift.cells {
+                            cells.push(screen_pos, self.drifted(color));
+                        } else {
+                            cells.push(screen_pos, color);
+                        }
                     }
-                } else if DEBUG {
-                    self.draw_unfilled_cell(draw, pos)
                 }
             }
         }
 
-        // Draw the active piece
-        if let Some(piece) = &self.active_piece {
-            for &(dx, dy) in piece.cells() {
-                let pos = BoardPosition {
-                    x: piece.position.x + dx,
-                    y: piece.position.y + dy,
-                };
-
-                if pos.x >= 0 && pos.x < self.board.width && pos.y >= 0 && pos.y < self.board.height
-                {
-                    self.draw_cell(draw, pos, piece.color);
-                }
+        // Draw the active piece, unless it's still turning
+        if let Some(piece) = self
+            .active_piece
+            .as_ref()
+            .filter(|_| self.piece_turn == 0.0)
+        {
+            let color = self.active_color(piece);
+            for pos in self.visible_cells(piece) {
+                cells.push(pos.to_screen(self), color);
             }
         }
+        cells
+    }
 
-        // Draw the clearing animation if effective state is Clearing state
-        if effective_state == GameState::Clearing {
-            self.draw_clear_animation(draw);
+    fn active_color(&self, piece: &PieceInstance) -> Rgba {
+        let flash = self.lock_flash();
+        let base = self.drifted(piece.color);
+        rgba(
+            base.red + (1.0 - base.red) * flash,
+            base.green + (1.0 - base.green) * flash,
+            base.blue + (1.0 - base.blue) * flash,
+            base.alpha,
+        )
+    }
+
+    // The active piece part way through a rotation, turned about its pivot
+    fn draw_turning_piece(&self, draw: &Draw) {
+        let Some(piece) = self
+            .active_piece
+            .as_ref()
+            .filter(|_| self.piece_turn != 0.0)
+        else {
+            return;
+        };
+        let color = self.active_color(piece);
+
+        // Flipped boards turn the other way on screen
+        let (px, py) = self.board.pieces().pivot(piece.typ);
+        let up = if self.render.flip_y { -1.0 } else { 1.0 };
+        let pivot = piece.position.to_screen(self) + vec2(px, py * up) * self.cell_size;
+        let angle = self.piece_turn * up;
+        let turn = Vec2::from_angle(angle);
+
+        for pos in self.visible_cells(piece) {
+            let center = pivot + turn.rotate(pos.to_screen(self) - pivot);
+            draw.rect()
+                .xy(center)
+                .z_radians(angle)
+                .w_h(self.cell_size, self.cell_size)
+                .color(color)
+                .stroke_weight(CELL_STROKE_WEIGHT)
+                .stroke(BLACK);
         }
+    }
 
-        // Draw the game over animation if effective state is GameOver state
-        if effective_state == GameState::GameOver {
-            self.draw_game_over(draw, game_over_line_pos);
+    fn draw_background(&self, draw: &Draw) {
+        self.render.backdrop.draw(
+            draw,
+            self.center(),
+            vec2(self.screen_width, self.screen_height),
+        );
+    }
+
+    // The board color, hue shifted by the cell's age if the heatmap is on
+    fn aged_color(&self, pos: BoardPosition) -> Rgba {
+        let heatmap = &self.render.age_heatmap;
+        let Some(age) = self.board.cell_age(pos).filter(|_| heatmap.enabled) else {
+            return self.color;
+        };
+
+        let t = (age as f32 / heatmap.max_age.max(1) as f32).min(1.0);
+        let mut color = Hsla::from(self.color);
+        color.hue = RgbHue::from_degrees(color.hue.to_degrees() + heatmap.hue_shift * t);
+        Rgba::from(color)
+    }
+
+    fn draw_trail(&self, draw: &Draw) {
+        let lifetime = self.render.drop_trail.lifetime.max(f32::EPSILON);
+        for cell in &self.trail {
+            let alpha = self.render.drop_trail.alpha * (1.0 - cell.age / lifetime);
+            let base = self.drift_color();
+            let color = rgba(base.red, base.green, base.blue, alpha);
+            draw.rect()
+                .xy(cell.pos.to_screen(self))
+                .w_h(self.cell_size, self.cell_size)
+                .color(color);
         }
+    }
 
-        // Draw boundary around the board
-        if effective_state == GameState::Frozen {
-            self.draw_boundary(draw, altered_color);
-        } else {
-            self.draw_boundary(draw, self.boundary_color);
+    // Fragments shrink as they fade
+    fn draw_fragments(&self, draw: &Draw) {
+        let lifetime = self.render.clear_burst.lifetime.max(f32::EPSILON);
+        for fragment in &self.fragments {
+            let t = 1.0 - fragment.age / lifetime;
+            let mut color = fragment.color;
+            color.alpha *= t;
+            let size = self.cell_size * (0.5 + 0.5 * t);
+            draw.rect().xy(fragment.pos).w_h(size, size).color(color);
         }
     }
 
-    // Draw a filled cell
-    fn draw_cell(&self, draw: &Draw, pos: BoardPosition, color: Rgba) {
-        // Draw block
+    fn draw_spawn_preview(&self, draw: &Draw) {
+        let base = self.drift_color();
+        let color = rgba(
+            base.red,
+            base.green,
+            base.blue,
+            self.render.spawn_preview.alpha,
+        );
+
+        // Spun about the piece's pivot, which sits between rows when it's flipped too
+        let next_piece = self.next_piece();
+        let (px, py) = self.board.pieces().pivot(next_piece);
+        let up = if self.render.flip_y { -1.0 } else { 1.0 };
+        let pivot =
+            self.spawn_position(next_piece).to_screen(self) + vec2(px, py * up) * self.cell_size;
+        let turn = Vec2::from_angle(self.preview_angle);
+
+        for pos in self.spawn_preview_cells() {
+            let center = pivot + turn.rotate(pos.to_screen(self) - pivot);
+            draw.rect()
+                .xy(center)
+                .z_radians(self.preview_angle)
+                .w_h(self.cell_size, self.cell_size)
+                .no_fill()
+                .stroke_weight(CELL_STROKE_WEIGHT)
+                .stroke_color(color);
+        }
+    }
+
+    // A faint white band over the landing row, stack and all
+    fn draw_landing_row(&self, draw: &Draw) {
+        let Some(y) = self.landing_row else {
+            return;
+        };
+        let center = BoardPosition { x: 0, y }.to_screen(self);
         draw.rect()
-            .xy(pos.to_screen(self))
-            .w_h(self.cell_size, self.cell_size) // cell size
-            .color(color) // color
-            .stroke_weight(1.5)
-            .stroke(BLACK);
+            .x_y(self.center().x, center.y)
+            .w_h(self.screen_width, self.cell_size)
+            .color(rgba(1.0, 1.0, 1.0, LANDING_ROW_ALPHA));
+    }
+
+    fn draw_piece_glow(&self, draw: &Draw) {
+        let size = self.cell_size * self.render.piece_glow.scale;
+        let base = self.drift_color();
+        let color = rgba(
+            base.red,
+            base.green,
+            base.blue,
+            self.render.piece_glow.alpha,
+        );
+        for center in self.glow_cells() {
+            draw.rect().xy(center).w_h(size, size).color(color);
+        }
+    }
+
+    // A piece's cells that are on the board. Parts above the top, or pushed
+    // off an edge, aren't drawn.
+    fn visible_cells(&self, piece: &PieceInstance) -> Vec<BoardPosition> {
+        piece
+            .board_cells(self.board.pieces())
+            .into_iter()
+            .filter(|pos| {
+                (0..self.board.width).contains(&pos.x) && (0..self.board.height).contains(&pos.y)
+            })
+            .collect()
+    }
+
+    // Screen centers of the active piece's on-board cells, if the glow is on
+    fn glow_cells(&self) -> Vec<Vec2> {
+        let Some(piece) = self
+            .active_piece
+            .as_ref()
+            .filter(|_| self.render.piece_glow.enabled)
+        else {
+            return Vec::new();
+        };
+
+        self.visible_cells(piece)
+            .into_iter()
+            .map(|pos| pos.to_screen(self))
+            .collect()
+    }
+
+    fn draw_col_heights(&self, draw: &Draw) {
+        let color = rgba(0.2, 0.8, 1.0, 0.6);
+        for (center, size) in self.col_height_bars() {
+            if size.y > 0.0 {
+                draw.rect().xy(center).wh(size).color(color);
+            }
+        }
+    }
+
+    // A thin bar standing on the floor of each column, col_score cells tall.
+    // Returns each bar's screen center and size.
+    fn col_height_bars(&self) -> Vec<(Vec2, Vec2)> {
+        // Bars stand on row 0's edge, which is the top when flipped
+        let up = if self.render.flip_y { -1.0 } else { 1.0 };
+        let floor = self.center().y - up * self.screen_height / 2.0;
+        self.board
+            .col_score_all()
+            .iter()
+            .enumerate()
+            .map(|(x, &height)| {
+                let x = BoardPosition {
+                    x: x as isize,
+                    y: 0,
+                }
+                .to_screen(self)
+                .x;
+                let size = vec2(self.cell_size * 0.2, height as f32 * self.cell_size);
+                (vec2(x, floor + up * size.y / 2.0), size)
+            })
+            .collect()
     }
 
     // For debug, draw the unfilled cell's outline
@@ -689,7 +2046,7 @@ impl BoardInstance {
         .to_screen(self)
         .y;
 
-        let board_left_edge = self.location.x - (self.board.width as f32 * self.cell_size / 2.0);
+        let board_left_edge = self.center().x - (self.board.width as f32 * self.cell_size / 2.0);
         let board_width = self.board.width as f32 * self.cell_size;
 
         // Calculate separation based on progress. Minimum is half a cell height.
@@ -710,7 +2067,7 @@ impl BoardInstance {
             // Start clearing after a little bit of separation
             let clear_height = (top_y - bottom_y).abs();
             draw.rect()
-                .x_y(self.location.x, center_y)
+                .x_y(self.center().x, center_y)
                 .w_h(board_width, clear_height)
                 .color(rgba(1.0, 0.91, 0.65, alpha));
         }
@@ -729,7 +2086,7 @@ impl BoardInstance {
     }
 
     fn draw_game_over(&self, draw: &Draw, line_pos: f32) {
-        let board_left_edge = self.location.x - self.screen_width / 2.0;
+        let board_left_edge = self.center().x - self.screen_width / 2.0;
         let board_width = self.screen_width;
 
         // Main line
@@ -746,7 +2103,7 @@ impl BoardInstance {
     // Draw the outer boundary of the grid
     fn draw_boundary(&self, draw: &Draw, color: Rgba) {
         draw.rect()
-            .x_y(self.location.x, self.location.y)
+            .x_y(self.center().x, self.center().y)
             .w_h(self.screen_width, self.screen_height)
             .stroke_weight(1.0)
             .stroke_color(color)
@@ -755,58 +2112,452 @@ impl BoardInstance {
 
     /************************ Utility methods *******************************/
 
-    pub fn board(&self) -> &Board {
-        &self.board
+    // Where the piece and garbage generator is, exactly, for snapshots
+    pub fn rng_state(&self) -> RngState {
+        self.rng.state()
     }
 
-    pub fn board_mut(&mut self) -> &mut Board {
-        &mut self.board
+    // Deal on from where a snapshot left off: the generator, the piece it
+    // had dealt next and what was left in the bag. The cells stay as they are.
+    pub fn restore(&mut self, snapshot: &BoardSnapshot) {
+        self.rng = SeededRng::from_state(snapshot.rng);
+        if let Some(typ) = PieceType::named(&snapshot.next_piece) {
+            self.next_piece = typ;
+        }
+        let bag: Vec<PieceType> = snapshot
+            .bag
+            .iter()
+            .filter_map(|name| PieceType::named(name))
+            .collect();
+        self.bag = PieceBag::with_remaining(&bag);
     }
-}
 
-/************************ Stdout functions *******************************/
+    // Start a fresh game on an empty board with a new piece sequence
+    pub fn restart(&mut self, seed: u64) {
+        self.board.reset();
+        self.game_state = GameState::Ready;
+        self.prev_game_state = None;
+        self.timers.resume_all();
+        self.timers.reset_all();
+        self.rows_to_clear = None;
+        self.active_piece = None;
+        self.last_lock = LockOutcome::default();
+        self.perfect_clear = false;
+        self.deep_well = None;
+        self.new_well = None;
+        self.untaken_lock = None;
+        self.queued_inputs.clear();
+        self.trail.clear();
+        self.fragments.clear();
+        self.rise_rows = 0.0;
+        self.stats = GameStats::default();
+        self.apply_level_gravity();
+        self.held_piece = None;
+        self.hold_used = false;
+        self.rng = SeededRng::new(seed);
+        self.spawn_wait = None;
+        self.jitter_rng = StdRng::seed_from_u64(seed ^ JITTER_SEED);
+        self.hue_drift = drift_start(seed);
+        self.full = false;
+        self.board_full = false;
+        self.bag.reset();
+        self.next_piece = self.get_random_piece_type();
+        self.practice.clear();
+        self.fill_garbage();
+    }
 
-fn spawn_new_piece_msg(piece: &PieceInstance) {
-    println!("\n-- Spawned new piece --");
-    println!(
-        "PieceType: {:?}\nPosition:{:?}\n",
-        piece.typ, piece.position
-    )
-}
+    pub fn game_state(&self) -> GameState {
+        self.game_state
+    }
 
-fn print_col_score(col_score: &Vec<isize>) {
-    println!("\nCol score:");
-    println!("{:?}", col_score);
-}
+    // One per update, whatever its dt; only ever goes up
+    pub fn current_tick(&self) -> u64 {
+        self.tick
+    }
 
-struct GameTimers {
-    gravity: Timer,
-    lock: Timer,
-    clear_animation: Timer,
-    slide_animation: Timer,
-    game_over_animation: Timer,
-}
+    pub fn active_piece(&self) -> Option<&PieceInstance> {
+        self.active_piece.as_ref()
+    }
 
-impl GameTimers {
-    pub fn new(
-        gravity_interval: f32,
-        lock_delay: f32,
-        clear_duration: f32,
-        slide_duration: f32,
-        game_over_duration: f32,
-    ) -> Self {
-        Self {
-            gravity: Timer::new(gravity_interval),
-            lock: Timer::new(lock_delay),
-            clear_animation: Timer::new(clear_duration),
-            slide_animation: Timer::new(slide_duration), // currently unused
-            game_over_animation: Timer::new(game_over_duration),
-        }
+    pub fn next_piece(&self) -> PieceType {
+        self.practice.front().copied().unwrap_or(self.next_piece)
     }
 
-    pub fn pause_all(&mut self) {
-        self.gravity.pause();
-        self.lock.pause();
+    // Practice: make `typ` the next piece to spawn, ahead of any already asked for.
+    // The randomizer carries on as if nothing came between.
+    pub fn queue_piece(&mut self, typ: PieceType) {
+        self.practice.push_front(typ);
+    }
+
+    // Types the current bag has yet to deal, in no order. Empty unless dealing from a bag.
+    // The randomizer's next piece, behind any asked-for ones
+    pub fn dealt_piece(&self) -> PieceType {
+        self.next_piece
+    }
+
+    pub fn bag_remaining(&self) -> Vec<PieceType> {
+        self.bag.remaining().to_vec()
+    }
+
+    pub fn held_piece(&self) -> Option<PieceType> {
+        self.held_piece
+    }
+
+    // How much of the lock delay has run, 0 to 1, while the piece is locking
+    pub fn lock_progress(&self) -> Option<f32> {
+        matches!(self.game_state, GameState::Locking { .. })
+            .then(|| self.timers.lock.progress().clamp(0.0, 1.0))
+    }
+
+    // How far toward white the active piece is drawn right now, 0 when not locking
+    pub fn lock_flash(&self) -> f32 {
+        let Some(progress) = self
+            .lock_progress()
+            .filter(|_| self.render.lock_flash.enabled)
+        else {
+            return 0.0;
+        };
+        let pulse = 0.5 - 0.5 * (TAU * self.render.lock_flash.pulses * progress).cos();
+        self.render.lock_flash.brightness * progress * pulse
+    }
+
+    // Gravity right now, level curve and all, in G: rows per 60th of a second.
+    // Taken from the interval, not the frame rate, so 1G needs an interval of a 60th.
+    pub fn gravity_g(&self) -> f32 {
+        self.gravity.g(self.timers.gravity.duration())
+    }
+
+    // How far along the wait for the next gravity step is, 0 to 1
+    pub fn gravity_progress(&self) -> f32 {
+        self.timers.gravity.progress().clamp(0.0, 1.0)
+    }
+
+    pub fn last_lock(&self) -> &LockOutcome {
+        &self.last_lock
+    }
+
+    pub fn set_render_options(&mut self, render: RenderOptions) {
+        if !render.spawn_preview.spin {
+            self.preview_angle = 0.0;
+        }
+        self.rise_rows = 0.0;
+        self.render = render;
+    }
+
+    // Turn the spawn preview a little more, if it spins
+    fn spin_preview(&mut self, dt: f32) {
+        if self.render.spawn_preview.spin {
+            let step = self.render.spawn_preview.spin_speed * TAU * dt;
+            self.preview_angle = (self.preview_angle + step).rem_euclid(TAU);
+        }
+    }
+
+    // Turn the active piece the rest of the way into its rotation
+    fn finish_turn(&mut self, dt: f32) {
+        let step = PI / 2.0 / TURN_DURATION * dt;
+        self.piece_turn = (self.piece_turn + step).min(0.0);
+    }
+
+    // Radians the active piece is still drawn turned back from its rotation,
+    // about its pivot. Like the preview, only the drawing turns.
+    pub fn piece_turn(&self) -> f32 {
+        self.piece_turn
+    }
+
+    // Radians the spawn preview has spun. Only the drawing turns: the next
+    // piece still spawns in its first rotation.
+    pub fn preview_angle(&self) -> f32 {
+        self.preview_angle
+    }
+
+    fn drift_hue(&mut self, dt: f32) {
+        if self.render.color_drift.enabled {
+            let step = self.render.color_drift.speed * dt;
+            self.hue_drift = (self.hue_drift + step).rem_euclid(360.0);
+        }
+    }
+
+    // The board's color as drawn right now, drifted if that's on
+    pub fn drift_color(&self) -> Rgba {
+        self.drifted(self.color)
+    }
+
+    fn drifted(&self, color: Rgba) -> Rgba {
+        if !self.render.color_drift.enabled {
+            return color;
+        }
+        let mut drifted = Hsla::from(color);
+        drifted.hue = RgbHue::from_degrees(drifted.hue.to_degrees() + self.hue_drift);
+        Rgba::from(drifted)
+    }
+
+    // Cells the next piece will take when it spawns, if the preview is showing.
+    // Only drawn: they don't block anything.
+    pub fn spawn_preview_cells(&self) -> Vec<BoardPosition> {
+        if !self.render.spawn_preview.enabled || self.game_state != GameState::Falling {
+            return Vec::new();
+        }
+        let next_piece = self.next_piece();
+        let spawn = self.spawn_position(next_piece);
+        self.visible_cells(&PieceInstance::new(next_piece, self.color, spawn))
+    }
+
+    pub fn flip_y(&self) -> bool {
+        self.render.flip_y
+    }
+
+    // Screen position of the board's center, wherever it's anchored
+    pub fn center(&self) -> Vec2 {
+        let half = vec2(self.screen_width, self.screen_height) / 2.0;
+        match self.render.anchor {
+            Anchor::Center => self.location,
+            Anchor::TopLeft => self.location + vec2(half.x, -half.y),
+            Anchor::BottomLeft => self.location + half,
+        }
+    }
+
+    pub fn landing_row(&self) -> Option<isize> {
+        self.landing_row
+    }
+
+    pub fn stats(&self) -> GameStats {
+        self.stats
+    }
+
+    // Everything a HUD or outside viewer reads, in one go
+    pub fn telemetry(&self) -> Telemetry<'_> {
+        Telemetry::capture(self)
+    }
+
+    // Each lock once it's finished, rows cleared and all, exactly once
+    pub fn take_lock(&mut self) -> Option<LockOutcome> {
+        self.untaken_lock.take()
+    }
+
+    // Column and depth of a deep 1-wide well, once as it forms
+    pub fn take_new_well(&mut self) -> Option<(isize, isize)> {
+        self.new_well.take()
+    }
+
+    // True once after each perfect clear, for the app's celebration
+    pub fn take_perfect_clear(&mut self) -> bool {
+        std::mem::take(&mut self.perfect_clear)
+    }
+
+    // True once as the board fills past its full ratio, for music to build
+    pub fn take_board_full(&mut self) -> bool {
+        std::mem::take(&mut self.board_full)
+    }
+
+    // Snapshot of what the board looks like right now, for playback
+    pub fn frame(&self) -> BoardFrame {
+        BoardFrame::capture(&self.board, self.active_piece.as_deref())
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn board_mut(&mut self) -> &mut Board {
+        &mut self.board
+    }
+
+    // Drop floating cells to rest with the board's clear gravity, so a hand
+    // made layout looks like one a game could have left
+    pub fn settle(&mut self) {
+        match self.clear_gravity {
+            ClearGravity::Naive => self.board.settle(),
+            ClearGravity::Sticky => self.board.settle_sticky(),
+        }
+    }
+
+    // Change the board's size in cells, keeping what still fits from the bottom left
+    pub fn resize(&mut self, width: usize, height: usize) -> Result<(), SizeError> {
+        self.board.resize(width, height)?;
+        self.screen_width = width as f32 * self.cell_size;
+        self.screen_height = height as f32 * self.cell_size;
+        self.trail.clear();
+        if let Some(rows) = self.rows_to_clear.as_mut() {
+            rows.retain(|&y| y < height as isize);
+        }
+
+        // A piece the new edges cut through starts over from the spawn position
+        let Some(piece_type) = self
+            .active_piece
+            .as_ref()
+            .filter(|piece| {
+                piece.board_cells(self.board.pieces()).iter().any(|pos| {
+                    pos.x < 0 || pos.x >= width as isize || pos.y < 0 || pos.y >= height as isize
+                })
+            })
+            .map(|piece| piece.typ)
+        else {
+            return Ok(());
+        };
+        self.timers.lock.reset();
+        self.lock_resets = 0;
+        self.lock_time = 0.0;
+        if self.spawn_piece(piece_type) {
+            println!(
+                "GameOver: active piece on board {} was cut off by resize, respawned at the top",
+                self.id
+            );
+            if matches!(self.game_state, GameState::Locking { .. }) {
+                self.game_state = GameState::Falling;
+            }
+        } else {
+            println!(
+                "GameOver: no room to respawn the active piece on board {} after resize",
+                self.id
+            );
+            self.timers.reset_all();
+            self.game_state = GameState::GameOver;
+        }
+        Ok(())
+    }
+
+    // Where the board sits in a render texture of `texture_size` pixels
+    pub fn pixel_bounds(&self, texture_size: [u32; 2]) -> PixelRect {
+        PixelRect::from_screen(
+            self.center(),
+            vec2(self.screen_width, self.screen_height),
+            texture_size,
+        )
+    }
+}
+
+/************************ Stdout functions *******************************/
+
+fn spawn_new_piece_msg(piece: &PieceInstance) {
+    println!("\n-- Spawned new piece --");
+    println!(
+        "PieceType: {:?}\nPosition:{:?}\n",
+        piece.typ, piece.position
+    )
+}
+
+// Auto-shift steps owed after holding a direction for `charge` seconds.
+// With no repeat delay, enough to reach the wall.
+fn shifts_due(das: &DasConfig, charge: f32) -> usize {
+    if charge < das.delay {
+        0
+    } else if das.repeat <= 0.0 {
+        usize::MAX
+    } else {
+        ((charge - das.delay) / das.repeat) as usize + 1
+    }
+}
+
+// Holes on the board once the piece is dropped straight down and committed
+fn holes_after_drop(board: &mut Board, piece: &PieceInstance) -> usize {
+    let mut landed = piece.clone();
+    landed.position = board.calculate_drop(piece).0;
+    let mut after = board.clone();
+    after.commit_piece(&landed);
+    after.hole_count()
+}
+
+// The rows a piece filled, bottom up, each with the columns of the piece's cells in it
+fn full_rows(rows: Option<&[isize]>, piece_cells: Option<&[BoardPosition; 4]>) -> Vec<FullRow> {
+    let mut rows = rows.unwrap_or_default().to_vec();
+    rows.sort_unstable();
+    rows.dedup();
+
+    rows.into_iter()
+        .map(|row| {
+            let mut piece_columns: Vec<isize> = piece_cells
+                .into_iter()
+                .flatten()
+                .filter(|pos| pos.y == row)
+                .map(|pos| pos.x)
+                .collect();
+            piece_columns.sort_unstable();
+            FullRow { row, piece_columns }
+        })
+        .collect()
+}
+
+fn wall_kicks(config: &KickTable) -> Result<WallKicks, KickTableError> {
+    match config {
+        KickTable::Srs => Ok(WallKicks::Srs),
+        KickTable::Ars => Ok(WallKicks::Ars),
+        KickTable::Custom { jlszt, i } => WallKicks::custom(jlszt, i),
+    }
+}
+
+fn deal_piece(randomizer: Randomizer, bag: &mut PieceBag, rng: &mut SeededRng) -> PieceType {
+    match randomizer {
+        Randomizer::Uniform => PieceType::from_idx(rng.gen_range(0..7)),
+        Randomizer::Bag => bag.deal(rng),
+    }
+}
+
+// Seconds between gravity steps at `level`, interpolated between breakpoints
+// and held at the curve's ends. None for an empty curve.
+fn curve_interval(curve: &[GravityPoint], level: usize) -> Option<f32> {
+    let first = curve.first()?;
+    if level <= first.level {
+        return Some(first.interval);
+    }
+
+    for pair in curve.windows(2) {
+        let (from, to) = (pair[0], pair[1]);
+        if level <= to.level {
+            let t = (level - from.level) as f32 / (to.level - from.level) as f32;
+            return Some(from.interval + (to.interval - from.interval) * t);
+        }
+    }
+    curve.last().map(|point| point.interval)
+}
+
+// Where a board's hue drift starts, the same for the same seed
+fn drift_start(seed: u64) -> f32 {
+    StdRng::seed_from_u64(seed ^ DRIFT_SEED).gen_range(0.0..360.0)
+}
+
+fn print_col_score(col_score: &Vec<isize>) {
+    println!("\nCol score:");
+    println!("{:?}", col_score);
+}
+
+#[derive(Clone)]
+struct GameTimers {
+    gravity: Timer,
+    soft_drop: Timer,
+    lock: Timer,
+    clear_animation: Timer,
+    slide_animation: Timer,
+    game_over_animation: Timer,
+}
+
+impl GameTimers {
+    pub fn new(
+        gravity_interval: f32,
+        lock_delay: f32,
+        clear_duration: f32,
+        slide_duration: f32,
+        game_over_duration: f32,
+    ) -> Self {
+        Self {
+            gravity: Timer::new(gravity_interval),
+            soft_drop: Timer::new(gravity_interval / SOFT_DROP_FACTOR),
+            lock: Timer::new(lock_delay),
+            clear_animation: Timer::new(clear_duration),
+            slide_animation: Timer::new(slide_duration), // currently unused
+            game_over_animation: Timer::new(game_over_duration),
+        }
+    }
+
+    pub fn set_gravity_interval(&mut self, gravity_interval: f32) {
+        self.gravity.set_duration(gravity_interval);
+        self.soft_drop
+            .set_duration(gravity_interval / SOFT_DROP_FACTOR);
+    }
+
+    pub fn pause_all(&mut self) {
+        self.gravity.pause();
+        self.soft_drop.pause();
+        self.lock.pause();
         self.clear_animation.pause();
         self.slide_animation.pause();
         self.game_over_animation.pause();
@@ -814,6 +2565,7 @@ impl GameTimers {
 
     pub fn resume_all(&mut self) {
         self.gravity.resume();
+        self.soft_drop.resume();
         self.lock.resume();
         self.clear_animation.resume();
         self.slide_animation.resume();
@@ -822,6 +2574,7 @@ impl GameTimers {
 
     pub fn reset_all(&mut self) {
         self.gravity.reset();
+        self.soft_drop.reset();
         self.lock.reset();
         self.clear_animation.reset();
         self.slide_animation.reset();
@@ -845,3 +2598,1712 @@ impl PartialEq for GameState {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::{
+            AgeHeatmap, ColorDrift, DropTrail, GarbageRise, GravityMode, LockFlash, PieceDefFile,
+            PieceGlow, PieceSetFile, SpawnPreview,
+        },
+        models::{PieceSet, PieceSetError},
+    };
+
+    fn test_board() -> BoardInstance {
+        test_board_with_lock_mode(LockMode::StepReset)
+    }
+
+    fn test_config() -> BoardConfig {
+        BoardConfig::default()
+    }
+
+    fn test_board_with_lock_mode(lock_mode: LockMode) -> BoardInstance {
+        let config = BoardConfig {
+            lock_mode,
+            ..test_config()
+        };
+        BoardInstance::new("board1", vec2(0.0, 0.0), &config, 1).unwrap()
+    }
+
+    // Hard drop a piece, then wiggle it every `dt` until it locks.
+    // Returns how many moves it took.
+    fn moves_until_locked(lock_mode: LockMode, dt: f32, max_moves: usize) -> usize {
+        let mut board = test_board_with_lock_mode(lock_mode);
+        board.update(dt, &[]);
+        board.update(dt, &[PlayerInput::HardDrop]);
+        assert!(matches!(board.game_state(), GameState::Locking { .. }));
+
+        for moves in 0..max_moves {
+            let input = if moves % 2 == 0 {
+                PlayerInput::L
+            } else {
+                PlayerInput::R
+            };
+            board.update(dt, &[input]);
+            if !matches!(board.game_state(), GameState::Locking { .. }) {
+                return moves + 1;
+            }
+        }
+        max_moves
+    }
+
+    fn piece_cells(board: &BoardInstance) -> Vec<(isize, isize)> {
+        let piece = board.active_piece().unwrap();
+        piece
+            .cells(board.board.pieces())
+            .iter()
+            .map(|&(dx, dy)| (piece.position.x + dx, piece.position.y + dy))
+            .collect()
+    }
+
+    #[test]
+    fn test_piece_move_marks_dirty_cells() {
+        let mut board = test_board();
+        let dt = 1.0 / 60.0;
+
+        // Spawn a piece and forget the initial full-board redraw
+        board.update(dt, &[]);
+        assert_eq!(board.game_state(), GameState::Falling);
+        board.board_mut().take_dirty();
+
+        let before = piece_cells(&board);
+        board.update(dt, &[PlayerInput::L]);
+        let after = piece_cells(&board);
+        assert_ne!(before, after);
+
+        // Exactly the vacated and newly occupied cells
+        let mut expected: Vec<(isize, isize)> = before
+            .iter()
+            .filter(|cell| !after.contains(cell))
+            .chain(after.iter().filter(|cell| !before.contains(cell)))
+            .copied()
+            .collect();
+        let mut dirty: Vec<(isize, isize)> = board
+            .board_mut()
+            .take_dirty()
+            .iter()
+            .map(|pos| (pos.x, pos.y))
+            .collect();
+        expected.sort();
+        dirty.sort();
+        assert_eq!(dirty, expected);
+
+        // Nothing changed since
+        board.update(dt, &[]);
+        assert!(board.board_mut().take_dirty().is_empty());
+    }
+
+    #[test]
+    fn test_soft_drop_tap() {
+        let mut board = test_board();
+        let dt = 1.0 / 60.0;
+        board.update(dt, &[]);
+        let start_y = board.active_piece().unwrap().position.y;
+
+        // Press and release: one row, one point
+        board.update(dt, &[PlayerInput::SoftDrop]);
+        board.update(dt, &[PlayerInput::SoftDropRelease]);
+        for _ in 0..5 {
+            board.update(dt, &[]);
+        }
+
+        assert_eq!(board.active_piece().unwrap().position.y, start_y - 1);
+        assert_eq!(board.score(), 1);
+    }
+
+    #[test]
+    fn test_soft_drop_hold() {
+        let mut board = test_board();
+        let soft_drop_interval = 0.5 / SOFT_DROP_FACTOR;
+        board.update(soft_drop_interval, &[]);
+        let start_y = board.active_piece().unwrap().position.y;
+
+        // One row on press, then one per soft drop interval while held
+        board.update(soft_drop_interval, &[PlayerInput::SoftDrop]);
+        for _ in 0..10 {
+            board.update(soft_drop_interval, &[]);
+        }
+        assert_eq!(board.active_piece().unwrap().position.y, start_y - 11);
+        assert_eq!(board.score(), 11);
+
+        // Held all the way down, it lands and locks as usual
+        for _ in 0..100 {
+            board.update(soft_drop_interval, &[]);
+        }
+        assert!((0..10).any(|x| board.board().is_cell_filled(BoardPosition { x, y: 0 })));
+    }
+
+    #[test]
+    fn test_lock_modes() {
+        // Each move comes after half the 0.25s lock delay
+        let dt = 0.125;
+
+        // Moving never resets the delay, so the second frame locks
+        assert_eq!(moves_until_locked(LockMode::StepReset, dt, 100), 2);
+
+        // Every move resets until the resets run out
+        assert_eq!(
+            moves_until_locked(LockMode::MoveReset, dt, 100),
+            MAX_LOCK_RESETS as usize + 1
+        );
+
+        // Moves keep resetting until the cap on time spent locking
+        let moves = moves_until_locked(LockMode::Infinite, dt, 100);
+        assert!(moves > MAX_LOCK_RESETS as usize + 1);
+        assert_eq!(moves, (INFINITE_LOCK_CAP / dt) as usize);
+    }
+
+    #[test]
+    fn test_move_while_possible() {
+        let mut board = test_board();
+        board.update(1.0 / 60.0, &[]);
+
+        let gap = piece_cells(&board).iter().map(|&(x, _)| x).min().unwrap();
+        assert!(gap > 0);
+        assert_eq!(board.move_while_possible(MoveDirection::Left), gap as usize);
+
+        // Flush against the wall, with nowhere left to go
+        let left = piece_cells(&board).iter().map(|&(x, _)| x).min();
+        assert_eq!(left, Some(0));
+        assert_eq!(board.move_while_possible(MoveDirection::Left), 0);
+    }
+
+    #[test]
+    fn test_dig_garbage() {
+        let config = BoardConfig {
+            dig: DigConfig {
+                garbage_rows: 6,
+                hole_density: 0.3,
+            },
+            ..test_config()
+        };
+        let mut board = BoardInstance::new("board1", vec2(0.0, 0.0), &config, 7).unwrap();
+
+        for _ in 0..2 {
+            let grid = board.board();
+            for y in 0..grid.height {
+                let filled = grid.row_score(y).unwrap();
+                if y < 6 {
+                    assert!(filled > 0 && filled < grid.width, "row {}: {}", y, filled);
+                } else {
+                    assert_eq!(filled, 0, "row {}", y);
+                }
+            }
+
+            // Garbage comes back on restart
+            board.restart(8);
+        }
+    }
+
+    #[test]
+    fn test_perfect_clear() {
+        let mut board = test_board();
+        let full = vec![true; 10];
+        let mut gap = full.clone();
+        gap[3] = false;
+
+        // A row left over is no perfect clear
+        board.board.add_garbage(&[gap, full.clone()]);
+        let outcome = board.clear_rows(&[1]);
+        assert!(!outcome.perfect_clear);
+        assert!(!board.take_perfect_clear());
+        assert_eq!(board.score(), 100);
+
+        // Clearing the rest empties the board
+        board.board.clear();
+        board.board.add_garbage(&[full.clone(), full]);
+        let outcome = board.clear_rows(&[0, 1]);
+        assert!(outcome.perfect_clear);
+        assert_eq!(board.score(), 100 + 300 + 3000);
+        assert!(board.take_perfect_clear());
+        assert!(!board.take_perfect_clear());
+    }
+
+    // Make a piece the active one, as if it had just spawned
+    fn set_active_piece(board: &mut BoardInstance, typ: PieceType, rot_idx: usize, x: isize) {
+        let mut piece = PieceInstance::new(typ, board.color, BoardPosition { x, y: 0 });
+        piece.rot_idx = rot_idx;
+        board.active_piece = Some(piece);
+        board.rotated_last = false;
+        board.game_state = GameState::Falling;
+    }
+
+    // Rows of garbage from the bottom up, listing the holes in each
+    fn garbage(board: &mut BoardInstance, holes: &[&[isize]]) {
+        let rows: Vec<Vec<bool>> = holes
+            .iter()
+            .map(|row| (0..10).map(|x| !row.contains(&x)).collect())
+            .collect();
+        board.board.clear();
+        board.board.add_garbage(&rows);
+    }
+
+    #[test]
+    fn test_full_rows_from_piece() {
+        let mut board = test_board();
+        board.update(1.0 / 60.0, &[]);
+
+        // A T pointing down into a gap, finishing both bottom rows
+        garbage(&mut board, &[&[4], &[3, 4, 5]]);
+        set_active_piece(&mut board, PieceType::T, 0, 3);
+        let piece = board
+            .active_piece()
+            .unwrap()
+            .board_cells(board.board.pieces());
+        let lock = board.force_lock().unwrap();
+        assert_eq!(lock.rows_cleared, 2);
+
+        let row = |row, piece_columns: Vec<isize>| FullRow { row, piece_columns };
+        assert_eq!(lock.full_rows, vec![row(0, vec![4]), row(1, vec![3, 4, 5])]);
+        for full in &lock.full_rows {
+            let in_row = piece.iter().filter(|pos| pos.y == full.row);
+            assert!(in_row
+                .map(|pos| pos.x)
+                .eq(full.piece_columns.iter().copied()));
+        }
+
+        // Nothing filled, nothing listed
+        set_active_piece(&mut board, PieceType::O, 0, 0);
+        assert!(board.force_lock().unwrap().full_rows.is_empty());
+    }
+
+    #[test]
+    fn test_game_stats() {
+        let mut board = test_board();
+        board.update(1.0 / 60.0, &[]);
+
+        // T spun into a slot under an overhang, without clearing
+        garbage(
+            &mut board,
+            &[&[0, 5], &[0, 4, 5, 6], &[0, 1, 2, 3, 5, 6, 7, 8, 9]],
+        );
+        set_active_piece(&mut board, PieceType::T, 3, 4);
+        board.update(1.0 / 60.0, &[PlayerInput::Rotate]);
+        assert_eq!(board.force_lock().unwrap().rows_cleared, 0);
+
+        // A double that empties the board, then a single
+        garbage(&mut board, &[&[0, 1], &[0, 1]]);
+        set_active_piece(&mut board, PieceType::O, 0, 0);
+        assert!(board.force_lock().unwrap().perfect_clear);
+
+        garbage(&mut board, &[&[0, 1]]);
+        set_active_piece(&mut board, PieceType::O, 0, 0);
+        assert_eq!(board.force_lock().unwrap().rows_cleared, 1);
+
+        // Breaks the combo
+        set_active_piece(&mut board, PieceType::O, 0, 4);
+        assert_eq!(board.force_lock().unwrap().rows_cleared, 0);
+
+        let stats = board.stats();
+        assert_eq!(stats.pieces_placed, 4);
+        assert_eq!(stats.tspins, 1);
+        assert_eq!((stats.singles, stats.doubles), (1, 1));
+        assert_eq!((stats.triples, stats.tetrises), (0, 0));
+        assert_eq!(stats.max_combo, 2);
+        assert_eq!(stats.combo(), 0);
+        assert_eq!(stats.perfect_clears, 1);
+
+        board.restart(2);
+        assert_eq!(board.stats(), GameStats::default());
+    }
+
+    #[test]
+    fn test_finesse_faults() {
+        let mut board = test_board();
+        let dt = 1.0 / 60.0;
+
+        // Each piece ends up one column left of spawn
+        let pieces = [
+            vec![PlayerInput::L, PlayerInput::R, PlayerInput::L],
+            vec![PlayerInput::L],
+        ];
+        for (placed, inputs) in (1..).zip(pieces) {
+            board.update(dt, &[]);
+            for input in inputs.into_iter().chain([PlayerInput::HardDrop]) {
+                board.update(dt, &[input]);
+            }
+            board.update(0.5, &[]);
+
+            // Only the roundabout first piece is a fault
+            assert_eq!(board.stats().pieces_placed, placed);
+            assert_eq!(board.stats().finesse_faults, 1);
+        }
+    }
+
+    #[test]
+    fn test_glow_only_on_active_piece() {
+        let mut board = test_board();
+        board.board.add_garbage(&[vec![
+            true, false, true, true, true, true, true, true, true, true,
+        ]]);
+        board.update(1.0 / 60.0, &[]);
+
+        // Off by default
+        assert!(board.glow_cells().is_empty());
+
+        board.set_render_options(RenderOptions {
+            piece_glow: PieceGlow {
+                enabled: true,
+                ..PieceGlow::default()
+            },
+            ..RenderOptions::default()
+        });
+        let active: Vec<Vec2> = piece_cells(&board)
+            .into_iter()
+            .map(|(x, y)| BoardPosition { x, y }.to_screen(&board))
+            .collect();
+        assert_eq!(board.glow_cells(), active);
+
+        // None of it lands on the committed row
+        let bottom = BoardPosition { x: 0, y: 0 }.to_screen(&board).y;
+        assert!(board.glow_cells().iter().all(|center| center.y > bottom));
+    }
+
+    #[test]
+    fn test_col_height_bars() {
+        let mut board = test_board();
+        let mut row = vec![true; 10];
+        row[4] = false;
+        board
+            .board
+            .add_garbage(&[row.clone(), vec![false; 10], row]);
+
+        let bars = board.col_height_bars();
+        assert_eq!(bars.len(), 10);
+        let floor = -board.screen_height / 2.0;
+        for (x, (center, size)) in bars.into_iter().enumerate() {
+            let height = if x == 4 { 0 } else { 3 };
+            assert_eq!(size.y, height as f32 * board.cell_size, "col {}", x);
+            assert_eq!(center.y - size.y / 2.0, floor);
+            let col = BoardPosition {
+                x: x as isize,
+                y: 0,
+            };
+            assert_eq!(center.x, col.to_screen(&board).x);
+        }
+    }
+
+    // Hold left until the piece reaches the wall, hard drop it, and spawn
+    // the next piece with left still held. Returns the new piece and its spawn x.
+    fn spawn_with_left_held(preserve_charge: bool) -> (BoardInstance, isize) {
+        let config = BoardConfig {
+            das: DasConfig {
+                preserve_charge,
+                ..DasConfig::default()
+            },
+            ..test_config()
+        };
+        let mut board = BoardInstance::new("board1", vec2(0.0, 0.0), &config, 1).unwrap();
+        board.update(0.01, &[]);
+        board.update(0.01, &[PlayerInput::LHold]);
+        board.update(0.3, &[]);
+        board.update(0.01, &[PlayerInput::HardDrop]);
+        while board.game_state() != GameState::Ready {
+            board.update(0.01, &[]);
+        }
+
+        board.update(0.01, &[]);
+        let piece = board.active_piece().unwrap();
+        let spawn_x = board.spawn_position(piece.typ).x;
+        (board, spawn_x)
+    }
+
+    #[test]
+    fn test_das_charge_across_pieces() {
+        // Kept charge shifts the new piece at once, and keeps shifting
+        let (mut board, spawn_x) = spawn_with_left_held(true);
+        assert_eq!(board.active_piece().unwrap().position.x, spawn_x - 1);
+        board.update(0.034, &[]);
+        assert_eq!(board.active_piece().unwrap().position.x, spawn_x - 2);
+
+        // Otherwise the new piece sits until DAS charges again
+        let (mut board, spawn_x) = spawn_with_left_held(false);
+        assert_eq!(board.active_piece().unwrap().position.x, spawn_x);
+        board.update(0.1, &[]);
+        assert_eq!(board.active_piece().unwrap().position.x, spawn_x);
+        board.update(0.1, &[]);
+        assert!(board.active_piece().unwrap().position.x < spawn_x);
+    }
+
+    #[test]
+    fn test_irs() {
+        let config = BoardConfig {
+            irs: true,
+            ..test_config()
+        };
+        let mut board = BoardInstance::new("board1", vec2(0.0, 0.0), &config, 1).unwrap();
+        board.rotate_held = true;
+        board.next_piece = PieceType::T;
+        assert!(board.spawn_new_piece());
+        assert_eq!(board.active_piece().unwrap().rot_idx, 1);
+
+        // Everything but the unrotated T's own cells is filled, so no rotation fits
+        let spawn = PieceInstance::new(
+            PieceType::T,
+            board.color,
+            board.spawn_position(PieceType::T),
+        );
+        let cells: Vec<(isize, isize)> = spawn
+            .cells(board.board.pieces())
+            .iter()
+            .map(|&(dx, dy)| (spawn.position.x + dx, spawn.position.y + dy))
+            .collect();
+        let rows: Vec<Vec<bool>> = (0..20)
+            .map(|y| (0..10).map(|x| !cells.contains(&(x, y))).collect())
+            .collect();
+        board.board_mut().add_garbage(&rows);
+
+        board.next_piece = PieceType::T;
+        assert!(board.spawn_new_piece());
+        assert_eq!(board.active_piece().unwrap().rot_idx, 0);
+    }
+
+    #[test]
+    fn test_hold_and_ihs() {
+        let config = BoardConfig {
+            ihs: true,
+            ..test_config()
+        };
+        let mut board = BoardInstance::new("board1", vec2(0.0, 0.0), &config, 1).unwrap();
+        board.update(0.01, &[]);
+        let first = board.active_piece().unwrap().typ;
+        let next = board.next_piece();
+
+        // Holding with nothing held brings in the next piece, once per piece
+        board.update(0.01, &[PlayerInput::Hold]);
+        assert_eq!(board.held_piece(), Some(first));
+        assert_eq!(board.active_piece().unwrap().typ, next);
+        board.update(0.01, &[PlayerInput::Hold]);
+        assert_eq!(board.active_piece().unwrap().typ, next);
+
+        // With hold still down, the next spawn swaps the held piece in at once
+        board.next_piece = PieceType::S;
+        board.update(0.01, &[PlayerInput::HardDrop]);
+        while board.game_state() != GameState::Ready {
+            board.update(0.01, &[]);
+        }
+        board.update(0.01, &[]);
+        assert_eq!(board.active_piece().unwrap().typ, first);
+        assert_eq!(board.held_piece(), Some(PieceType::S));
+    }
+
+    #[test]
+    fn test_timer_progress() {
+        let mut board = test_board();
+        board.update(0.01, &[]);
+        board.update(0.25, &[]);
+        assert!((board.gravity_progress() - 0.5).abs() < 1e-4);
+        assert_eq!(board.lock_progress(), None);
+
+        // Land the piece, then let gravity find it can't fall
+        board.move_while_possible(MoveDirection::Down);
+        board.update(0.25, &[]);
+        assert!(matches!(board.game_state(), GameState::Locking { .. }));
+        assert_eq!(board.lock_progress(), Some(0.0));
+
+        board.update(0.1, &[]);
+        let progress = board.lock_progress().unwrap();
+        assert!((progress - 0.4).abs() < 1e-4);
+        assert!((0.0..=1.0).contains(&board.gravity_progress()));
+    }
+
+    #[test]
+    fn test_long_pause_keeps_gravity() {
+        let mut board = test_board();
+        board.update(1.0 / 60.0, &[]);
+        board.update(0.4, &[]);
+        let y = board.active_piece().unwrap().position.y;
+
+        board.update(1.0 / 60.0, &[PlayerInput::Pause]);
+        board.update(600.0, &[]);
+        board.update(1.0 / 60.0, &[PlayerInput::Pause]);
+
+        // The frame after unpausing carries the whole pause
+        board.update(600.0, &[]);
+        assert_eq!(board.game_state(), GameState::Falling);
+        assert_eq!(board.active_piece().unwrap().position.y, y);
+
+        // and gravity picks up where it left off
+        board.update(0.05, &[]);
+        assert_eq!(board.active_piece().unwrap().position.y, y);
+        board.update(0.05, &[]);
+        assert_eq!(board.active_piece().unwrap().position.y, y - 1);
+    }
+
+    #[test]
+    fn test_force_lock_mid_fall() {
+        let mut board = test_board();
+        let dt = 1.0 / 60.0;
+        board.update(dt, &[]);
+        board.update(0.5, &[]);
+        assert_eq!(board.game_state(), GameState::Falling);
+
+        let cells = piece_cells(&board);
+        assert_eq!(board.force_lock(), Some(LockOutcome::default()));
+
+        // Committed in mid-air, without waiting for the lock delay
+        assert!(board.active_piece().is_none());
+        assert_eq!(board.game_state(), GameState::Ready);
+        for (x, y) in cells {
+            assert!(board.board().is_cell_filled(BoardPosition { x, y }));
+        }
+        assert_eq!(board.score(), 4);
+
+        // The next update spawns the next piece
+        board.update(dt, &[]);
+        assert_eq!(board.game_state(), GameState::Falling);
+        assert!(board.active_piece().is_some());
+    }
+
+    #[test]
+    fn test_gravity_curve() {
+        let point = |level, interval| GravityPoint { level, interval };
+        let curve = [point(1, 0.5), point(5, 0.1), point(10, 0.05)];
+
+        let interval = |level| curve_interval(&curve, level).unwrap();
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-6;
+
+        // Halfway between breakpoints is halfway between their intervals
+        assert!(close(interval(3), 0.3));
+        assert!(close(interval(5), 0.1));
+        assert!(close(interval(7), 0.08));
+
+        // Past either end the interval holds
+        assert_eq!(interval(0), 0.5);
+        assert_eq!(interval(40), 0.05);
+        assert_eq!(curve_interval(&[], 3), None);
+
+        // A board starts on the curve's level 1 interval, listed in any order
+        let config = BoardConfig {
+            gravity_curve: vec![point(10, 0.05), point(1, 0.4)],
+            ..test_config()
+        };
+        let board = BoardInstance::new("board1", vec2(0.0, 0.0), &config, 1).unwrap();
+        assert_eq!(board.timers.gravity.duration(), 0.4);
+        let board = BoardInstance::new("board1", vec2(0.0, 0.0), &test_config(), 1).unwrap();
+        assert_eq!(board.timers.gravity.duration(), 0.5);
+    }
+
+    #[test]
+    fn test_level_lines() {
+        let config = BoardConfig {
+            level_lines: vec![2, 3],
+            ..test_config()
+        };
+        let mut board = BoardInstance::new("board1", vec2(0.0, 0.0), &config, 1).unwrap();
+        board.update(1.0 / 60.0, &[]);
+
+        // Level 2 after 2 lines, level 3 after 3 more, then 3 a level
+        let levels = [1, 2, 2, 2, 3, 3, 3, 4];
+        for level in levels {
+            garbage(&mut board, &[&[0, 1]]);
+            set_active_piece(&mut board, PieceType::O, 0, 0);
+            assert_eq!(board.force_lock().unwrap().rows_cleared, 1);
+            assert_eq!(board.stats().level(), level);
+        }
+
+        // The default takes 10 lines a level
+        let mut board = test_board();
+        board.update(1.0 / 60.0, &[]);
+        for _ in 0..10 {
+            garbage(&mut board, &[&[0, 1]]);
+            set_active_piece(&mut board, PieceType::O, 0, 0);
+            board.force_lock();
+        }
+        assert_eq!(board.stats().level(), 2);
+    }
+
+    #[test]
+    fn test_restore() {
+        for randomizer in [Randomizer::Uniform, Randomizer::Bag] {
+            let config = BoardConfig {
+                randomizer,
+                ..test_config()
+            };
+            let mut board = BoardInstance::new("board1", vec2(0.0, 0.0), &config, 1).unwrap();
+            for tick in 0..300 {
+                let input = (tick % 20 == 0).then_some(PlayerInput::HardDrop);
+                board.update(1.0 / 60.0, input.as_slice());
+            }
+            assert!(board.stats().pieces_placed > 0);
+
+            // Snapshot mid-game, then rebuild on a board seeded otherwise
+            let snapshot = BoardSnapshot::capture(&board);
+            let mut restored = BoardInstance::new("board1", vec2(0.0, 0.0), &config, 99).unwrap();
+            restored.restore(&snapshot);
+            assert_eq!(restored.rng_state(), board.rng_state());
+            assert_eq!(restored.next_piece(), board.next_piece());
+            assert_eq!(restored.bag_remaining(), board.bag_remaining());
+
+            // Both deal the same pieces from there on
+            let deal = |board: &mut BoardInstance| {
+                (0..30).map(|_| board.take_next_piece()).collect::<Vec<_>>()
+            };
+            assert_eq!(deal(&mut restored), deal(&mut board), "{:?}", randomizer);
+        }
+    }
+
+    #[test]
+    fn test_bag_remaining() {
+        let config = BoardConfig {
+            randomizer: Randomizer::Bag,
+            ..test_config()
+        };
+        let mut board = BoardInstance::new("board1", vec2(0.0, 0.0), &config, 1).unwrap();
+        assert_eq!(board.bag_remaining().len(), 6);
+
+        // The first next piece, and two more drawn after it: 3 of 7 dealt
+        let mut dealt = vec![board.next_piece()];
+        for _ in 0..2 {
+            board.take_next_piece();
+            dealt.push(board.next_piece());
+        }
+
+        // What's left is the 4 types not dealt yet
+        let remaining = board.bag_remaining();
+        assert_eq!(remaining.len(), 4);
+        for typ in (0..7).map(PieceType::from_idx) {
+            assert!(dealt.contains(&typ) != remaining.contains(&typ));
+        }
+
+        // Uniform dealing has no bag
+        let board = BoardInstance::new("board1", vec2(0.0, 0.0), &test_config(), 1).unwrap();
+        assert!(board.bag_remaining().is_empty());
+    }
+
+    #[test]
+    fn test_clone_is_independent() {
+        let mut board = BoardInstance::new("board1", vec2(0.0, 0.0), &test_config(), 1).unwrap();
+        board.update(1.0 / 60.0, &[]);
+        let before = board.frame();
+
+        // The clone plays on without touching the source
+        let mut branch = board.clone();
+        for _ in 0..5 {
+            branch.update(1.0 / 60.0, &[PlayerInput::HardDrop]);
+            branch.force_lock();
+            branch.update(1.0 / 60.0, &[]);
+        }
+        assert!(branch.stats().pieces_placed > 0);
+        assert_eq!(board.frame(), before);
+        assert_eq!(board.stats(), GameStats::default());
+
+        // Played the same way, the source deals the same pieces as the clone did
+        for _ in 0..5 {
+            board.update(1.0 / 60.0, &[PlayerInput::HardDrop]);
+            board.force_lock();
+            board.update(1.0 / 60.0, &[]);
+        }
+        assert_eq!(board.frame(), branch.frame());
+        assert_eq!(board.next_piece(), branch.next_piece());
+    }
+
+    #[test]
+    fn test_drop_trail() {
+        let mut board = BoardInstance::new("board1", vec2(0.0, 0.0), &test_config(), 1).unwrap();
+        board.set_render_options(RenderOptions {
+            drop_trail: DropTrail {
+                enabled: true,
+                ..DropTrail::default()
+            },
+            ..RenderOptions::default()
+        });
+        board.update(0.01, &[]);
+        let from = board.active_piece().unwrap().position;
+
+        // The trail fills the columns the piece fell down, above where it landed
+        board.update(0.01, &[PlayerInput::HardDrop]);
+        let piece = board.active_piece().unwrap();
+        assert!(!board.trail.is_empty());
+        for cell in &board.trail {
+            assert!(cell.pos.y > piece.position.y && cell.pos.y < from.y + 4);
+            assert!(cell.pos.x >= piece.position.x && cell.pos.x < piece.position.x + 4);
+        }
+
+        // and fades out once it has lived its lifetime
+        board.update(0.1, &[]);
+        assert!(!board.trail.is_empty());
+        board.update(0.1, &[]);
+        assert!(board.trail.is_empty());
+    }
+
+    #[test]
+    fn test_lock_above_top() {
+        let mut board = BoardInstance::new("board1", vec2(0.0, 0.0), &test_config(), 1).unwrap();
+        board.update(0.01, &[]);
+
+        // A vertical I with its top two cells above the board, locking now
+        set_active_piece(&mut board, PieceType::I, 1, 0);
+        board.active_piece.as_mut().unwrap().position.y = board.board.height - 2;
+        board.game_state = GameState::Locking {
+            now: true,
+            hard_drop: false,
+        };
+        board.update(0.01, &[]);
+
+        // The lost cells end the game rather than vanishing
+        assert!(board.board().topped_out());
+        assert_eq!(board.game_state(), GameState::GameOver);
+        assert_eq!(board.board().filled_count(), 2);
+
+        // until the next game
+        board.restart(1);
+        assert!(!board.board().topped_out());
+    }
+
+    #[test]
+    fn test_new_well() {
+        let mut board = BoardInstance::new("board1", vec2(0.0, 0.0), &test_config(), 1).unwrap();
+        board.update(0.01, &[]);
+        garbage(
+            &mut board,
+            &[&[0, 1, 2, 3, 9], &[0, 1, 2, 3, 9], &[9], &[9]],
+        );
+
+        // The first lock with the well in place reports it
+        set_active_piece(&mut board, PieceType::O, 0, 0);
+        board.force_lock();
+        assert_eq!(board.take_new_well(), Some((9, 4)));
+        assert_eq!(board.take_new_well(), None);
+
+        // Only once while it stays open
+        set_active_piece(&mut board, PieceType::O, 0, 2);
+        board.force_lock();
+        assert_eq!(board.take_new_well(), None);
+    }
+
+    #[test]
+    fn test_clear_burst() {
+        let mut board = BoardInstance::new("board1", vec2(0.0, 0.0), &test_config(), 1).unwrap();
+        board.set_render_options(RenderOptions {
+            clear_burst: ClearBurst {
+                enabled: true,
+                ..ClearBurst::default()
+            },
+            age_heatmap: AgeHeatmap {
+                enabled: true,
+                ..AgeHeatmap::default()
+            },
+            ..RenderOptions::default()
+        });
+        board.update(0.01, &[]);
+
+        // Two rows of garbage old enough to be tinted, finished by a fresh O
+        garbage(&mut board, &[&[0, 1], &[0, 1]]);
+        for _ in 0..100 {
+            board.board.tick();
+        }
+        let expected: Vec<(Vec2, Rgba)> = (0..2)
+            .flat_map(|y| (0..10).map(move |x| BoardPosition { x, y }))
+            .map(|pos| {
+                let color = match board.board.is_cell_filled(pos) {
+                    true => board.aged_color(pos),
+                    false => board.color,
+                };
+                (pos.to_screen(&board), color)
+            })
+            .collect();
+        assert_ne!(expected[0].1, expected[9].1);
+
+        set_active_piece(&mut board, PieceType::O, 0, 0);
+        assert_eq!(board.force_lock().unwrap().rows_cleared, 2);
+        let spawned: Vec<(Vec2, Rgba)> = board
+            .fragments
+            .iter()
+            .map(|fragment| (fragment.pos, fragment.color))
+            .collect();
+        assert_eq!(spawned, expected);
+
+        // They fly off and fade out, leaving the clear itself alone
+        board.update(0.5, &[]);
+        assert!(board
+            .fragments
+            .iter()
+            .all(|fragment| fragment.pos.y < expected[0].0.y));
+        board.update(0.5, &[]);
+        assert!(board.fragments.is_empty());
+        assert_eq!(board.board.filled_count(), 0);
+    }
+
+    #[test]
+    fn test_instant_gravity() {
+        let config = BoardConfig {
+            gravity: GravityMode::Instant,
+            ..test_config()
+        };
+        let mut board = BoardInstance::new("board1", vec2(0.0, 0.0), &config, 1).unwrap();
+        board.update(0.01, &[]);
+        assert_eq!(board.game_state(), GameState::Falling);
+
+        // The first falling frame takes the piece to the floor and starts the lock delay
+        board.update(0.0, &[]);
+        let piece = board.active_piece().unwrap();
+        assert!(BoardInstance::is_piece_at_bottom(
+            piece,
+            board.board.pieces()
+        ));
+        assert!(matches!(board.game_state(), GameState::Locking { .. }));
+    }
+
+    #[test]
+    fn test_degenerate_size() {
+        let config = BoardConfig {
+            width: 0,
+            ..test_config()
+        };
+        let board = BoardInstance::new("board1", vec2(0.0, 0.0), &config, 1);
+        assert_eq!(
+            board.err(),
+            Some(SizeError {
+                width: 0,
+                height: 20
+            })
+        );
+
+        // A 1x1 board has no room for a piece, so the game is over at once
+        let config = BoardConfig {
+            width: 1,
+            height: 1,
+            ..test_config()
+        };
+        let mut board = BoardInstance::new("board1", vec2(0.0, 0.0), &config, 1).unwrap();
+        board.update(0.01, &[]);
+        assert_eq!(board.game_state(), GameState::GameOver);
+        board.update(0.01, &[]);
+    }
+
+    #[test]
+    fn test_landing_row() {
+        let mut board = test_board();
+        board.set_render_options(RenderOptions {
+            landing_guide: LandingGuide::Row,
+            ..RenderOptions::default()
+        });
+        board.update(0.01, &[]);
+        garbage(&mut board, &[&[0, 1, 2], &[0, 1, 2, 3, 4]]);
+
+        // An S over the uneven stack lands where a hard drop puts it
+        set_active_piece(&mut board, PieceType::S, 0, 2);
+        board.active_piece.as_mut().unwrap().position.y = 15;
+        board.update(0.0, &[]);
+        let landing = board.landing_row();
+
+        board.update(0.0, &[PlayerInput::HardDrop]);
+        let piece = board.active_piece().unwrap();
+        let bottom = piece
+            .cells(board.board.pieces())
+            .iter()
+            .map(|&(_, dy)| dy)
+            .min()
+            .unwrap();
+        assert_eq!(landing, Some(piece.position.y + bottom));
+
+        // and goes with the guide turned off
+        board.set_render_options(RenderOptions {
+            landing_guide: LandingGuide::Off,
+            ..RenderOptions::default()
+        });
+        board.update(0.0, &[]);
+        assert_eq!(board.landing_row(), None);
+    }
+
+    #[test]
+    fn test_anchor() {
+        // 10x20 cells of 30 points, at (100, 50)
+        let mut board = BoardInstance::new("board1", vec2(100.0, 50.0), &test_config(), 1).unwrap();
+        let corners = [BoardPosition { x: 0, y: 0 }, BoardPosition { x: 9, y: 19 }];
+        let screen = |board: &BoardInstance| corners.map(|pos| pos.to_screen(board));
+
+        let centered = screen(&board);
+        assert_eq!(centered, [vec2(-35.0, -235.0), vec2(235.0, 335.0)]);
+
+        // Top left: the top left cell's corner is at the location
+        board.set_render_options(RenderOptions {
+            anchor: Anchor::TopLeft,
+            ..RenderOptions::default()
+        });
+        assert_eq!(screen(&board), [vec2(115.0, -535.0), vec2(385.0, 35.0)]);
+
+        // Bottom left: the bottom left cell's corner is
+        board.set_render_options(RenderOptions {
+            anchor: Anchor::BottomLeft,
+            ..RenderOptions::default()
+        });
+        assert_eq!(screen(&board), [vec2(115.0, 65.0), vec2(385.0, 635.0)]);
+
+        // Screen points map back to the same cells, and the pixel bounds move along
+        for pos in corners {
+            let point = pos.to_screen(&board) + vec2(10.0, -10.0);
+            assert_eq!(BoardPosition::from_screen(point, &board), Some(pos));
+        }
+        assert_eq!(BoardPosition::from_screen(vec2(99.0, 65.0), &board), None);
+        let bounds = board.pixel_bounds([1000, 2000]);
+        assert_eq!((bounds.x, bounds.y), (600, 350));
+        assert_eq!((bounds.width, bounds.height), (300, 600));
+    }
+
+    #[test]
+    fn test_draw_local() {
+        let local_cells = |location, anchor| {
+            let mut board = BoardInstance::new("board1", location, &test_config(), 1).unwrap();
+            board.set_render_options(RenderOptions {
+                anchor,
+                ..RenderOptions::default()
+            });
+            board.update(1.0 / 60.0, &[]);
+            garbage(&mut board, &[&[0, 1], &[3]]);
+            let mesh = board.cell_mesh(board.game_state(), f32::MIN, board.color);
+            let offset = board.local_offset();
+            mesh.centers()
+                .map(|center| center + offset)
+                .collect::<Vec<Vec2>>()
+        };
+
+        // The same cells wherever the board is and however it's anchored
+        let home = local_cells(vec2(0.0, 0.0), Anchor::Center);
+        assert_eq!(home, local_cells(vec2(250.0, -120.0), Anchor::Center));
+        assert_eq!(home, local_cells(vec2(-40.0, 300.0), Anchor::TopLeft));
+
+        // Centered on the origin: the first cell, column 2 of row 0, is 2.5
+        // cells left of center and 9.5 down
+        assert_eq!(home[0], vec2(-75.0, -285.0));
+    }
+
+    #[test]
+    fn test_flip_y() {
+        let mut board = test_board();
+        let (bottom, top) = (BoardPosition { x: 3, y: 0 }, BoardPosition { x: 3, y: 19 });
+        let (row_0, top_row) = (bottom.to_screen(&board), top.to_screen(&board));
+        assert!(row_0.y < top_row.y);
+
+        // Flipped, row 0 and the top row trade places on screen, and only there
+        board.set_render_options(RenderOptions {
+            flip_y: true,
+            ..RenderOptions::default()
+        });
+        assert_eq!(bottom.to_screen(&board), top_row);
+        assert_eq!(top.to_screen(&board), row_0);
+        assert_eq!(BoardPosition::from_screen(row_0, &board), Some(top));
+
+        // Column height bars hang from the top
+        board.board_mut().add_garbage(&[vec![true; 10]]);
+        let (bar, size) = board.col_height_bars()[0];
+        assert_eq!(
+            bar.y + size.y / 2.0,
+            board.center().y + board.screen_height / 2.0
+        );
+    }
+
+    #[test]
+    fn test_spawn_preview() {
+        let mut board = test_board();
+        board.set_render_options(RenderOptions {
+            spawn_preview: SpawnPreview {
+                enabled: true,
+                ..SpawnPreview::default()
+            },
+            ..RenderOptions::default()
+        });
+        board.update(0.01, &[]);
+        let preview = board.spawn_preview_cells();
+        assert_eq!(preview.len(), 4);
+
+        // The next piece spawns into exactly the previewed cells
+        board.update(0.01, &[PlayerInput::HardDrop]);
+        board.force_lock();
+        board.update(0.01, &[]);
+        let piece = board.active_piece().unwrap();
+        let spawned: Vec<BoardPosition> = piece
+            .cells(board.board.pieces())
+            .iter()
+            .map(|&(dx, dy)| BoardPosition {
+                x: piece.position.x + dx,
+                y: piece.position.y + dy,
+            })
+            .collect();
+        assert_eq!(spawned, preview);
+
+        // Hidden outside of Falling
+        board.update(0.01, &[PlayerInput::HardDrop]);
+        assert!(board.spawn_preview_cells().is_empty());
+    }
+
+    #[test]
+    fn test_spawn_preview_spin() {
+        let mut board = test_board();
+        board.set_render_options(RenderOptions {
+            spawn_preview: SpawnPreview {
+                enabled: true,
+                spin: true,
+                spin_speed: 0.25,
+                ..SpawnPreview::default()
+            },
+            ..RenderOptions::default()
+        });
+        board.update(0.01, &[]);
+        let preview = board.spawn_preview_cells();
+
+        // A quarter turn a second: an eighth in half a second, and the cells stay put
+        let start = board.preview_angle();
+        for _ in 0..50 {
+            board.update(0.01, &[]);
+            assert_eq!(board.spawn_preview_cells(), preview);
+        }
+        let turned = board.preview_angle() - start;
+        assert!((turned - TAU / 8.0).abs() < 1e-4);
+
+        // The piece spawns unrotated however far the preview has spun
+        board.update(0.01, &[PlayerInput::HardDrop]);
+        board.force_lock();
+        board.update(0.01, &[]);
+        assert_eq!(board.active_piece().unwrap().rot_idx, 0);
+
+        // Turned off, it stops and straightens up
+        board.set_render_options(RenderOptions {
+            spawn_preview: SpawnPreview {
+                enabled: true,
+                ..SpawnPreview::default()
+            },
+            ..RenderOptions::default()
+        });
+        board.update(0.5, &[]);
+        assert_eq!(board.preview_angle(), 0.0);
+    }
+
+    #[test]
+    fn test_drop_assist() {
+        // A 2-wide gap the O just misses, one column to the left
+        let near_miss = |assist: bool| {
+            let config = BoardConfig {
+                assist,
+                ..test_config()
+            };
+            let mut board = BoardInstance::new("board1", vec2(0.0, 0.0), &config, 1).unwrap();
+            board.update(0.01, &[]);
+            garbage(&mut board, &[&[4, 5, 9]]);
+            set_active_piece(&mut board, PieceType::O, 0, 3);
+            board.active_piece.as_mut().unwrap().position.y = 15;
+            board.update(0.0, &[PlayerInput::HardDrop]);
+            board.force_lock();
+            board.board.hole_count()
+        };
+
+        // Off, the O hangs over the gap; on, it drops into it
+        assert_eq!(near_miss(false), 1);
+        assert_eq!(near_miss(true), 0);
+    }
+
+    #[test]
+    fn test_inputs_in_one_frame() {
+        let mut board = test_board();
+        board.update(1.0 / 60.0, &[]);
+
+        // A shift and a spin in the same frame both apply
+        set_active_piece(&mut board, PieceType::T, 0, 4);
+        board.active_piece.as_mut().unwrap().position.y = 15;
+        board.update(1.0 / 60.0, &[PlayerInput::Rotate, PlayerInput::L]);
+        let piece = board.active_piece.as_ref().unwrap();
+        assert_eq!((piece.position.x, piece.rot_idx), (3, 1));
+
+        // The shift goes first, so the drop lands on the ledge it moved over
+        garbage(&mut board, &[&[0, 1, 2, 3, 4], &[0, 1, 2, 3, 4]]);
+        set_active_piece(&mut board, PieceType::T, 0, 3);
+        board.active_piece.as_mut().unwrap().position.y = 15;
+        board.update(1.0 / 60.0, &[PlayerInput::HardDrop, PlayerInput::R]);
+        let piece = board.active_piece.as_ref().unwrap();
+        assert_eq!(piece.position.x, 4);
+        assert!(piece.position.y >= 2);
+    }
+
+    #[test]
+    fn test_queued_inputs() {
+        let mut board = test_board();
+        board.update(1.0 / 60.0, &[]);
+
+        // Three taps between frames all shift the piece
+        set_active_piece(&mut board, PieceType::T, 0, 4);
+        board.active_piece.as_mut().unwrap().position.y = 15;
+        for _ in 0..3 {
+            board.queue_input(PlayerInput::L);
+        }
+        for _ in 0..3 {
+            board.update(1.0 / 60.0, &[]);
+        }
+        assert_eq!(board.active_piece.as_ref().unwrap().position.x, 1);
+        assert_eq!(board.queued_inputs(), 0);
+
+        // Queued taps apply in order: an I flat against the wall stands up,
+        // then the shifts it made room for go through
+        set_active_piece(&mut board, PieceType::I, 0, 0);
+        board.active_piece.as_mut().unwrap().position.y = 15;
+        for input in [PlayerInput::Rotate, PlayerInput::L, PlayerInput::L] {
+            board.queue_input(input);
+        }
+        board.update(1.0 / 60.0, &[]);
+        let piece = board.active_piece.as_ref().unwrap();
+        assert_eq!(piece.rot_idx, 1);
+        assert!(piece
+            .board_cells(board.board.pieces())
+            .iter()
+            .all(|cell| cell.x == 0));
+
+        // A flood is capped, and drains a few a tick
+        for _ in 0..100 {
+            board.queue_input(PlayerInput::RotateRelease);
+        }
+        assert_eq!(board.queued_inputs(), INPUT_QUEUE_CAPACITY);
+        board.update(1.0 / 60.0, &[]);
+        assert_eq!(
+            board.queued_inputs(),
+            INPUT_QUEUE_CAPACITY - QUEUED_INPUTS_PER_TICK
+        );
+    }
+
+    #[test]
+    fn test_lock_flash() {
+        let mut board = test_board();
+        let flash = LockFlash {
+            enabled: true,
+            pulses: 1.0,
+            brightness: 0.8,
+        };
+        board.set_render_options(RenderOptions {
+            lock_flash: flash,
+            ..RenderOptions::default()
+        });
+        board.update(0.01, &[]);
+        board.update(0.25, &[]);
+        assert_eq!(board.lock_flash(), 0.0);
+
+        // Landed, 40% into the lock delay
+        board.move_while_possible(MoveDirection::Down);
+        board.update(0.25, &[]);
+        board.update(0.1, &[]);
+        let progress = board.lock_progress().unwrap();
+        let pulse = 0.5 - 0.5 * (TAU * progress).cos();
+        assert!((board.lock_flash() - 0.8 * progress * pulse).abs() < 1e-5);
+        assert!(board.lock_flash() > 0.0);
+
+        // Only drawing reads it
+        board.set_render_options(RenderOptions::default());
+        assert_eq!(board.lock_flash(), 0.0);
+        assert_eq!(board.lock_progress(), Some(progress));
+    }
+
+    #[test]
+    fn test_lock_delay_by_row() {
+        let config = BoardConfig {
+            lock_delay_floor: Some(0.1),
+            ..test_config()
+        };
+        let mut board = BoardInstance::new("board1", vec2(0.0, 0.0), &config, 1).unwrap();
+        board.update(1.0 / 60.0, &[]);
+        assert!((board.lock_delay_at(0) - 0.1).abs() < 1e-5);
+        assert!((board.lock_delay_at(19) - 0.25).abs() < 1e-5);
+
+        // Landing on the floor, then on a stack ten rows up
+        let landed_delay = |board: &mut BoardInstance| {
+            set_active_piece(board, PieceType::O, 0, 4);
+            board.active_piece.as_mut().unwrap().position.y = 15;
+            board.update(1.0 / 60.0, &[PlayerInput::HardDrop]);
+            assert!(matches!(board.game_state(), GameState::Locking { .. }));
+            board.timers.lock.duration()
+        };
+        let floor = landed_delay(&mut board);
+        garbage(&mut board, &[&[0][..]; 10]);
+        let high = landed_delay(&mut board);
+        assert!(floor < high);
+        assert!(high < 0.25);
+    }
+
+    #[test]
+    fn test_visible_cells() {
+        let mut board = test_board();
+        board.update(1.0 / 60.0, &[]);
+
+        // A T with its flat side poking out of the top
+        set_active_piece(&mut board, PieceType::T, 0, 4);
+        board.active_piece.as_mut().unwrap().position.y = 19;
+        let piece = board.active_piece.clone().unwrap();
+        assert_eq!(
+            board.visible_cells(&piece),
+            vec![BoardPosition { x: 5, y: 19 }]
+        );
+
+        board.set_render_options(RenderOptions {
+            piece_glow: PieceGlow {
+                enabled: true,
+                ..PieceGlow::default()
+            },
+            ..RenderOptions::default()
+        });
+        assert_eq!(board.glow_cells().len(), 1);
+
+        // Entirely above the board, nothing to draw
+        board.active_piece.as_mut().unwrap().position.y = 25;
+        let piece = board.active_piece.clone().unwrap();
+        assert!(board.visible_cells(&piece).is_empty());
+    }
+
+    #[test]
+    fn test_spawn_jitter() {
+        // Frames each board waits for its first three pieces
+        let spawn_frames = |spawn_jitter: f32, seed: u64| {
+            let config = BoardConfig {
+                spawn_jitter,
+                ..test_config()
+            };
+            let mut board = BoardInstance::new("board1", vec2(0.0, 0.0), &config, seed).unwrap();
+            let mut frames = Vec::new();
+            for _ in 0..3 {
+                let mut waited = 0;
+                while board.active_piece.is_none() {
+                    board.update(1.0 / 60.0, &[]);
+                    waited += 1;
+                }
+                frames.push(waited);
+                board.move_while_possible(MoveDirection::Down);
+                board.force_lock();
+            }
+            frames
+        };
+
+        assert_eq!(spawn_frames(0.0, 1), vec![1, 1, 1]);
+        assert_eq!(spawn_frames(0.0, 1), spawn_frames(0.0, 2));
+        assert_ne!(spawn_frames(0.5, 1), spawn_frames(0.5, 2));
+        assert_eq!(spawn_frames(0.5, 1), spawn_frames(0.5, 1));
+    }
+
+    #[test]
+    fn test_spawn_delay() {
+        let config = BoardConfig {
+            spawn_delay: 0.25,
+            ..test_config()
+        };
+        let mut board = BoardInstance::new("board1", vec2(0.0, 0.0), &config, 1).unwrap();
+
+        // Every piece waits out the delay, a quarter second at 60 fps
+        for _ in 0..3 {
+            let mut waited = 0;
+            while board.active_piece.is_none() {
+                board.update(1.0 / 60.0, &[]);
+                waited += 1;
+            }
+            assert!((15..=16).contains(&waited), "waited {} frames", waited);
+            board.move_while_possible(MoveDirection::Down);
+            board.force_lock();
+        }
+    }
+
+    #[test]
+    fn test_queue_piece() {
+        let config = BoardConfig {
+            randomizer: Randomizer::Bag,
+            ..test_config()
+        };
+        let spawns = |board: &mut BoardInstance, count: usize| {
+            let mut types = Vec::new();
+            for _ in 0..count {
+                board.update(1.0 / 60.0, &[]);
+                types.push(board.active_piece.as_ref().unwrap().typ);
+                board.move_while_possible(MoveDirection::Down);
+                board.force_lock();
+            }
+            types
+        };
+        let mut plain = BoardInstance::new("board1", vec2(0.0, 0.0), &config, 3).unwrap();
+        let mut practice = BoardInstance::new("board1", vec2(0.0, 0.0), &config, 3).unwrap();
+
+        // The asked-for T comes first, then the bag picks up where it was
+        practice.queue_piece(PieceType::T);
+        assert_eq!(practice.next_piece(), PieceType::T);
+        let expected = spawns(&mut plain, 3);
+        let practiced = spawns(&mut practice, 4);
+        assert_eq!(practiced[0], PieceType::T);
+        assert_eq!(practiced[1..], expected[..]);
+    }
+
+    #[test]
+    fn test_garbage_rise() {
+        let mut board = test_board();
+        board.set_render_options(RenderOptions {
+            garbage_rise: GarbageRise {
+                enabled: true,
+                duration: 0.2,
+            },
+            ..RenderOptions::default()
+        });
+        board.update(1.0 / 60.0, &[]);
+        let piece_at = board.active_piece().unwrap().position;
+
+        // The rows are on the board at once, while the drawing lags behind
+        board.receive_garbage(2, 3);
+        assert_eq!(board.rise_offset(), 2.0);
+        assert!(board.board().is_cell_filled(BoardPosition { x: 0, y: 1 }));
+        assert!(!board.board().is_cell_filled(BoardPosition { x: 3, y: 0 }));
+        assert_eq!(board.board().col_score(0), Some(2));
+        assert_eq!(board.active_piece().unwrap().position, piece_at);
+
+        board.update(0.1, &[]);
+        assert!((board.rise_offset() - 1.0).abs() < 1e-4);
+        board.update(0.1, &[]);
+        assert_eq!(board.rise_offset(), 0.0);
+    }
+
+    #[test]
+    fn test_custom_piece_set() {
+        // A T that's a 2x2 square, in red
+        let file = PieceSetFile {
+            piece: vec![PieceDefFile {
+                typ: "t".to_owned(),
+                rotations: vec![vec![[0, 0], [1, 0], [0, 1], [1, 1]]],
+                color: Some([1.0, 0.0, 0.0]),
+            }],
+        };
+        let config = BoardConfig {
+            pieces: Rc::new(file.piece_set().unwrap()),
+            ..test_config()
+        };
+
+        let mut board = BoardInstance::new("board1", vec2(0.0, 0.0), &config, 1).unwrap();
+        board.queue_piece(PieceType::T);
+        board.update(1.0 / 60.0, &[]);
+        let piece = board.active_piece().unwrap().clone();
+        let pieces = board.board.pieces();
+        let mut cells = *piece.cells(pieces);
+        cells.sort_unstable();
+        assert_eq!(cells, [(0, 0), (0, 1), (1, 0), (1, 1)]);
+        assert_eq!(pieces.rotation_count(PieceType::T), 4);
+        assert_eq!(pieces.get_rotation(PieceType::T, 3), piece.cells(pieces));
+        assert_eq!((piece.color.red, piece.color.green), (1.0, 0.0));
+        assert_eq!(pieces.color(PieceType::I), None);
+
+        // The set goes with the board: its clones keep it, other boards don't see it
+        assert_eq!(board.clone().board.pieces(), pieces);
+        let standard = test_board();
+        assert_eq!(*standard.board.pieces(), PieceSet::default());
+        assert_ne!(standard.board.pieces(), pieces);
+
+        // Rotations that don't each have four cells are refused
+        let lopsided = PieceSetFile {
+            piece: vec![PieceDefFile {
+                typ: "L".to_owned(),
+                rotations: vec![vec![[0, 0], [1, 0], [2, 0]]],
+                color: None,
+            }],
+        };
+        assert!(matches!(
+            lopsided.piece_set(),
+            Err(PieceSetError::Cells { count: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn test_gravity_g() {
+        let board_at = |gravity_interval: f32, gravity: GravityMode| {
+            let config = BoardConfig {
+                gravity_interval,
+                gravity,
+                ..test_config()
+            };
+            BoardInstance::new("board1", vec2(0.0, 0.0), &config, 1).unwrap()
+        };
+
+        let slow = board_at(0.5, GravityMode::Naive).gravity_g();
+        let fast = board_at(0.25, GravityMode::Fractional).gravity_g();
+        assert!((slow - 1.0 / 30.0).abs() < 1e-6);
+        assert!((fast - 2.0 * slow).abs() < 1e-6);
+        assert_eq!(board_at(0.5, GravityMode::Instant).gravity_g(), 20.0);
+    }
+
+    #[test]
+    fn test_instant_lock() {
+        let land = |lock_mode| {
+            let mut board = test_board_with_lock_mode(lock_mode);
+            board.update(1.0 / 60.0, &[]);
+            board.move_while_possible(MoveDirection::Down);
+            assert_eq!(board.game_state(), GameState::Falling);
+
+            // The gravity step that finds the piece can't fall
+            board.update(0.5, &[]);
+            board
+        };
+
+        // Committed on the tick it landed, with the next piece on its way
+        let board = land(LockMode::Instant);
+        assert_eq!(board.game_state(), GameState::Ready);
+        assert!(board.active_piece().is_none());
+        assert_eq!(board.lock_delay_at(0), 0.0);
+
+        // A hard drop commits at once too
+        let mut board = test_board_with_lock_mode(LockMode::Instant);
+        board.update(1.0 / 60.0, &[]);
+        board.update(1.0 / 60.0, &[PlayerInput::HardDrop]);
+        assert_eq!(board.game_state(), GameState::Ready);
+
+        // With a lock delay it waits
+        let board = land(LockMode::StepReset);
+        assert!(matches!(board.game_state(), GameState::Locking { .. }));
+    }
+
+    #[test]
+    fn test_board_full() {
+        let config = BoardConfig {
+            full_ratio: Some(0.5),
+            ..test_config()
+        };
+        let mut board = BoardInstance::new("board1", vec2(0.0, 0.0), &config, 1).unwrap();
+        let fill = |board: &mut BoardInstance, rows: usize| {
+            garbage(board, &[&[0][..]; 20][..rows]);
+            board.update(1.0 / 60.0, &[]);
+        };
+
+        // 11 rows of 9 cells is just under half, 12 is over
+        fill(&mut board, 11);
+        assert!(!board.take_board_full());
+        fill(&mut board, 12);
+        assert!(board.take_board_full());
+        assert!(!board.take_board_full());
+
+        // Staying full, and dipping under the ratio but not past the hysteresis
+        board.update(1.0 / 60.0, &[]);
+        fill(&mut board, 10);
+        fill(&mut board, 12);
+        assert!(!board.take_board_full());
+
+        // Drained well below, then filled again
+        fill(&mut board, 0);
+        fill(&mut board, 12);
+        assert!(board.take_board_full());
+    }
+
+    #[test]
+    fn test_scoring_table() {
+        // A vertical I into a well four rows deep
+        let tetris = |scoring: ScoringConfig| {
+            let config = BoardConfig {
+                scoring,
+                ..test_config()
+            };
+            let mut board = BoardInstance::new("board1", vec2(0.0, 0.0), &config, 1).unwrap();
+            board.update(1.0 / 60.0, &[]);
+            garbage(&mut board, &[&[0], &[0], &[0], &[0]]);
+            set_active_piece(&mut board, PieceType::I, 1, -2);
+            let before = board.score();
+            assert_eq!(board.force_lock().unwrap().rows_cleared, 4);
+            board.score() - before
+        };
+
+        let guideline = tetris(ScoringConfig::default());
+        let custom = tetris(ScoringConfig {
+            tetris: 1200,
+            ..ScoringConfig::default()
+        });
+        assert_eq!(custom - guideline, 400);
+    }
+
+    #[test]
+    fn test_color_drift() {
+        let mut board = test_board();
+        board.update(1.0 / 60.0, &[]);
+        let base = board.drift_color();
+
+        // Off, the color holds
+        for _ in 0..60 {
+            board.update(1.0 / 60.0, &[]);
+        }
+        assert_eq!(board.drift_color(), base);
+
+        board.set_render_options(RenderOptions {
+            color_drift: ColorDrift {
+                enabled: true,
+                speed: 90.0,
+                cells: false,
+            },
+            ..RenderOptions::default()
+        });
+        let mut last = board.drift_color();
+        for _ in 0..8 {
+            for _ in 0..30 {
+                board.update(1.0 / 60.0, &[]);
+            }
+            let color = board.drift_color();
+            assert_ne!(color, last);
+            for channel in [color.red, color.green, color.blue, color.alpha] {
+                assert!((0.0..=1.0).contains(&channel), "{:?}", color);
+            }
+            assert!((color.alpha - base.alpha).abs() < 1e-4);
+            last = color;
+        }
+
+        // Boards with other seeds start from other hues
+        assert_ne!(drift_start(1), drift_start(2));
+    }
+
+    #[test]
+    fn test_tspin_mini() {
+        let spin = |holes: &[&[isize]], rot_idx, x, y| {
+            let mut board = test_board();
+            board.update(1.0 / 60.0, &[]);
+            garbage(&mut board, holes);
+            set_active_piece(&mut board, PieceType::T, rot_idx, x);
+            board.active_piece.as_mut().unwrap().position.y = y;
+            board.update(1.0 / 60.0, &[PlayerInput::Rotate]);
+            let lock = board.force_lock().unwrap();
+            (lock.tspin, lock.tspin_mini, board.last_kick)
+        };
+
+        // A slot under an overhang, both corners below the point filled: full
+        let slot: &[&[isize]] = &[&[4, 9], &[3, 4, 5, 9], &[0, 1, 2, 4, 5, 6, 7, 8, 9]];
+        assert_eq!(spin(slot, 3, 3, 0), (true, false, (0, 0)));
+
+        // Turned to point at the side with an open corner: mini
+        assert_eq!(spin(slot, 0, 3, 0), (true, true, (0, 0)));
+
+        // The same three corners reached by the long kick down into the slot: full
+        let deep: &[&[isize]] = &[
+            &[4, 9],
+            &[4, 5, 9],
+            &[4, 5, 9],
+            &[4, 5, 6, 9],
+            &[0, 1, 2, 3, 6, 7, 8, 9],
+        ];
+        assert_eq!(spin(deep, 0, 4, 2), (true, false, (-1, -2)));
+    }
+
+    #[test]
+    fn test_resize_respawns_cut_piece() {
+        let mut board = test_board();
+        board.update(1.0 / 60.0, &[]);
+
+        // A flat I on the right is cut by the new wall, and starts over at the top
+        set_active_piece(&mut board, PieceType::I, 0, 6);
+        board.active_piece.as_mut().unwrap().position.y = 5;
+        board.resize(8, 20).unwrap();
+        let piece = board.active_piece().unwrap();
+        assert_eq!(piece.position, board.spawn_position(PieceType::I));
+        assert!(piece
+            .board_cells(board.board.pieces())
+            .iter()
+            .all(|pos| pos.x < 8));
+        assert_eq!(board.game_state(), GameState::Falling);
+
+        // With the stack up to the new top, there's nowhere to put it
+        let hole: &[isize] = &[0];
+        garbage(&mut board, &[hole; 14]);
+        set_active_piece(&mut board, PieceType::I, 1, 0);
+        board.active_piece.as_mut().unwrap().position.y = 12;
+        board.resize(8, 14).unwrap();
+        assert_eq!(board.game_state(), GameState::GameOver);
+    }
+
+    #[test]
+    fn test_rotation_turn() {
+        let mut board = test_board();
+        board.update(0.01, &[]);
+        let spawned = board.active_piece().unwrap().position;
+        board.active_piece = Some(PieceInstance::new(PieceType::T, board.color, spawned));
+
+        // A rotation starts a quarter turn back and turns into place
+        board.update(0.01, &[PlayerInput::Rotate]);
+        assert_eq!(board.active_piece().unwrap().rot_idx, 1);
+        assert_eq!(board.piece_turn(), -PI / 2.0);
+        board.update(TURN_DURATION / 2.0, &[]);
+        assert!((board.piece_turn() + PI / 4.0).abs() < 1e-4);
+        board.update(TURN_DURATION, &[]);
+        assert_eq!(board.piece_turn(), 0.0);
+
+        // The next piece doesn't inherit a turn
+        board.update(0.01, &[PlayerInput::Rotate]);
+        board.update(0.01, &[PlayerInput::HardDrop]);
+        board.force_lock();
+        board.update(0.01, &[]);
+        assert_eq!(board.piece_turn(), 0.0);
+    }
+
+    #[test]
+    fn test_tspin_mini_scoring() {
+        let spin = |holes: &[&[isize]], rot_idx| {
+            let mut board = test_board();
+            board.update(1.0 / 60.0, &[]);
+            garbage(&mut board, holes);
+            set_active_piece(&mut board, PieceType::T, rot_idx, 3);
+            let before = board.score();
+            board.update(1.0 / 60.0, &[PlayerInput::Rotate]);
+            let lock = board.force_lock().unwrap();
+            (
+                lock.tspin,
+                lock.tspin_mini,
+                lock.rows_cleared,
+                board.score() - before,
+            )
+        };
+        let scoring = ScoringConfig::default();
+        let placed = 4; // a point a cell for locking the T
+
+        // The slot from test_tspin_mini, clearing nothing: full, then mini
+        let slot: &[&[isize]] = &[&[4, 9], &[3, 4, 5, 9], &[0, 1, 2, 4, 5, 6, 7, 8, 9]];
+        assert_eq!(spin(slot, 3), (true, false, 0, placed + scoring.tspin));
+        assert_eq!(spin(slot, 0), (true, true, 0, placed + scoring.tspin_mini));
+
+        // Spun in pointing down with a bottom corner open, filling a row. A
+        // rotation won't end on a full row, so the T is put there as spun.
+        let mut board = test_board();
+        board.update(1.0 / 60.0, &[]);
+        garbage(&mut board, &[&[4, 5], &[3, 4, 5], &[4]]);
+        set_active_piece(&mut board, PieceType::T, 0, 3);
+        board.rotated_last = true;
+        let before = board.score();
+        let lock = board.force_lock().unwrap();
+        assert_eq!(
+            (lock.tspin, lock.tspin_mini, lock.rows_cleared),
+            (true, true, 1)
+        );
+        assert_eq!(board.score() - before, placed + scoring.tspin_mini_single);
+    }
+
+    #[test]
+    fn test_ages_hold_while_paused() {
+        let mut board = test_board();
+        board.update(1.0 / 60.0, &[]);
+        garbage(&mut board, &[&[0]]);
+        let age = |board: &BoardInstance| board.board.cell_age(BoardPosition { x: 1, y: 0 });
+        board.update(1.0 / 60.0, &[]);
+        assert_eq!(age(&board), Some(1));
+
+        // Paused, the heatmap holds still; the pausing frame itself counts
+        board.update(1.0 / 60.0, &[PlayerInput::Pause]);
+        let paused = age(&board);
+        for _ in 0..30 {
+            board.update(1.0 / 60.0, &[]);
+        }
+        assert_eq!(age(&board), paused);
+
+        // and goes on once the game does
+        board.update(1.0 / 60.0, &[PlayerInput::Pause]);
+        board.update(1.0 / 60.0, &[]);
+        assert!(age(&board) > paused);
+
+        // A finished game stays as it ended
+        board.game_state = GameState::Frozen;
+        let frozen = age(&board);
+        board.update(1.0 / 60.0, &[]);
+        assert_eq!(age(&board), frozen);
+    }
+
+    #[test]
+    fn test_force_lock_above_board() {
+        let mut board = test_board();
+        board.update(1.0 / 60.0, &[]);
+        assert_eq!(board.game_state(), GameState::Falling);
+
+        // Lift the piece until part of it is above the top row
+        let filled = board.board.filled_count();
+        board.active_piece.as_mut().unwrap().position.y = board.board.height - 1;
+
+        assert_eq!(board.force_lock(), None);
+        assert_eq!(board.game_state(), GameState::GameOver);
+        assert_eq!(board.board().filled_count(), filled);
+    }
+}
@@ -0,0 +1,150 @@
+// src/views/board_player.rs
+//
+// Plays back recorded BoardFrames for rendering.
+// Needs no game engine: each frame already holds everything to draw.
+
+use crate::{
+    models::{BoardFrame, PieceSet},
+    views::{BoardPosition, CellMesh},
+};
+use nannou::prelude::*;
+use std::rc::Rc;
+
+// black outline around each filled cell, as on a live board
+const CELL_STROKE_WEIGHT: f32 = 1.5;
+
+pub struct BoardPlayer {
+    frames: Vec<BoardFrame>,
+    current: usize, // index of the frame being shown
+
+    pub location: Vec2, // screen location of the board
+    pub cell_size: f32, // size of the grid cells
+
+    color: Rgba,          // color of cells
+    boundary_color: Rgba, // color of outer boundary
+    looping: bool,        // start over after the last frame
+    pieces: Rc<PieceSet>, // shapes of the recorded boards' pieces
+}
+
+impl BoardPlayer {
+    pub fn new(frames: Vec<BoardFrame>, location: Vec2, cell_size: f32) -> Self {
+        Self {
+            frames,
+            current: 0,
+
+            location,
+            cell_size,
+
+            color: hsva(40.0 / 360.0, 1.0, 0.7, 1.0).into(),
+            boundary_color: hsva(40.0 / 360.0, 1.0, 0.75, 1.0).into(),
+            looping: false,
+            pieces: Rc::default(),
+        }
+    }
+
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping;
+    }
+
+    // The piece set the frames were recorded with
+    pub fn set_pieces(&mut self, pieces: Rc<PieceSet>) {
+        self.pieces = pieces;
+    }
+
+    /************************ Playback controls *******************************/
+
+    // Advance one frame. Frames were recorded once per tick.
+    // Returns false once playback has finished.
+    pub fn step(&mut self) -> bool {
+        if self.current + 1 < self.frames.len() {
+            self.current += 1;
+            true
+        } else if self.looping && !self.frames.is_empty() {
+            self.current = 0;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn seek(&mut self, idx: usize) {
+        self.current = idx.min(self.frames.len().saturating_sub(1));
+    }
+
+    pub fn is_finished(&self) -> bool {
+        !self.looping && self.current + 1 >= self.frames.len()
+    }
+
+    pub fn current_frame(&self) -> Option<&BoardFrame> {
+        self.frames.get(self.current)
+    }
+
+    pub fn position(&self) -> usize {
+        self.current
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /************************ Drawing methods *******************************/
+
+    pub fn draw(&self, draw: &Draw) {
+        let Some(frame) = self.current_frame() else {
+            return;
+        };
+
+        let mut cells = CellMesh::with_capacity(
+            self.cell_size,
+            CELL_STROKE_WEIGHT,
+            (frame.width * frame.height) as usize,
+        );
+
+        // Draw the board
+        for y in 0..frame.height {
+            for x in 0..frame.width {
+                let pos = BoardPosition { x, y };
+                if frame.is_cell_filled(pos) {
+                    cells.push(self.to_screen(frame, pos), self.color);
+                }
+            }
+        }
+
+        // Draw the active piece
+        if let Some(piece) = &frame.active_piece {
+            for pos in piece.cells(&self.pieces) {
+                if pos.x >= 0 && pos.x < frame.width && pos.y >= 0 && pos.y < frame.height {
+                    cells.push(self.to_screen(frame, pos), self.color);
+                }
+            }
+        }
+
+        cells.draw(draw);
+
+        // Draw boundary around the board
+        draw.rect()
+            .xy(self.location)
+            .w_h(
+                frame.width as f32 * self.cell_size,
+                frame.height as f32 * self.cell_size,
+            )
+            .stroke_weight(1.0)
+            .stroke_color(self.boundary_color)
+            .color(rgba(0.0, 0.0, 0.0, 0.0));
+    }
+
+    // Cell origin is bottom_left. Board origin is center of board.
+    fn to_screen(&self, frame: &BoardFrame, pos: BoardPosition) -> Vec2 {
+        let half_width = (frame.width as f32 - 1.0) * 0.5 * self.cell_size;
+        let half_height = (frame.height as f32 - 1.0) * 0.5 * self.cell_size;
+
+        vec2(
+            self.location.x + pos.x as f32 * self.cell_size - half_width,
+            self.location.y + pos.y as f32 * self.cell_size - half_height,
+        )
+    }
+}
@@ -0,0 +1,172 @@
+// src/views/cell_mesh.rs
+//
+// Batches board cells into one mesh so a whole board is a single draw call.
+//
+// Looks the same as drawing each cell with draw.rect().stroke(BLACK):
+// the stroke is centered on the cell edge, so each cell becomes a black
+// outline quad grown by half the stroke, and a colored fill quad shrunk by
+// half the stroke. All outlines go first so no fill covers a neighbour's stroke.
+
+use nannou::prelude::*;
+
+pub struct CellMesh {
+    cell_size: f32,
+    stroke_weight: f32,
+    cells: Vec<(Vec2, Rgba)>, // screen centers and fill colors
+}
+
+impl CellMesh {
+    pub fn new(cell_size: f32, stroke_weight: f32) -> Self {
+        Self::with_capacity(cell_size, stroke_weight, 0)
+    }
+
+    pub fn with_capacity(cell_size: f32, stroke_weight: f32, capacity: usize) -> Self {
+        Self {
+            cell_size,
+            stroke_weight,
+            cells: Vec::with_capacity(capacity),
+        }
+    }
+
+    // Add a filled cell centered at a screen position
+    pub fn push(&mut self, center: Vec2, color: Rgba) {
+        self.cells.push((center, color));
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    // Screen centers of the cells, in the order pushed
+    pub fn centers(&self) -> impl Iterator<Item = Vec2> + '_ {
+        self.cells.iter().map(|&(center, _)| center)
+    }
+
+    pub fn draw(&self, draw: &Draw) {
+        if self.is_empty() {
+            return;
+        }
+
+        let (points, indices) = self.geometry();
+        draw.mesh().indexed_colored(points, indices);
+    }
+
+    // Vertices and triangle indices, outlines first
+    fn geometry(&self) -> (Vec<(Vec3, Rgba)>, Vec<usize>) {
+        let quad_count = self.cells.len() * 2;
+        let mut points = Vec::with_capacity(quad_count * 4);
+        let mut indices = Vec::with_capacity(quad_count * 6);
+
+        let black: Rgba = rgba(0.0, 0.0, 0.0, 1.0);
+        let outer = (self.cell_size + self.stroke_weight) / 2.0;
+        let inner = (self.cell_size - self.stroke_weight).max(0.0) / 2.0;
+
+        let outlines = self.cells.iter().map(|&(center, _)| (center, outer, black));
+        let fills = self
+            .cells
+            .iter()
+            .map(|&(center, color)| (center, inner, color));
+
+        for (center, half_size, color) in outlines.chain(fills) {
+            let start = points.len();
+            points.push((vec3(center.x - half_size, center.y - half_size, 0.0), color));
+            points.push((vec3(center.x + half_size, center.y - half_size, 0.0), color));
+            points.push((vec3(center.x + half_size, center.y + half_size, 0.0), color));
+            points.push((vec3(center.x - half_size, center.y + half_size, 0.0), color));
+            indices.extend_from_slice(&[start, start + 1, start + 2, start, start + 2, start + 3]);
+        }
+
+        (points, indices)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CELL_SIZE: f32 = 10.0;
+    const STROKE: f32 = 1.5;
+
+    // What stroked rects drawn one per cell leave at point p
+    fn per_cell_pixel(cells: &[(Vec2, Rgba)], p: Vec2) -> Option<Rgba> {
+        let mut pixel = None;
+        for &(center, color) in cells {
+            let d = (p - center).abs();
+            let half = CELL_SIZE / 2.0;
+
+            // Fill covers the rect, then the stroke straddles its edge
+            let in_fill = d.x <= half && d.y <= half;
+            let in_stroke_outer = d.x <= half + STROKE / 2.0 && d.y <= half + STROKE / 2.0;
+            let in_stroke_inner = d.x < half - STROKE / 2.0 && d.y < half - STROKE / 2.0;
+
+            if in_fill {
+                pixel = Some(color);
+            }
+            if in_stroke_outer && !in_stroke_inner {
+                pixel = Some(rgba(0.0, 0.0, 0.0, 1.0));
+            }
+        }
+        pixel
+    }
+
+    // What the mesh leaves at point p: the last triangle covering it wins
+    fn mesh_pixel(mesh: &CellMesh, p: Vec2) -> Option<Rgba> {
+        let (points, indices) = mesh.geometry();
+        let sign = |p1: Vec2, p2: Vec2, p3: Vec2| {
+            (p1.x - p3.x) * (p2.y - p3.y) - (p2.x - p3.x) * (p1.y - p3.y)
+        };
+
+        let mut pixel = None;
+        for tri in indices.chunks(3) {
+            let [a, b, c] = [tri[0], tri[1], tri[2]].map(|i| points[i].0.truncate());
+            let (d1, d2, d3) = (sign(p, a, b), sign(p, b, c), sign(p, c, a));
+            let has_neg = d1 < 0.0 || d2 < 0.0 || d3 < 0.0;
+            let has_pos = d1 > 0.0 || d2 > 0.0 || d3 > 0.0;
+            if !(has_neg && has_pos) {
+                pixel = Some(points[tri[0]].1);
+            }
+        }
+        pixel
+    }
+
+    #[test]
+    fn test_mesh_matches_per_cell_rects() {
+        let gold = rgba(1.0, 0.67, 0.0, 1.0);
+        let grey = rgba(0.5, 0.5, 0.5, 1.0);
+
+        // An L-shaped group, a lone cell and two differently colored neighbours
+        let cells: Vec<(Vec2, Rgba)> = [
+            (0, 0, gold),
+            (1, 0, gold),
+            (0, 1, gold),
+            (3, 5, gold),
+            (4, 3, grey),
+            (5, 3, gold),
+        ]
+        .iter()
+        .map(|&(x, y, color)| (vec2(x as f32, y as f32) * CELL_SIZE, color))
+        .collect();
+
+        let mut mesh = CellMesh::new(CELL_SIZE, STROKE);
+        for &(center, color) in &cells {
+            mesh.push(center, color);
+        }
+
+        // Sample at pixel centers, which never land exactly on a stroke boundary
+        for py in -20..80 {
+            for px in -20..80 {
+                let p = vec2(px as f32 + 0.5, py as f32 + 0.5);
+                assert_eq!(
+                    mesh_pixel(&mesh, p),
+                    per_cell_pixel(&cells, p),
+                    "pixel mismatch at {:?}",
+                    p
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,699 @@
+// src/views/game_manager.rs
+//
+// Owns the Tetris boards and routes input to them.
+// Runs an AI attract mode when nobody has played for a while.
+// Optionally records a BoardFrame per board every tick for playback.
+// For debugging, the game can be frozen and stepped a tick at a time.
+
+use crate::{
+    ai::{personality, AiPlayer},
+    config::{AttractConfig, BoardConfig, VersusConfig},
+    models::FrameBuffer,
+    views::{
+        AttackRouter, BoardInstance, BoardSnapshot, GameSnapshot, GameState, PlayerInput,
+        RenderOptions,
+    },
+};
+use nannou::prelude::*;
+use std::{collections::HashMap, io, path::Path};
+
+// how far one step moves a frozen game: a tick at 60 fps
+const STEP_DT: f32 = 1.0 / 60.0;
+
+// inputs a frozen game keeps for its next tick, as many as a board's queue takes
+const HELD_INPUT_CAPACITY: usize = 32;
+
+pub struct GameManager {
+    boards: HashMap<String, BoardInstance>,
+    board_config: BoardConfig,
+    render: RenderOptions,               // how every board is drawn
+    attack_router: Option<AttackRouter>, // versus garbage, when on
+    tick: u64,                           // updates since the manager was made
+    countdown_length: f32,               // seconds of countdown before versus games
+    countdown: Option<f32>,              // seconds until every board starts at once
+    seed: u64,                           // master seed every board's seed comes from
+
+    // Attract mode
+    attract_config: AttractConfig,
+    idle_time: f32,                        // seconds since the last player input
+    ai_players: HashMap<String, AiPlayer>, // one per board while attract mode is on
+    attract_mode: bool,
+
+    // Output recording, one buffer per board while recording is on
+    frame_buffers: Option<HashMap<String, FrameBuffer>>,
+    frame_capacity: usize,
+
+    // Freeze frame debugging
+    frozen: bool,                  // only step() moves the game
+    held_inputs: Vec<PlayerInput>, // inputs that came in while frozen, for the next tick
+}
+
+impl GameManager {
+    pub fn new(board_config: BoardConfig, attract_config: AttractConfig) -> Self {
+        Self {
+            boards: HashMap::new(),
+            board_config,
+            render: RenderOptions::default(),
+            attack_router: None,
+            tick: 0,
+            countdown_length: 0.0,
+            countdown: None,
+            seed: 0,
+
+            attract_config,
+            idle_time: 0.0,
+            ai_players: HashMap::new(),
+            attract_mode: false,
+
+            frame_buffers: None,
+            frame_capacity: 0,
+
+            frozen: false,
+            held_inputs: Vec::new(),
+        }
+    }
+
+    // Same master seed, same boards: a session plays back from this and its inputs
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    // The seed the master seed gives the board `id`
+    pub fn board_seed(&self, id: &str) -> u64 {
+        demo_seed(self.seed, id)
+    }
+
+    pub fn make_board(&mut self, id: &str, location: Vec2, seed: u64) {
+        let config = &self.board_config;
+        let mut board = match BoardInstance::new(id, location, config, seed) {
+            Ok(board) => board,
+            Err(e) => {
+                println!("GameOver: can't make board {}: {}", id, e);
+                return;
+            }
+        };
+        board.set_render_options(self.render.clone());
+        self.boards.insert(board.id.to_owned(), board);
+        if let Some(buffers) = self.frame_buffers.as_mut() {
+            buffers.insert(id.to_owned(), FrameBuffer::new(self.frame_capacity));
+        }
+        println!("\n<------ Board Created: <{}> ----->", id);
+        println!(
+            "size: {}x{} blocks\nlocation: {}\n",
+            config.width, config.height, location
+        );
+        self.start_countdown();
+    }
+
+    // Take a board out of the game, with its demo player and recording
+    pub fn remove_board(&mut self, id: &str) -> Option<BoardInstance> {
+        self.ai_players.remove(id);
+        if let Some(buffers) = self.frame_buffers.as_mut() {
+            buffers.remove(id);
+        }
+        let board = self.boards.remove(id)?;
+        println!("\n<------ Board Removed: <{}> ----->", id);
+        Some(board)
+    }
+
+    // How each board is drawn, existing boards included
+    pub fn set_render_options(&mut self, render: RenderOptions) {
+        for board in self.boards.values_mut() {
+            board.set_render_options(render.clone());
+        }
+        self.render = render;
+    }
+
+    // Versus play sends garbage between boards. Turning it on starts afresh.
+    pub fn set_versus(&mut self, config: &VersusConfig) {
+        self.attack_router = config
+            .enabled
+            .then(|| AttackRouter::new(config.routing, config.seed));
+        self.countdown_length = if config.enabled {
+            config.countdown.max(0.0)
+        } else {
+            0.0
+        };
+        self.start_countdown();
+    }
+
+    // Hold every board still for the versus countdown, starting over if one is running
+    pub fn start_countdown(&mut self) {
+        self.countdown = (self.countdown_length > 0.0).then_some(self.countdown_length);
+    }
+
+    // Seconds left before the boards start, while counting down
+    pub fn countdown(&self) -> Option<f32> {
+        self.countdown
+    }
+
+    /************************ Update orchestrator *******************************/
+
+    // Frozen, the frame's inputs wait for the next step; unfrozen, they go
+    // in after any that waited
+    pub fn update(&mut self, dt: f32, inputs: &[PlayerInput]) {
+        if self.frozen {
+            self.hold_inputs(inputs);
+            return;
+        }
+        if !self.held_inputs.is_empty() {
+            self.hold_inputs(inputs);
+            let held = std::mem::take(&mut self.held_inputs);
+            return self.advance(dt, &held);
+        }
+        self.advance(dt, inputs);
+    }
+
+    // Keep inputs for the next tick, dropping any past the cap. Says so once,
+    // as it fills, not every frame of a long freeze.
+    fn hold_inputs(&mut self, inputs: &[PlayerInput]) {
+        let room = HELD_INPUT_CAPACITY.saturating_sub(self.held_inputs.len());
+        if inputs.len() > room && room > 0 {
+            println!(
+                "GameOver: {} inputs held while frozen, dropping the rest",
+                HELD_INPUT_CAPACITY
+            );
+        }
+        self.held_inputs
+            .extend_from_slice(&inputs[..room.min(inputs.len())]);
+    }
+
+    // Move a frozen game on by exactly one tick
+    pub fn step(&mut self) {
+        let held = std::mem::take(&mut self.held_inputs);
+        self.advance(STEP_DT, &held);
+    }
+
+    pub fn set_frozen(&mut self, frozen: bool) {
+        self.frozen = frozen;
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+
+    fn advance(&mut self, dt: f32, inputs: &[PlayerInput]) {
+        self.tick += 1;
+
+        // Nothing moves until the countdown runs out, then every board starts this tick
+        if let Some(remaining) = self.countdown.as_mut() {
+            *remaining -= dt;
+            if *remaining > 0.0 {
+                return;
+            }
+            self.countdown = None;
+        }
+
+        self.update_idle(dt, inputs);
+
+        for (id, board) in self.boards.iter_mut() {
+            if self.attract_mode {
+                // Keep the demo going after a game over
+                if board.game_state() == GameState::Frozen {
+                    board.restart(demo_seed(self.attract_config.demo_seed, id));
+                }
+
+                let ai_input = self
+                    .ai_players
+                    .get_mut(id)
+                    .and_then(|ai| ai.next_input(dt, board));
+                board.update(dt, ai_input.as_slice());
+            } else {
+                // Players' inputs wait in the board's queue, so a burst
+                // bigger than a tick takes applies over the next few
+                board.queue_inputs(inputs);
+                board.update(dt, &[]);
+            }
+
+            if let Some(buffer) = self
+                .frame_buffers
+                .as_mut()
+                .and_then(|buffers| buffers.get_mut(id))
+            {
+                buffer.push(board.frame());
+            }
+        }
+
+        self.route_attacks();
+    }
+
+    // Clears send garbage; a lock that clears nothing takes in what's waiting.
+    // Boards go in id order so random routing replays the same.
+    fn route_attacks(&mut self) {
+        let Some(router) = self.attack_router.as_mut() else {
+            return;
+        };
+
+        let mut scores: Vec<(String, usize)> = self
+            .boards
+            .iter()
+            .map(|(id, board)| (id.to_owned(), board.score()))
+            .collect();
+        scores.sort();
+        let topped_out: Vec<String> = self
+            .boards
+            .iter()
+            .filter(|(_, board)| {
+                matches!(board.game_state(), GameState::GameOver | GameState::Frozen)
+            })
+            .map(|(id, _)| id.to_owned())
+            .collect();
+
+        for (id, _) in &scores {
+            let Some(board) = self.boards.get_mut(id) else {
+                continue;
+            };
+            let Some(lock) = board.take_lock() else {
+                continue;
+            };
+
+            if lock.rows_cleared > 0 {
+                // Garbage for a board that's topped out would be lost
+                let opponents: Vec<(String, usize)> = scores
+                    .iter()
+                    .filter(|(other, _)| other != id && !topped_out.contains(other))
+                    .cloned()
+                    .collect();
+                router.send(id, &lock, board.stats().combo(), &opponents);
+            } else {
+                // Each batch lands with its own hole
+                for count in router.take_pending(id) {
+                    let hole = router.garbage_hole(self.board_config.width);
+                    board.receive_garbage(count, hole);
+                }
+            }
+        }
+    }
+
+    pub fn draw(&self, draw: &Draw) {
+        for board in self.boards.values() {
+            board.draw(draw);
+        }
+    }
+
+    /************************ Attract mode *******************************/
+
+    // Any player input hands control back immediately
+    fn update_idle(&mut self, dt: f32, inputs: &[PlayerInput]) {
+        if !inputs.is_empty() {
+            self.idle_time = 0.0;
+            if self.attract_mode {
+                self.stop_attract_mode();
+            }
+            return;
+        }
+
+        self.idle_time += dt;
+        if !self.attract_mode && self.idle_time >= self.attract_config.idle_timeout {
+            self.start_attract_mode();
+        }
+    }
+
+    // Start a fresh demo game on every board, each driven by an AI player
+    fn start_attract_mode(&mut self) {
+        println!("\n<------ Attract mode ON ----->");
+        for (id, board) in self.boards.iter_mut() {
+            board.restart(demo_seed(self.attract_config.demo_seed, id));
+            let seed = demo_seed(self.attract_config.demo_seed, id);
+            let weights = personality(
+                &self.attract_config.ai_weights,
+                self.attract_config.personality_spread,
+                seed,
+            );
+            let ai = AiPlayer::with_skill(weights, self.attract_config.ai_skill, seed);
+            self.ai_players.insert(id.to_owned(), ai);
+        }
+        self.attract_mode = true;
+    }
+
+    fn stop_attract_mode(&mut self) {
+        println!("\n<------ Attract mode OFF ----->");
+        self.ai_players.clear();
+        self.attract_mode = false;
+    }
+
+    pub fn is_attract_mode(&self) -> bool {
+        self.attract_mode
+    }
+
+    pub fn idle_time(&self) -> f32 {
+        self.idle_time
+    }
+
+    /************************ Frame recording *******************************/
+
+    // Keep up to `capacity` of the most recent frames for each board
+    pub fn start_recording_frames(&mut self, capacity: usize) {
+        println!("\n<------ Frame recording ON ----->");
+        let buffers = self
+            .boards
+            .keys()
+            .map(|id| (id.to_owned(), FrameBuffer::new(capacity)))
+            .collect();
+        self.frame_buffers = Some(buffers);
+        self.frame_capacity = capacity;
+    }
+
+    // Stop recording and write each board's frames to <dir>/<id>.gof
+    pub fn stop_recording_frames(&mut self, dir: &Path) -> io::Result<()> {
+        println!("\n<------ Frame recording OFF ----->");
+        let Some(buffers) = self.frame_buffers.take() else {
+            return Ok(());
+        };
+
+        std::fs::create_dir_all(dir)?;
+        for (id, buffer) in buffers.iter() {
+            let path = dir.join(format!("{}.gof", id));
+            buffer.save(&path)?;
+            println!("Saved {} frames to {}", buffer.len(), path.display());
+        }
+        Ok(())
+    }
+
+    pub fn is_recording_frames(&self) -> bool {
+        self.frame_buffers.is_some()
+    }
+
+    pub fn frame_buffer(&self, id: &str) -> Option<&FrameBuffer> {
+        self.frame_buffers
+            .as_ref()
+            .and_then(|buffers| buffers.get(id))
+    }
+
+    /************************ Utility methods *******************************/
+
+    // Boards are sorted by id so consecutive snapshots compare equal
+    pub fn snapshot(&self) -> GameSnapshot {
+        let mut boards: Vec<BoardSnapshot> =
+            self.boards.values().map(BoardSnapshot::capture).collect();
+        boards.sort_by(|a, b| a.id.cmp(&b.id));
+
+        GameSnapshot {
+            attract_mode: self.attract_mode,
+            boards,
+        }
+    }
+
+    // For syncing outside visuals: counts updates, not time
+    pub fn current_tick(&self) -> u64 {
+        self.tick
+    }
+
+    pub fn boards(&self) -> &HashMap<String, BoardInstance> {
+        &self.boards
+    }
+
+    pub fn board_config(&self) -> &BoardConfig {
+        &self.board_config
+    }
+}
+
+// Each board gets its own demo sequence, the same one every time attract mode starts
+fn demo_seed(seed: u64, id: &str) -> u64 {
+    id.bytes()
+        .fold(seed, |acc, b| acc.wrapping_mul(31).wrapping_add(b as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::{AiSkill, AiWeights},
+        views::BoardPosition,
+    };
+
+    fn test_manager() -> GameManager {
+        let board_config = BoardConfig::default();
+        let attract_config = AttractConfig {
+            idle_timeout: 1.0,
+            demo_seed: 7,
+            ai_weights: AiWeights::default(),
+            personality_spread: 0.2,
+            ai_skill: AiSkill::default(),
+        };
+
+        let mut manager = GameManager::new(board_config, attract_config);
+        manager.make_board("board1", vec2(0.0, 0.0), 1);
+        manager
+    }
+
+    #[test]
+    fn test_attract_mode_idle_and_input() {
+        let mut manager = test_manager();
+        let dt = 1.0 / 60.0;
+
+        // Not idle long enough yet
+        for _ in 0..30 {
+            manager.update(dt, &[]);
+        }
+        assert!(!manager.is_attract_mode());
+
+        // Idle past the timeout: the AI takes over
+        for _ in 0..60 {
+            manager.update(dt, &[]);
+        }
+        assert!(manager.is_attract_mode());
+        assert_eq!(manager.ai_players.len(), 1);
+
+        // Let the AI play for a moment
+        for _ in 0..120 {
+            manager.update(dt, &[]);
+        }
+        assert!(manager.is_attract_mode());
+
+        // Any input hands control back to the player
+        manager.update(dt, &[PlayerInput::L]);
+        assert!(!manager.is_attract_mode());
+        assert!(manager.ai_players.is_empty());
+        assert_eq!(manager.idle_time(), 0.0);
+    }
+
+    #[test]
+    fn test_tick_counter() {
+        let mut manager = test_manager();
+
+        // Long, short and empty frames each count once, paused or not
+        for dt in [1.0 / 60.0, 0.5, 0.0, 3.0, 1.0 / 240.0] {
+            manager.update(dt, &[]);
+        }
+        manager.update(1.0 / 60.0, &[PlayerInput::Pause]);
+        manager.update(1.0 / 60.0, &[]);
+        assert_eq!(manager.current_tick(), 7);
+        assert_eq!(manager.boards()["board1"].current_tick(), 7);
+
+        // A board made later counts from its own start
+        manager.make_board("board2", vec2(400.0, 0.0), 2);
+        manager.update(1.0 / 60.0, &[]);
+        assert_eq!(manager.current_tick(), 8);
+        assert_eq!(manager.boards()["board2"].current_tick(), 1);
+    }
+
+    #[test]
+    fn test_countdown() {
+        let mut manager = test_manager();
+        manager.set_versus(&VersusConfig {
+            enabled: true,
+            countdown: 1.0,
+            ..VersusConfig::default()
+        });
+        manager.make_board("board2", vec2(400.0, 0.0), 2);
+        assert_eq!(manager.countdown(), Some(1.0));
+
+        // Frozen for the whole second, whatever's pressed
+        for _ in 0..3 {
+            manager.update(0.25, &[PlayerInput::HardDrop]);
+        }
+        assert!(manager.countdown().unwrap() > 0.0);
+        for board in manager.boards().values() {
+            assert!(board.active_piece().is_none());
+            assert_eq!(board.current_tick(), 0);
+        }
+
+        // Then both go on the same tick
+        manager.update(0.25, &[]);
+        assert_eq!(manager.countdown(), None);
+        for board in manager.boards().values() {
+            assert!(board.active_piece().is_some());
+            assert_eq!(board.current_tick(), 1);
+        }
+    }
+
+    #[test]
+    fn test_master_seed() {
+        let session = |seed| {
+            let mut manager = test_manager().with_seed(seed);
+            for id in ["board1", "board2"] {
+                let seed = manager.board_seed(id);
+                manager.make_board(id, vec2(0.0, 0.0), seed);
+            }
+            manager
+        };
+        let mut a = session(42);
+        let mut b = session(42);
+        assert_ne!(a.board_seed("board1"), a.board_seed("board2"));
+
+        // The same input log played into both sessions
+        let inputs = [PlayerInput::L, PlayerInput::Rotate, PlayerInput::HardDrop];
+        for tick in 0..600 {
+            let input = (tick % 5 == 0).then(|| inputs[tick / 5 % inputs.len()]);
+            a.update(1.0 / 60.0, input.as_slice());
+            b.update(1.0 / 60.0, input.as_slice());
+        }
+
+        for id in ["board1", "board2"] {
+            let (a, b) = (&a.boards[id], &b.boards[id]);
+            assert!(a.score() > 0);
+            assert_eq!(a.frame(), b.frame());
+            assert_eq!(a.stats(), b.stats());
+            assert_eq!(a.score(), b.score());
+        }
+        assert_eq!(a.seed(), 42);
+        assert_ne!(session(43).board_seed("board1"), a.board_seed("board1"));
+    }
+
+    #[test]
+    fn test_freeze_frame() {
+        let mut manager = test_manager();
+        manager.update(1.0 / 60.0, &[]);
+        let board = |manager: &GameManager| manager.boards()["board1"].frame();
+        let x = |manager: &GameManager| {
+            let piece = manager.boards()["board1"].active_piece().unwrap();
+            piece.position.x
+        };
+
+        // Seconds go by and keys are pressed, but nothing moves
+        manager.set_frozen(true);
+        let (frozen, start) = (board(&manager), x(&manager));
+        for _ in 0..10 {
+            manager.update(1.0, &[]);
+        }
+        manager.update(1.0, &[PlayerInput::L]);
+        assert_eq!(board(&manager), frozen);
+        assert_eq!(manager.current_tick(), 1);
+
+        // Each step is one tick, with the input that was waiting
+        manager.step();
+        assert_eq!(manager.current_tick(), 2);
+        assert_eq!(manager.boards()["board1"].current_tick(), 2);
+        assert_eq!(x(&manager), start - 1);
+        manager.step();
+        assert_eq!(x(&manager), start - 1);
+
+        manager.set_frozen(false);
+        manager.update(1.0 / 60.0, &[]);
+        assert_eq!(manager.current_tick(), 4);
+    }
+
+    #[test]
+    fn test_held_input_cap() {
+        let mut manager = test_manager();
+        manager.update(1.0 / 60.0, &[]);
+
+        // A long freeze keeps only so many inputs, oldest first
+        manager.set_frozen(true);
+        for _ in 0..100 {
+            manager.update(1.0 / 60.0, &[PlayerInput::L]);
+        }
+        manager.update(1.0 / 60.0, &[PlayerInput::R]);
+        assert_eq!(
+            manager.held_inputs,
+            vec![PlayerInput::L; HELD_INPUT_CAPACITY]
+        );
+
+        // Unfreezing plays them, with nothing left over
+        manager.set_frozen(false);
+        manager.update(1.0 / 60.0, &[PlayerInput::R]);
+        assert!(manager.held_inputs.is_empty());
+    }
+
+    #[test]
+    fn test_input_burst() {
+        let mut manager = test_manager();
+        manager.update(1.0 / 60.0, &[]);
+        let x = |manager: &GameManager| {
+            let piece = manager.boards()["board1"].active_piece().unwrap();
+            piece.position.x
+        };
+        let start = x(&manager);
+
+        // More taps in a frame than a tick takes: the rest wait their turn
+        let burst = [PlayerInput::L; 3].into_iter().chain([PlayerInput::R; 3]);
+        manager.update(1.0 / 60.0, &burst.collect::<Vec<_>>());
+        assert_eq!(x(&manager), start - 2);
+        assert_eq!(manager.boards()["board1"].queued_inputs(), 2);
+
+        manager.update(1.0 / 60.0, &[]);
+        assert_eq!(x(&manager), start);
+        assert_eq!(manager.boards()["board1"].queued_inputs(), 0);
+    }
+
+    #[test]
+    fn test_attacks_skip_topped_out() {
+        let mut manager = test_manager();
+        manager.attract_config.idle_timeout = f32::MAX;
+        manager.boards.clear();
+        manager.set_versus(&VersusConfig {
+            enabled: true,
+            ..VersusConfig::default()
+        });
+        for (i, id) in ["a", "b", "c"].into_iter().enumerate() {
+            manager.make_board(id, vec2(0.0, 0.0), i as u64);
+        }
+        manager.update(1.0 / 60.0, &[]);
+
+        // b stacks out under a wall of garbage
+        let b = manager.boards.get_mut("b").unwrap();
+        let wall: Vec<bool> = (0..10).map(|x| x != 0).collect();
+        b.board_mut().add_garbage(&vec![wall; 20]);
+        assert!(b.force_lock().is_none());
+        assert_eq!(b.game_state(), GameState::GameOver);
+
+        // a's piece drops into two rows left open just for it: a double
+        let a = manager.boards.get_mut("a").unwrap();
+        let cells = a.active_piece().unwrap().board_cells(a.board().pieces());
+        let bottom = cells.iter().map(|pos| pos.y).min().unwrap();
+        let rows: Vec<Vec<bool>> = (0..2)
+            .map(|y| {
+                (0..10)
+                    .map(|x| !cells.contains(&BoardPosition { x, y: bottom + y }))
+                    .collect()
+            })
+            .collect();
+        assert!(rows[1].contains(&false), "the first piece needs two rows");
+        a.board_mut().add_garbage(&rows);
+
+        // A double's row skips b, the first in turn, and lands on c when c
+        // next locks a piece
+        let filled = |manager: &GameManager, id: &str| manager.boards()[id].board().filled_count();
+        let (b_before, c_before) = (filled(&manager, "b"), filled(&manager, "c"));
+        for _ in 0..2 {
+            manager.update(1.0 / 60.0, &[PlayerInput::HardDrop]);
+            for _ in 0..90 {
+                manager.update(1.0 / 60.0, &[]);
+            }
+        }
+
+        assert_eq!(manager.boards()["a"].stats().lines, 2);
+        assert_eq!(filled(&manager, "b"), b_before);
+        assert!(filled(&manager, "c") > c_before + 9);
+    }
+
+    #[test]
+    fn test_remove_board() {
+        let mut manager = test_manager();
+        manager.make_board("board2", vec2(400.0, 0.0), 2);
+        manager.start_attract_mode();
+        assert_eq!(manager.ai_players.len(), 2);
+
+        // Gone with its demo player, and gone only once
+        assert_eq!(manager.remove_board("board2").unwrap().id, "board2");
+        assert!(manager.remove_board("board2").is_none());
+        assert_eq!(manager.boards().len(), 1);
+        assert!(!manager.ai_players.contains_key("board2"));
+    }
+}
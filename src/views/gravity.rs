@@ -0,0 +1,167 @@
+// src/views/gravity.rs
+//
+// How the active piece falls between inputs. Each strategy drives the
+// board's gravity timer and says, tick by tick, how many rows the piece
+// drops and when it has landed and should start locking.
+
+use crate::{config::GravityMode, utils::Timer};
+
+// G counts rows fallen per 60th of a second
+const G_FRAME: f32 = 1.0 / 60.0;
+
+// the conventional G of gravity that lands pieces at once
+const INSTANT_G: f32 = 20.0;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GravityStep {
+    pub rows: isize, // rows to drop this tick, never more than there's room for
+    pub lock: bool,  // the piece has nowhere left to fall: start locking
+}
+
+pub trait GravityStrategy {
+    // `room` is how many rows the piece can fall before it lands
+    fn step(&mut self, timer: &mut Timer, dt: f32, room: isize) -> GravityStep;
+
+    // A new piece starts falling
+    fn reset(&mut self) {}
+
+    // Speed in G, for a gravity step every `interval` seconds
+    fn g(&self, interval: f32) -> f32 {
+        G_FRAME / interval.max(f32::EPSILON)
+    }
+
+    fn box_clone(&self) -> Box<dyn GravityStrategy>;
+}
+
+impl Clone for Box<dyn GravityStrategy> {
+    fn clone(&self) -> Self {
+        self.box_clone()
+    }
+}
+
+pub fn strategy(mode: GravityMode) -> Box<dyn GravityStrategy> {
+    match mode {
+        GravityMode::Naive => Box::new(NaiveGravity),
+        GravityMode::Fractional => Box::new(FractionalGravity::default()),
+        GravityMode::Instant => Box::new(InstantGravity),
+    }
+}
+
+// One row each time the timer runs out, however short the interval
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NaiveGravity;
+
+impl GravityStrategy for NaiveGravity {
+    fn step(&mut self, timer: &mut Timer, dt: f32, room: isize) -> GravityStep {
+        if !timer.tick(dt) {
+            return GravityStep::default();
+        }
+        GravityStep {
+            rows: room.min(1),
+            lock: room == 0,
+        }
+    }
+
+    fn box_clone(&self) -> Box<dyn GravityStrategy> {
+        Box::new(*self)
+    }
+}
+
+// Rows fall in proportion to time, carrying the fraction of a row over,
+// so intervals shorter than a frame drop several rows at once
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FractionalGravity {
+    fallen: f32, // part of a row fallen since the last whole row
+}
+
+impl GravityStrategy for FractionalGravity {
+    fn step(&mut self, timer: &mut Timer, dt: f32, room: isize) -> GravityStep {
+        // The timer only shows how far along the next row is
+        timer.tick(dt);
+        self.fallen += dt / timer.duration().max(f32::EPSILON);
+
+        let rows = self.fallen.floor();
+        self.fallen -= rows;
+        let rows = rows as isize;
+        GravityStep {
+            rows: rows.min(room),
+            lock: rows > room,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.fallen = 0.0;
+    }
+
+    fn box_clone(&self) -> Box<dyn GravityStrategy> {
+        Box::new(*self)
+    }
+}
+
+// 20G: the piece lands the moment it spawns or slides off a ledge
+#[derive(Debug, Clone, Copy, Default)]
+pub struct InstantGravity;
+
+impl GravityStrategy for InstantGravity {
+    fn step(&mut self, _timer: &mut Timer, _dt: f32, room: isize) -> GravityStep {
+        GravityStep {
+            rows: room,
+            lock: true,
+        }
+    }
+
+    fn g(&self, _interval: f32) -> f32 {
+        INSTANT_G
+    }
+
+    fn box_clone(&self) -> Box<dyn GravityStrategy> {
+        Box::new(*self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_naive_gravity() {
+        let mut gravity = NaiveGravity;
+        let mut timer = Timer::new(0.5);
+
+        // A row when the interval is up, and a lock once there's no room
+        assert_eq!(gravity.step(&mut timer, 0.25, 5), GravityStep::default());
+        assert_eq!(gravity.step(&mut timer, 0.25, 5).rows, 1);
+        assert_eq!(gravity.step(&mut timer, 2.0, 5).rows, 1);
+        let landed = gravity.step(&mut timer, 0.5, 0);
+        assert_eq!((landed.rows, landed.lock), (0, true));
+    }
+
+    #[test]
+    fn test_fractional_gravity() {
+        let mut gravity = FractionalGravity::default();
+        let mut timer = Timer::new(0.5);
+
+        // Two and a half rows' worth drops two, and the half carries over
+        assert_eq!(gravity.step(&mut timer, 1.25, 10).rows, 2);
+        assert_eq!(gravity.step(&mut timer, 0.25, 10).rows, 1);
+
+        // More than there's room for lands the piece
+        let landed = gravity.step(&mut timer, 1.5, 1);
+        assert_eq!((landed.rows, landed.lock), (1, true));
+
+        gravity.fallen = 0.5;
+        gravity.reset();
+        assert_eq!(gravity.step(&mut timer, 0.25, 10).rows, 0);
+    }
+
+    #[test]
+    fn test_instant_gravity() {
+        let mut gravity = InstantGravity;
+        let mut timer = Timer::new(0.5);
+
+        // Straight to the floor however little time passes
+        let step = gravity.step(&mut timer, 0.0, 17);
+        assert_eq!((step.rows, step.lock), (17, true));
+        assert_eq!(gravity.step(&mut timer, 0.0, 0).rows, 0);
+    }
+}
@@ -0,0 +1,108 @@
+// src/views/key_sampler.rs
+//
+// Turns raw key events into the inputs a board wants. Boards take key
+// edges: one press when a key goes down and one release when it comes up.
+// Shift repeats come from the board's own DAS and repeat rate, so the OS
+// auto-repeat of a held key must not reach a board as more presses.
+
+use crate::views::PlayerInput;
+
+#[derive(Debug, Clone, Default)]
+pub struct KeySampler {
+    down: Vec<PlayerInput>, // press inputs of the keys held right now
+}
+
+impl KeySampler {
+    // A key went down. None if it already was: that's the OS repeating it.
+    pub fn press(&mut self, input: PlayerInput) -> Option<PlayerInput> {
+        if self.down.contains(&input) {
+            return None;
+        }
+        self.down.push(input);
+        Some(input)
+    }
+
+    // The key for `input` came up. Returns the release to send, if it has one.
+    pub fn release(&mut self, input: PlayerInput) -> Option<PlayerInput> {
+        let was_down = self.down.contains(&input);
+        self.down.retain(|&held| held != input);
+        was_down.then(|| release_of(input)).flatten()
+    }
+
+    pub fn is_down(&self, input: PlayerInput) -> bool {
+        self.down.contains(&input)
+    }
+}
+
+// The release input a held key sends when it comes up
+fn release_of(input: PlayerInput) -> Option<PlayerInput> {
+    match input {
+        PlayerInput::LHold => Some(PlayerInput::LRelease),
+        PlayerInput::RHold => Some(PlayerInput::RRelease),
+        PlayerInput::RotateHold => Some(PlayerInput::RotateRelease),
+        PlayerInput::Hold => Some(PlayerInput::HoldRelease),
+        PlayerInput::SoftDrop => Some(PlayerInput::SoftDropRelease),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::{BoardConfig, DasConfig},
+        views::BoardInstance,
+    };
+    use nannou::prelude::*;
+
+    #[test]
+    fn test_os_repeats_ignored() {
+        let config = BoardConfig {
+            gravity_interval: 10.0,
+            das: DasConfig {
+                delay: 0.2,
+                repeat: 0.1,
+                preserve_charge: false,
+            },
+            ..BoardConfig::default()
+        };
+        let new_board = || {
+            let mut board = BoardInstance::new("board1", vec2(0.0, 0.0), &config, 1).unwrap();
+            board.update(1.0 / 60.0, &[]);
+            board
+        };
+        let x = |board: &BoardInstance| board.active_piece().unwrap().position.x;
+
+        // Left held for a quarter second, the OS repeating it every frame
+        let mut keys = KeySampler::default();
+        let mut repeated = new_board();
+        for _ in 0..15 {
+            let input = keys.press(PlayerInput::LHold);
+            repeated.update(1.0 / 60.0, input.as_slice());
+        }
+        assert!(keys.is_down(PlayerInput::LHold));
+
+        // The same hold, pressed once
+        let mut held = new_board();
+        held.update(1.0 / 60.0, &[PlayerInput::LHold]);
+        for _ in 1..15 {
+            held.update(1.0 / 60.0, &[]);
+        }
+        assert_eq!(x(&repeated), x(&held));
+
+        // One step for the press and one after the DAS delay, no more
+        let start = x(&new_board());
+        assert_eq!(x(&held), start - 2);
+
+        assert_eq!(
+            keys.release(PlayerInput::LHold),
+            Some(PlayerInput::LRelease)
+        );
+        assert_eq!(keys.release(PlayerInput::LHold), None);
+        assert_eq!(
+            keys.press(PlayerInput::HardDrop),
+            Some(PlayerInput::HardDrop)
+        );
+        assert_eq!(keys.press(PlayerInput::HardDrop), None);
+    }
+}
@@ -1,9 +1,33 @@
 // src/views/mod.rs
 
+pub mod attack_router;
 pub mod background;
+pub mod board_backdrop;
 pub mod board_instance;
+pub mod board_player;
+pub mod cell_mesh;
+pub mod game_manager;
+pub mod gravity;
+pub mod key_sampler;
 pub mod piece_instance;
+pub mod render_options;
+pub mod replay;
+pub mod snapshot;
+pub mod telemetry;
 
+pub use attack_router::AttackRouter;
 pub use background::BackgroundManager;
-pub use board_instance::{BoardInstance, PlayerInput};
-pub use piece_instance::{BoardPosition, PieceInstance, RotationDirection};
+pub use board_backdrop::BoardBackdrop;
+pub use board_instance::{
+    BoardInstance, FullRow, GameState, LockOutcome, MoveDirection, PlayerInput,
+};
+pub use board_player::BoardPlayer;
+pub use cell_mesh::CellMesh;
+pub use game_manager::GameManager;
+pub use gravity::{GravityStep, GravityStrategy};
+pub use key_sampler::KeySampler;
+pub use piece_instance::{BoardPosition, BoardRect, PieceInstance, RotationDirection};
+pub use render_options::RenderOptions;
+pub use replay::{find_divergence, Divergence, Replay, ReplayScrubber};
+pub use snapshot::{BoardSnapshot, GameSnapshot, PieceSnapshot};
+pub use telemetry::{PieceSummary, Telemetry};
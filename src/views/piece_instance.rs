@@ -3,8 +3,12 @@
 //
 // Defining the individual pieces on the screen
 
-use crate::{models::PieceType, views::BoardInstance};
+use crate::{
+    models::{PieceShape, PieceType},
+    views::BoardInstance,
+};
 use nannou::prelude::*;
+use std::ops::{Deref, DerefMut};
 
 pub enum RotationDirection {
     Cw,
@@ -12,59 +16,184 @@ pub enum RotationDirection {
 }
 
 // Board position of a piece
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct BoardPosition {
     pub x: isize,
     pub y: isize,
 }
 
 impl BoardPosition {
-    // Cell origin is bottom_left. Board origin is center of board.
+    // Cell origin is bottom_left, or top_left when the board draws flipped.
+    // Board origin is center of board.
     pub fn to_screen(&self, board: &BoardInstance) -> Vec2 {
-        let half_width = (board.board.width as f32 - 1.0) * 0.5 * board.cell_size;
-        let half_height = (board.board.height as f32 - 1.0) * 0.5 * board.cell_size;
+        cells_to_screen(self.x as f32, self.y as f32, board)
+    }
+
+    // The cell under a screen point, if it's on the board
+    pub fn from_screen(point: Vec2, board: &BoardInstance) -> Option<Self> {
+        let origin = BoardPosition { x: 0, y: 0 }.to_screen(board);
+        let up = if board.flip_y() { -1.0 } else { 1.0 };
+        let pos = Self {
+            x: ((point.x - origin.x) / board.cell_size).round() as isize,
+            y: ((point.y - origin.y) * up / board.cell_size).round() as isize,
+        };
+        let on_board =
+            (0..board.board.width).contains(&pos.x) && (0..board.board.height).contains(&pos.y);
+        on_board.then_some(pos)
+    }
+}
+
+// A rect over the board in cells, for buttons and the like drawn on it:
+// x from the board's left edge and y from row 0's edge, in whole or part cells.
+// It follows the board's anchor and flip just as the cells do.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BoardRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
 
-        vec2(
-            board.location.x + self.x as f32 * board.cell_size - half_width,
-            board.location.y + self.y as f32 * board.cell_size - half_height,
+impl BoardRect {
+    // Screen center and size of the rect, for drawing it
+    pub fn to_screen(&self, board: &BoardInstance) -> (Vec2, Vec2) {
+        let (a, b) = self.screen_corners(board);
+        ((a + b) / 2.0, (b - a).abs())
+    }
+
+    // Whether a screen point falls on the rect, edges included
+    pub fn contains(&self, point: Vec2, board: &BoardInstance) -> bool {
+        let (a, b) = self.screen_corners(board);
+        let (min, max) = (a.min(b), a.max(b));
+        point.x >= min.x && point.x <= max.x && point.y >= min.y && point.y <= max.y
+    }
+
+    // Cell centers sit half a cell in from the edges
+    fn screen_corners(&self, board: &BoardInstance) -> (Vec2, Vec2) {
+        let (x, y) = (self.x - 0.5, self.y - 0.5);
+        (
+            cells_to_screen(x, y, board),
+            cells_to_screen(x + self.width, y + self.height, board),
         )
     }
 }
 
-type Cells = [(isize, isize); 4];
+// The screen point of a spot on the board in cells, whole numbers at cell
+// centers. Cell origin is bottom_left, or top_left when the board draws flipped.
+fn cells_to_screen(x: f32, y: f32, board: &BoardInstance) -> Vec2 {
+    let half_width = (board.board.width as f32 - 1.0) * 0.5 * board.cell_size;
+    let half_height = (board.board.height as f32 - 1.0) * 0.5 * board.cell_size;
+    let center = board.center();
+    let row = match board.flip_y() {
+        true => (board.board.height - 1) as f32 - y,
+        false => y,
+    };
+
+    vec2(
+        center.x + x * board.cell_size - half_width,
+        center.y + row * board.cell_size - half_height,
+    )
+}
 
+// A piece on the screen: its shape and the color it's drawn in
 #[derive(Clone)]
 pub struct PieceInstance {
-    pub typ: PieceType,
+    pub shape: PieceShape,
     pub color: Rgba,
-    pub rot_idx: usize, // rotation index
-    pub position: BoardPosition,
 }
 
 impl PieceInstance {
     pub fn new(typ: PieceType, color: Rgba, position: BoardPosition) -> Self {
         Self {
-            typ,
+            shape: PieceShape::new(typ, position),
             color,
-            rot_idx: 0,
-            position,
         }
     }
+}
+
+// Reads as its shape, so `piece.position` and the rest reach through
+impl Deref for PieceInstance {
+    type Target = PieceShape;
 
-    pub fn cells(&self) -> &Cells {
-        self.typ.get_rotation(self.rot_idx)
+    fn deref(&self) -> &PieceShape {
+        &self.shape
     }
+}
+
+impl DerefMut for PieceInstance {
+    fn deref_mut(&mut self) -> &mut PieceShape {
+        &mut self.shape
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{models::PieceSet, views::RenderOptions};
 
-    // Look up the rotation from the piece type table
-    pub fn rotate(&mut self, direction: &RotationDirection) -> &Cells {
-        let count = self.typ.rotation_count();
+    #[test]
+    fn test_board_cells() {
+        let position = BoardPosition { x: 4, y: 10 };
+        let mut piece = PieceInstance::new(PieceType::T, rgba(1.0, 1.0, 1.0, 1.0), position);
+        let pieces = PieceSet::default();
+        let at = |x, y| BoardPosition { x, y };
+        assert_eq!(
+            piece.board_cells(&pieces),
+            [at(4, 11), at(5, 11), at(6, 11), at(5, 10)]
+        );
 
-        let inx = match direction {
-            RotationDirection::Cw => (self.rot_idx + 1) % count,
-            RotationDirection::Ccw => (self.rot_idx + count - 1) % count,
+        // Follows the piece as it moves and turns
+        piece.position = BoardPosition { x: 0, y: 0 };
+        piece.rotate(&RotationDirection::Cw, &pieces);
+        assert_eq!(
+            piece.board_cells(&pieces),
+            [at(1, 0), at(1, 1), at(1, 2), at(2, 1)]
+        );
+    }
+
+    #[test]
+    fn test_board_rect_hits() {
+        use crate::{
+            config::{Anchor, BoardConfig},
+            views::BoardInstance,
+        };
+
+        // 10x20 cells of 30 points, centered on (100, -50): edges at x -50 and y -350
+        let config = BoardConfig::default();
+        let mut board = BoardInstance::new("board1", vec2(100.0, -50.0), &config, 1).unwrap();
+
+        // A button 4 cells wide and 2 high over the middle of the board
+        let button = BoardRect {
+            x: 3.0,
+            y: 9.0,
+            width: 4.0,
+            height: 2.0,
         };
+        assert_eq!(
+            button.to_screen(&board),
+            (vec2(100.0, -50.0), vec2(120.0, 60.0))
+        );
+        assert!(button.contains(vec2(100.0, -50.0), &board));
+        assert!(button.contains(vec2(41.0, -79.0), &board));
+        assert!(!button.contains(vec2(39.0, -50.0), &board));
+        assert!(!button.contains(vec2(100.0, -19.0), &board));
+
+        // Anchored by its top left corner, the board and the button move with it
+        board.set_render_options(RenderOptions {
+            anchor: Anchor::TopLeft,
+            ..RenderOptions::default()
+        });
+        assert!(!button.contains(vec2(100.0, -50.0), &board));
+        assert!(button.contains(vec2(250.0, -350.0), &board));
 
-        self.rot_idx = inx;
-        self.typ.get_rotation(inx)
+        // Flipped, y counts down from the top
+        board.set_render_options(RenderOptions {
+            anchor: Anchor::Center,
+            flip_y: true,
+            ..RenderOptions::default()
+        });
+        let top = BoardRect { y: 18.0, ..button };
+        assert!(top.contains(vec2(100.0, -320.0), &board));
+        assert!(!top.contains(vec2(100.0, 220.0), &board));
     }
 }
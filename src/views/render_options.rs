@@ -0,0 +1,51 @@
+// src/views/render_options.rs
+//
+// How boards are drawn, as set by the rendering config.
+// None of it changes how a game plays.
+
+use crate::{
+    config::{
+        AgeHeatmap, Anchor, ClearBurst, ColorDrift, DropTrail, GarbageRise, LandingGuide,
+        LockFlash, PieceGlow, RenderConfig, SpawnPreview,
+    },
+    views::BoardBackdrop,
+};
+use nannou::prelude::*;
+
+#[derive(Clone, Default)]
+pub struct RenderOptions {
+    pub backdrop: BoardBackdrop,     // drawn behind the cells
+    pub piece_glow: PieceGlow,       // highlight around the active piece
+    pub show_col_heights: bool,      // overlay bars showing col_score
+    pub age_heatmap: AgeHeatmap,     // tint cells by how long they've been filled
+    pub drop_trail: DropTrail,       // fading trail behind hard drops
+    pub clear_burst: ClearBurst,     // cleared cells burst into fragments
+    pub garbage_rise: GarbageRise,   // the stack slides up under received garbage
+    pub spawn_preview: SpawnPreview, // the next piece shown where it will spawn
+    pub lock_flash: LockFlash,       // the active piece pulses while locking
+    pub color_drift: ColorDrift,     // the board's color drifts through hue
+    pub landing_guide: LandingGuide, // shows where the active piece will land
+    pub anchor: Anchor,              // the point of a board its location gives
+    pub flip_y: bool,                // draw row 0 at the top
+}
+
+impl RenderOptions {
+    // The backdrop may be an image, so loading needs the app
+    pub fn load(app: &App, config: &RenderConfig) -> Self {
+        Self {
+            backdrop: BoardBackdrop::load(app, &config.board_background),
+            piece_glow: config.piece_glow,
+            show_col_heights: config.show_col_heights,
+            age_heatmap: config.age_heatmap,
+            drop_trail: config.drop_trail,
+            clear_burst: config.clear_burst,
+            garbage_rise: config.garbage_rise,
+            spawn_preview: config.spawn_preview,
+            lock_flash: config.lock_flash,
+            color_drift: config.color_drift,
+            landing_guide: config.landing_guide,
+            anchor: config.board_anchor,
+            flip_y: config.flip_y,
+        }
+    }
+}
@@ -0,0 +1,358 @@
+// src/views/replay.rs
+//
+// Input replays: a board's seed and the inputs fed to it on each tick.
+// Playing the inputs back through a fresh board rebuilds the game exactly.
+// The scrubber keeps a copy of the board every so often while it plays
+// through once, so seeking starts from the nearest copy, not the beginning.
+// Two replays played side by side show where their games part ways, which
+// finds nondeterminism when the same replay is played twice. For studying
+// a lost game, the scrubber also finds the board some ticks before it topped out.
+
+use crate::{
+    config::BoardConfig,
+    models::SizeError,
+    views::{BoardInstance, BoardPosition, GameState, PlayerInput},
+};
+use nannou::prelude::*;
+
+// ticks between keyframes; 10 seconds at 60 fps
+const KEYFRAME_INTERVAL: usize = 600;
+
+#[derive(Debug, Clone, Default)]
+pub struct Replay {
+    pub seed: u64,
+    ticks: Vec<(u64, f32, Vec<PlayerInput>)>, // tick, dt and inputs of each update
+}
+
+impl Replay {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            ticks: Vec::new(),
+        }
+    }
+
+    // Record one board update, stamped with current_tick after it. The stamps
+    // only go up, but needn't start at 1 if they come from another clock.
+    pub fn record(&mut self, tick: u64, dt: f32, inputs: &[PlayerInput]) {
+        self.ticks.push((tick, dt, inputs.to_vec()));
+    }
+
+    // Updates recorded up to and including the one stamped `tick`
+    fn updates_to(&self, tick: u64) -> usize {
+        self.ticks.partition_point(|&(stamp, _, _)| stamp <= tick)
+    }
+
+    pub fn len(&self) -> usize {
+        self.ticks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ticks.is_empty()
+    }
+}
+
+pub struct ReplayScrubber {
+    replay: Replay,
+    keyframes: Vec<BoardInstance>, // the board every KEYFRAME_INTERVAL ticks, from tick 0
+    current: BoardInstance,        // the board at the last tick sought
+    played: usize,                 // updates `current` has had
+    game_over: Option<u64>,        // the tick the board first topped out on
+}
+
+impl ReplayScrubber {
+    // Start a board with the replay's seed and play it through once for keyframes
+    pub fn new(
+        replay: Replay,
+        id: &str,
+        location: Vec2,
+        config: &BoardConfig,
+    ) -> Result<Self, SizeError> {
+        let mut board = BoardInstance::new(id, location, config, replay.seed)?;
+        let mut keyframes = Vec::with_capacity(replay.len() / KEYFRAME_INTERVAL + 1);
+        let mut game_over = None;
+
+        for (i, (tick, dt, inputs)) in replay.ticks.iter().enumerate() {
+            if i % KEYFRAME_INTERVAL == 0 {
+                keyframes.push(board.clone());
+            }
+            board.update(*dt, inputs);
+            if game_over.is_none() && board.game_state() == GameState::GameOver {
+                game_over = Some(*tick);
+            }
+        }
+        if replay.len() % KEYFRAME_INTERVAL == 0 {
+            keyframes.push(board.clone());
+        }
+
+        Ok(Self {
+            current: keyframes[0].clone(),
+            replay,
+            keyframes,
+            played: 0,
+            game_over,
+        })
+    }
+
+    // The tick the board topped out on, if it did
+    pub fn game_over_tick(&self) -> Option<u64> {
+        self.game_over
+    }
+
+    // The board `ticks` updates before it topped out, to see what went wrong.
+    // None if the game never ended, or ended sooner than that.
+    pub fn before_game_over(&mut self, ticks: u64) -> Option<&BoardInstance> {
+        let tick = self.game_over?.checked_sub(ticks)?;
+        Some(self.seek(tick))
+    }
+
+    // The board after the update stamped `tick`, or the last one before it,
+    // held at the replay's last tick
+    pub fn seek(&mut self, tick: u64) -> &BoardInstance {
+        let updates = self.replay.updates_to(tick);
+
+        // Carry on from where we are if that's closer than the keyframe
+        let keyframe = updates / KEYFRAME_INTERVAL;
+        if updates < self.played || self.played < keyframe * KEYFRAME_INTERVAL {
+            self.current = self.keyframes[keyframe].clone();
+            self.played = keyframe * KEYFRAME_INTERVAL;
+        }
+
+        for (_, dt, inputs) in &self.replay.ticks[self.played..updates] {
+            self.current.update(*dt, inputs);
+        }
+        self.played = updates;
+        &self.current
+    }
+
+    // The stamp of the last update played, 0 before the first
+    pub fn tick(&self) -> u64 {
+        self.played
+            .checked_sub(1)
+            .map_or(0, |i| self.replay.ticks[i].0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.replay.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.replay.is_empty()
+    }
+}
+
+// Where two runs first differ. The falling pieces are in `cells`, so
+// two pieces in different places show up even with the stacks alike.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    pub tick: u64,                         // the first run's stamp for the update
+    pub cells: Vec<(BoardPosition, bool)>, // cells that differ, as in the first run
+}
+
+// Play two replays through fresh boards in lockstep, up to the end of the
+// shorter one. None if every tick looks the same on both boards.
+pub fn find_divergence(
+    a: &Replay,
+    b: &Replay,
+    id: &str,
+    config: &BoardConfig,
+) -> Result<Option<Divergence>, SizeError> {
+    let mut board_a = BoardInstance::new(id, vec2(0.0, 0.0), config, a.seed)?;
+    let mut board_b = BoardInstance::new(id, vec2(0.0, 0.0), config, b.seed)?;
+
+    for ((tick, dt_a, inputs_a), (_, dt_b, inputs_b)) in a.ticks.iter().zip(&b.ticks) {
+        board_a.update(*dt_a, inputs_a);
+        board_b.update(*dt_b, inputs_b);
+
+        if board_a.frame() != board_b.frame() || board_a.game_state() != board_b.game_state() {
+            return Ok(Some(Divergence {
+                tick: *tick,
+                cells: diff_cells(&board_a, &board_b),
+            }));
+        }
+    }
+    Ok(None)
+}
+
+// Cells filled differently on the two boards, with their value on the first,
+// bottom row first. The falling pieces count as filled.
+fn diff_cells(a: &BoardInstance, b: &BoardInstance) -> Vec<(BoardPosition, bool)> {
+    let (pieces_a, pieces_b) = (piece_cells(a), piece_cells(b));
+    let board = a.board();
+    (0..board.height)
+        .flat_map(|y| (0..board.width).map(move |x| BoardPosition { x, y }))
+        .map(|pos| {
+            let filled_a = a.board().is_cell_filled(pos) || pieces_a.contains(&pos);
+            let filled_b = b.board().is_cell_filled(pos) || pieces_b.contains(&pos);
+            (pos, filled_a, filled_b)
+        })
+        .filter(|&(_, filled_a, filled_b)| filled_a != filled_b)
+        .map(|(pos, filled_a, _)| (pos, filled_a))
+        .collect()
+}
+
+fn piece_cells(board: &BoardInstance) -> Vec<BoardPosition> {
+    board
+        .active_piece()
+        .map(|piece| piece.board_cells(board.board().pieces()).to_vec())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{BoardConfig, Randomizer};
+
+    fn test_config() -> BoardConfig {
+        BoardConfig {
+            randomizer: Randomizer::Bag,
+            ..BoardConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_seek_matches_playthrough() {
+        // A game of shifting, spinning and dropping pieces
+        let mut replay = Replay::new(7);
+        let inputs = [
+            PlayerInput::L,
+            PlayerInput::Rotate,
+            PlayerInput::R,
+            PlayerInput::R,
+            PlayerInput::HardDrop,
+        ];
+        for tick in 0..2000 {
+            let input = (tick % 7 == 0).then(|| inputs[tick / 7 % inputs.len()]);
+            replay.record(tick as u64 + 1, 1.0 / 60.0, input.as_slice());
+        }
+
+        let config = test_config();
+        let mut scrubber =
+            ReplayScrubber::new(replay.clone(), "board1", vec2(0.0, 0.0), &config).unwrap();
+
+        let mut board = BoardInstance::new("board1", vec2(0.0, 0.0), &config, replay.seed).unwrap();
+        for (_, dt, inputs) in &replay.ticks[..1300] {
+            board.update(*dt, inputs);
+        }
+
+        // Seeking past a keyframe, back before it, and forward again all agree
+        for tick in [1900, 1300, 100, 1300] {
+            scrubber.seek(tick);
+        }
+        let sought = scrubber.seek(1300);
+        assert!(board.score() > 0);
+        assert_eq!(sought.frame(), board.frame());
+        assert_eq!(sought.stats(), board.stats());
+        assert_eq!(sought.game_state(), board.game_state());
+        assert_eq!(scrubber.tick(), 1300);
+    }
+
+    #[test]
+    fn test_find_divergence() {
+        let mut replay = Replay::new(7);
+        for tick in 0..600 {
+            let input = (tick % 60 == 0).then_some(PlayerInput::HardDrop);
+            replay.record(tick as u64 + 1, 1.0 / 60.0, input.as_slice());
+        }
+        let config = test_config();
+
+        // The same replay twice plays the same game
+        let same = find_divergence(&replay, &replay, "board1", &config).unwrap();
+        assert_eq!(same, None);
+
+        // A stray hard drop on tick 330 is where the games part
+        let mut strayed = replay.clone();
+        strayed.ticks[330].2.push(PlayerInput::HardDrop);
+        let divergence = find_divergence(&replay, &strayed, "board1", &config)
+            .unwrap()
+            .unwrap();
+        assert_eq!(divergence.tick, 331);
+
+        // Only the falling piece differs until the stray drop locks
+        let play = |replay: &Replay| {
+            let mut board = BoardInstance::new("board1", vec2(0.0, 0.0), &config, 7).unwrap();
+            for (_, dt, inputs) in &replay.ticks[..331] {
+                board.update(*dt, inputs);
+            }
+            board
+        };
+        let (falling, dropped) = (play(&replay), play(&strayed));
+        assert!(falling.board().diff(dropped.board()).is_empty());
+        assert_ne!(
+            falling.active_piece().map(|piece| piece.position),
+            dropped.active_piece().map(|piece| piece.position)
+        );
+
+        // so the difference is that piece, four cells where it falls
+        // and four where the stray drop put it
+        assert_eq!(divergence.cells.len(), 8);
+        assert_eq!(
+            divergence
+                .cells
+                .iter()
+                .filter(|&&(_, filled)| filled)
+                .count(),
+            4
+        );
+    }
+
+    #[test]
+    fn test_before_game_over() {
+        // Every piece hard dropped where it spawns, until the stack tops out
+        let mut replay = Replay::new(3);
+        for tick in 0..3000 {
+            let input = (tick % 60 == 0).then_some(PlayerInput::HardDrop);
+            replay.record(tick as u64 + 1, 1.0 / 60.0, input.as_slice());
+        }
+        let config = test_config();
+        let mut scrubber =
+            ReplayScrubber::new(replay.clone(), "board1", vec2(0.0, 0.0), &config).unwrap();
+        let game_over = scrubber.game_over_tick().unwrap();
+        assert!(game_over > KEYFRAME_INTERVAL as u64);
+
+        // Half a second before, straight from the start, the game was still on
+        let mut board = BoardInstance::new("board1", vec2(0.0, 0.0), &config, replay.seed).unwrap();
+        for (_, dt, inputs) in &replay.ticks[..game_over as usize - 30] {
+            board.update(*dt, inputs);
+        }
+        let before = scrubber.before_game_over(30).unwrap();
+        assert_ne!(before.game_state(), GameState::GameOver);
+        assert_eq!(before.frame(), board.frame());
+        assert_eq!(before.game_state(), board.game_state());
+        assert_eq!(scrubber.tick(), game_over - 30);
+
+        // The tick itself is the top out, and there's nothing before the start
+        assert_eq!(
+            scrubber.before_game_over(0).unwrap().game_state(),
+            GameState::GameOver
+        );
+        assert!(scrubber.before_game_over(game_over + 1).is_none());
+    }
+
+    #[test]
+    fn test_seek_by_stamp() {
+        // Recorded by a board that joined when the manager was on tick 100
+        let mut replay = Replay::new(5);
+        for tick in 0..200 {
+            let input = (tick % 40 == 0).then_some(PlayerInput::HardDrop);
+            replay.record(101 + tick as u64, 1.0 / 60.0, input.as_slice());
+        }
+        let config = test_config();
+        let mut scrubber =
+            ReplayScrubber::new(replay.clone(), "board1", vec2(0.0, 0.0), &config).unwrap();
+
+        // Stamp 150 is the board's 50th update
+        let mut board = BoardInstance::new("board1", vec2(0.0, 0.0), &config, replay.seed).unwrap();
+        for (_, dt, inputs) in &replay.ticks[..50] {
+            board.update(*dt, inputs);
+        }
+        assert_eq!(scrubber.seek(150).frame(), board.frame());
+        assert_eq!(scrubber.tick(), 150);
+
+        // Before the first stamp is the fresh board, past the last is the end
+        assert_eq!(scrubber.seek(100).current_tick(), 0);
+        assert_eq!(scrubber.tick(), 0);
+        assert_eq!(scrubber.seek(1000).current_tick(), 200);
+        assert_eq!(scrubber.tick(), 300);
+    }
+}
@@ -0,0 +1,139 @@
+// src/views/snapshot.rs
+//
+// Serializable snapshot of every board, for dashboards and other outside viewers.
+// Rows are listed bottom row first, matching board y-coordinates:
+// '#' is a filled cell and '.' an empty one.
+
+use crate::{
+    utils::RngState,
+    views::{BoardInstance, BoardPosition, GameState},
+};
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GameSnapshot {
+    pub attract_mode: bool,
+    pub boards: Vec<BoardSnapshot>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BoardSnapshot {
+    pub id: String,
+    pub width: isize,
+    pub height: isize,
+    pub score: usize,
+    pub state: &'static str,
+    pub rows: Vec<String>,
+    pub active_piece: Option<PieceSnapshot>,
+    pub rng: RngState, // where the piece generator is, to carry on the same game
+    pub next_piece: String, // dealt already, so the generator is past it
+    pub bag: Vec<String>, // types left in the 7-bag, in the order it deals from
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PieceSnapshot {
+    pub typ: String,
+    pub rot_idx: usize,
+    pub x: isize,
+    pub y: isize,
+}
+
+impl BoardSnapshot {
+    pub fn capture(board: &BoardInstance) -> Self {
+        let grid = board.board();
+        let rows = (0..grid.height)
+            .map(|y| {
+                (0..grid.width)
+                    .map(|x| {
+                        if grid.is_cell_filled(BoardPosition { x, y }) {
+                            '#'
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            id: board.id.to_owned(),
+            width: grid.width,
+            height: grid.height,
+            score: board.score(),
+            state: state_name(board.game_state()),
+            rows,
+            active_piece: board.active_piece().map(|piece| PieceSnapshot {
+                typ: format!("{:?}", piece.typ),
+                rot_idx: piece.rot_idx,
+                x: piece.position.x,
+                y: piece.position.y,
+            }),
+            rng: board.rng_state(),
+            next_piece: format!("{:?}", board.dealt_piece()),
+            bag: board
+                .bag_remaining()
+                .iter()
+                .map(|typ| format!("{:?}", typ))
+                .collect(),
+        }
+    }
+}
+
+fn state_name(state: GameState) -> &'static str {
+    match state {
+        GameState::Ready => "ready",
+        GameState::Falling => "falling",
+        GameState::Locking { .. } => "locking",
+        GameState::Clearing => "clearing",
+        GameState::GameOver => "game_over",
+        GameState::Frozen => "frozen",
+        GameState::Paused => "paused",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::BoardConfig;
+    use nannou::prelude::*;
+
+    #[test]
+    fn test_snapshot_json_schema() {
+        let config = BoardConfig {
+            width: 4,
+            height: 3,
+            ..BoardConfig::default()
+        };
+        let mut board = BoardInstance::new("board1", vec2(0.0, 0.0), &config, 1).unwrap();
+        board.update(1.0 / 60.0, &[]);
+
+        let snapshot = GameSnapshot {
+            attract_mode: false,
+            boards: vec![BoardSnapshot::capture(&board)],
+        };
+        let json = serde_json::to_value(&snapshot).unwrap();
+
+        assert_eq!(json["attract_mode"], false);
+        let board_json = &json["boards"][0];
+        assert_eq!(board_json["id"], "board1");
+        assert_eq!(board_json["width"], 4);
+        assert_eq!(board_json["height"], 3);
+        assert_eq!(board_json["score"], 0);
+        assert_eq!(board_json["state"], "falling");
+        assert_eq!(
+            board_json["rows"].as_array().map(|rows| rows.len()),
+            Some(3)
+        );
+        assert_eq!(board_json["rows"][0], "....");
+
+        let piece = &board_json["active_piece"];
+        assert!(piece["typ"].is_string());
+        assert_eq!(piece["rot_idx"], 0);
+        assert!(piece["x"].is_i64());
+        assert!(piece["y"].is_i64());
+        assert_eq!(board_json["rng"]["seed"], 1);
+        assert!(board_json["rng"]["words"].as_u64().unwrap() > 0);
+        assert!(board_json["next_piece"].is_string());
+        assert!(board_json["bag"].as_array().unwrap().is_empty());
+    }
+}
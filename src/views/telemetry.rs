@@ -0,0 +1,102 @@
+// src/views/telemetry.rs
+//
+// Everything a board shows the outside world, read in one call.
+// Cheap enough to take every frame: the only thing not copied is
+// the column heights, which borrow the board's own.
+
+use crate::{
+    models::PieceType,
+    views::{BoardInstance, BoardPosition, GameState},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Telemetry<'a> {
+    pub score: usize,
+    pub level: usize,
+    pub lines: usize,
+    pub game_state: GameState,
+    pub active_piece: Option<PieceSummary>,
+    pub col_heights: &'a [isize],
+    pub holes: usize,
+    pub combo: usize,
+    pub back_to_back: bool, // the last clear was a tetris or t-spin
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PieceSummary {
+    pub typ: PieceType,
+    pub rot_idx: usize,
+    pub position: BoardPosition,
+}
+
+impl<'a> Telemetry<'a> {
+    pub fn capture(board: &'a BoardInstance) -> Self {
+        let stats = board.stats();
+        Self {
+            score: board.score(),
+            level: stats.level(),
+            lines: stats.lines,
+            game_state: board.game_state(),
+            active_piece: board.active_piece().map(|piece| PieceSummary {
+                typ: piece.typ,
+                rot_idx: piece.rot_idx,
+                position: piece.position,
+            }),
+            col_heights: board.board().col_score_all(),
+            holes: board.board().hole_count(),
+            combo: stats.combo(),
+            back_to_back: stats.back_to_back(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::BoardConfig, views::PlayerInput};
+    use nannou::prelude::*;
+
+    #[test]
+    fn test_matches_getters() {
+        let config = BoardConfig {
+            width: 6,
+            height: 12,
+            ..BoardConfig::default()
+        };
+        let mut board = BoardInstance::new("board1", vec2(0.0, 0.0), &config, 1).unwrap();
+        board.update(1.0 / 60.0, &[]);
+
+        // A row with a covered hole, then a piece dropped on top
+        let mut row = vec![true; 6];
+        row[2] = false;
+        board
+            .board_mut()
+            .add_garbage(&[row, vec![false, false, true, false, false, false]]);
+        board.update(1.0 / 60.0, &[PlayerInput::HardDrop]);
+        board.update(1.0 / 60.0, &[]);
+
+        let telemetry = board.telemetry();
+        let stats = board.stats();
+        assert_eq!(telemetry.score, board.score());
+        assert_eq!(telemetry.level, stats.level());
+        assert_eq!(telemetry.lines, stats.lines);
+        assert_eq!(telemetry.game_state, board.game_state());
+        assert_eq!(
+            telemetry.col_heights,
+            board.board().col_score_all().as_slice()
+        );
+        assert_eq!(telemetry.holes, board.board().hole_count());
+        assert_eq!(telemetry.combo, stats.combo());
+        assert_eq!(telemetry.back_to_back, stats.back_to_back());
+
+        let piece = board.active_piece().unwrap();
+        let summary = telemetry.active_piece.unwrap();
+        assert_eq!(summary.typ, piece.typ);
+        assert_eq!(summary.rot_idx, piece.rot_idx);
+        assert_eq!(summary.position, piece.position);
+
+        // The garbage left a hole, and nothing has cleared yet
+        assert!(telemetry.holes >= 1);
+        assert_eq!((telemetry.lines, telemetry.level), (0, 1));
+    }
+}